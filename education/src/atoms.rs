@@ -0,0 +1,157 @@
+// Two-level atoms: an active medium that absorbs, amplifies and lases.
+//
+// N atoms per m³ share the lower level 1 and the upper level 2, ħω0 apart. Their coherence
+// is a polarization oscillating at ω0 whose strength follows the population difference
+// (the rate-equation limit of the Maxwell-Bloch equations, Chang & Taflove 2004):
+//   P'' + γ P' + ω0² P = κ (N1 − N2) E,        κ = 6π ε0 c³/(ω0² τ)
+//   dN2/dt = E·P'/(ħω0) − N2/τ + R N1,         N1 = N − N2
+// E·P' is the power the field gives the atoms: it lifts them (absorption) while N1 > N2
+// and is returned by stimulated emission once the pump R has inverted them. N2/τ is
+// spontaneous emission at the rate that also fixes the dipole strength κ; its light goes
+// into modes the grid does not hold, so it only empties the upper level.
+//
+// Small-signal light at ω0 changes amplitude by e^{−α z} with α = κ (N1 − N2)/(2 ε0 γ c):
+// absorption for α > 0, gain for α < 0. In a cavity that loses amplitude at a rate ℓ, gain
+// wins once (N2 − N1) κ/(2 ε0 γ) > ℓ; above that threshold the field grows until stimulated
+// emission clamps the inversion there.
+
+use std::f64::consts::PI;
+
+use ndarray::Array3;
+use num_complex::Complex;
+
+use crate::material::{MaterialMap, PoleCoefficients};
+use crate::{C, EPSILON_0, HBAR};
+
+/// A species of two-level atoms, pumped at `pump` from the lower level to the upper.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwoLevel {
+    pub density: f64,   // N, m⁻³
+    pub resonance: f64, // ω0, rad/s
+    pub linewidth: f64, // γ, rad/s (full width of the line)
+    pub lifetime: f64,  // τ of spontaneous emission, s
+    pub pump: f64,      // R, s⁻¹
+}
+
+impl TwoLevel {
+    /// κ, the coupling of P to E per unit of population difference.
+    pub fn coupling(&self) -> f64 {
+        6.0*PI*EPSILON_0*C.powi(3)/(self.resonance.powi(2)*self.lifetime)
+    }
+
+    /// N2 without light, where pumping and spontaneous emission balance: N Rτ/(1 + Rτ).
+    pub fn excited(&self) -> f64 {
+        let r = self.pump*self.lifetime;
+        self.density*r/(1.0 + r)
+    }
+
+    /// Contribution to ε(ω) of small signals about the unlit populations.
+    pub fn susceptibility(&self, omega: f64) -> Complex<f64> {
+        let difference = self.density - 2.0*self.excited();
+        let w0 = self.resonance;
+        self.coupling()*difference/EPSILON_0/(w0*w0 - omega*omega + Complex::i()*self.linewidth*omega)
+    }
+
+    /// Amplitude attenuation α per metre at ω0 for the unlit populations; negative is gain.
+    pub fn attenuation(&self) -> f64 {
+        self.coupling()*(self.density - 2.0*self.excited())/(2.0*EPSILON_0*self.linewidth*C)
+    }
+
+    pub(crate) fn valid(&self) -> bool {
+        let positive = |v: f64| v > 0.0 && v.is_finite();
+        self.density >= 0.0 && positive(self.resonance) && self.linewidth >= 0.0
+            && positive(self.lifetime) && self.pump >= 0.0
+    }
+}
+
+/// The atoms of one cell.
+pub(crate) struct AtomCell {
+    species: TwoLevel,
+    /// N2, m⁻³.
+    pub upper: f64,
+    /// The polarization update for the current populations.
+    coefficients: PoleCoefficients,
+    /// P^n, P^{n−1} and E^{n−1}, per component.
+    polarization: [f64; 3],
+    previous: [f64; 3],
+    field: [f64; 3],
+    /// E·ΔP summed over the components during a step, J/m³.
+    work: f64,
+}
+
+impl AtomCell {
+    fn new(species: TwoLevel, dt: f64) -> Self {
+        let mut cell = AtomCell {
+            species,
+            upper: species.excited(),
+            coefficients: PoleCoefficients { a: 0.0, b: 0.0, g: 0.0, h: 0.0, k: 0.0 },
+            polarization: [0.0; 3],
+            previous: [0.0; 3],
+            field: [0.0; 3],
+            work: 0.0,
+        };
+        cell.couple(dt);
+        cell
+    }
+
+    pub fn density(&self) -> f64 {
+        self.species.density
+    }
+
+    /// The polarization update for the populations as they are.
+    fn couple(&mut self, dt: f64) {
+        let s = &self.species;
+        let weight = s.coupling()*(s.density - 2.0*self.upper)/EPSILON_0;
+        self.coefficients = PoleCoefficients::second_order(dt, s.linewidth, s.resonance.powi(2), weight);
+    }
+
+    /// The terms of the E update of component `c` from E^n, as for a dispersive pole: g,
+    /// and the change of P that E^{n+1} does not drive.
+    pub fn drive(&self, c: usize, e: f64) -> (f64, f64) {
+        let k = &self.coefficients;
+        (k.g, (k.a - 1.0)*self.polarization[c] + k.b*self.previous[c] + k.k*self.field[c] + k.h*e)
+    }
+
+    /// Moves P of component `c` to step n + 1 once E went from `e` to `next`, and books
+    /// the work done on it.
+    pub fn advance(&mut self, c: usize, e: f64, next: f64) {
+        let k = &self.coefficients;
+        let p = self.polarization[c];
+        self.polarization[c] = k.a*p + k.b*self.previous[c] + k.g*next + k.h*e + k.k*self.field[c];
+        self.previous[c] = p;
+        self.field[c] = e;
+        self.work += 0.5*(e + next)*(self.polarization[c] - p);
+    }
+
+    /// Moves N2 over the step with the work the field did, the pump and spontaneous
+    /// emission, the last two taken at the mean of N2^n and N2^{n+1}.
+    pub fn relax(&mut self, dt: f64) {
+        let s = self.species;
+        let rate = 0.5*dt*(1.0/s.lifetime + s.pump);
+        let lifted = self.work/(HBAR*s.resonance);
+        self.upper = ((1.0 - rate)*self.upper + lifted + dt*s.pump*s.density)/(1.0 + rate);
+        self.upper = self.upper.clamp(0.0, s.density);
+        self.work = 0.0;
+        self.couple(dt);
+    }
+}
+
+/// The cells of a grid with atoms and where they are.
+pub(crate) struct Atoms {
+    pub slots: Array3<Option<usize>>,
+    pub cells: Vec<AtomCell>,
+}
+
+impl Atoms {
+    /// The atoms of `map` in their unlit populations; None if there are none.
+    pub fn new(map: &MaterialMap, dt: f64) -> Option<Self> {
+        let mut cells = Vec::new();
+        let slots = map.cells.map(|&index| {
+            map.materials[index].atoms.map(|species| {
+                cells.push(AtomCell::new(species, dt));
+                cells.len() - 1
+            })
+        });
+        (!cells.is_empty()).then_some(Atoms { slots, cells })
+    }
+}
@@ -0,0 +1,206 @@
+// Boundary conditions of the Yee grid, one per face.
+//
+// On an axis that is not periodic the physical domain runs from node 0 to node N−1, so the
+// faces carry the tangential E components of their nodes; samples staggered past node N−1
+// lie outside and stay zero. Each face is one of
+//   periodic   indices wrap (both faces of the axis)
+//   PEC        tangential E = 0 on the face (perfect electric conductor)
+//   PMC        tangential H = 0 on the face, by an odd image of tangential B beyond it
+//   Mur        first-order absorbing: ∂_t F = ±v ∂_n F on the face, exact at normal incidence
+//   PML        convolutional PML (Roden & Gedney 2000) inside the face, backed by PEC
+// The CPML replaces each derivative ∂_u across its layer by ∂_u/κ_u + ψ_u with the
+// recursive convolution
+//   ψ^{n} = b ψ^{n−1} + a ∂_u F,   b = exp(−(σ/κ + α) Δt/ε0),   a = σ (b − 1)/(σκ + κ²α)
+// graded with the depth d into the layer of thickness L:
+//   σ = σ_max (d/L)^m,   κ = 1 + (κ_max − 1)(d/L)^m,   α = α_max (1 − d/L)
+// with σ_max = scale · 0.8 (m+1)/(η0 Δx), the usual optimum.
+
+use std::error::Error;
+use std::fmt;
+
+use ndarray::Array3;
+
+use crate::{C, EPSILON_0, MU_0};
+
+/// Grading of a convolutional PML.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PmlGrading {
+    pub order: f64,       // polynomial order m of σ and κ
+    pub sigma_scale: f64, // σ_max in units of the optimum 0.8 (m+1)/(η0 Δx)
+    pub kappa_max: f64,
+    pub alpha_max: f64,   // S/m, shifts the pole to damp evanescent and low-frequency waves
+}
+
+impl Default for PmlGrading {
+    fn default() -> Self {
+        PmlGrading { order: 3.0, sigma_scale: 1.0, kappa_max: 1.0, alpha_max: 0.0 }
+    }
+}
+
+/// Condition on one face of the grid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Boundary {
+    Periodic,
+    ElectricConductor,
+    MagneticConductor,
+    Mur,
+    Pml { thickness: usize, grading: PmlGrading },
+}
+
+/// Low or high face of an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Low,
+    High,
+}
+
+/// The six faces: `faces[axis][side]`, axes x, y, z.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Boundaries {
+    pub faces: [[Boundary; 2]; 3],
+}
+
+impl Default for Boundaries {
+    fn default() -> Self {
+        Boundaries::uniform(Boundary::Periodic)
+    }
+}
+
+impl Boundaries {
+    /// The same condition on every face.
+    pub fn uniform(boundary: Boundary) -> Self {
+        Boundaries { faces: [[boundary; 2]; 3] }
+    }
+
+    /// `self` with `boundary` on both faces of `axis`.
+    pub fn with_axis(mut self, axis: usize, boundary: Boundary) -> Self {
+        self.faces[axis] = [boundary; 2];
+        self
+    }
+
+    /// `self` with `boundary` on one face.
+    pub fn with_face(mut self, axis: usize, side: Side, boundary: Boundary) -> Self {
+        self.faces[axis][side as usize] = boundary;
+        self
+    }
+
+    pub fn face(&self, axis: usize, side: Side) -> Boundary {
+        self.faces[axis][side as usize]
+    }
+
+    pub fn periodic(&self, axis: usize) -> bool {
+        self.faces[axis][0] == Boundary::Periodic
+    }
+
+    /// Checks the faces against a grid of `shape` cells.
+    pub fn validate(&self, shape: [usize; 3]) -> Result<(), BoundaryError> {
+        for (axis, faces) in self.faces.iter().enumerate() {
+            let periodic = faces.iter().filter(|&&f| f == Boundary::Periodic).count();
+            if periodic == 1 {
+                return Err(BoundaryError(format!("axis {} is periodic on one face only", axis)));
+            }
+            if periodic == 0 && shape[axis] < 2 {
+                return Err(BoundaryError(format!("axis {} needs two nodes for its faces", axis)));
+            }
+            let layers: usize = faces.iter().map(|f| match f {
+                Boundary::Pml { thickness, .. } => *thickness,
+                _ => 0,
+            }).sum();
+            if layers > 0 && layers + 2 > shape[axis] {
+                return Err(BoundaryError(format!(
+                    "PML of {} cells leaves no interior on axis {} of {} cells", layers, axis, shape[axis])));
+            }
+            for face in faces {
+                if let Boundary::Pml { thickness, grading } = face {
+                    if *thickness == 0 || !(grading.order >= 0.0 && grading.sigma_scale >= 0.0
+                                            && grading.kappa_max >= 1.0 && grading.alpha_max >= 0.0) {
+                        return Err(BoundaryError(format!("invalid PML on axis {}: {:?}", axis, face)));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn any(&self, matches: impl Fn(&Boundary) -> bool) -> bool {
+        self.faces.iter().flatten().any(matches)
+    }
+}
+
+/// Faces that do not fit together or into the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundaryError(pub String);
+
+impl fmt::Display for BoundaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for BoundaryError {}
+
+/// CPML coefficients along one axis, at the nodes or at the half nodes.
+#[derive(Debug, Clone)]
+pub(crate) struct Profile {
+    pub b: Vec<f64>,
+    pub a: Vec<f64>,
+    pub inv_kappa: Vec<f64>,
+}
+
+impl Profile {
+    /// Coefficients of the samples at x = (i + offset) Δx, i < n, between the faces at
+    /// nodes 0 and n − 1.
+    fn new(faces: &[Boundary; 2], n: usize, offset: f64, dx: f64, dt: f64) -> Self {
+        let mut profile = Profile { b: vec![0.0; n], a: vec![0.0; n], inv_kappa: vec![1.0; n] };
+        let eta0 = (MU_0/EPSILON_0).sqrt();
+        for i in 0..n {
+            let x = i as f64 + offset;
+            for (side, face) in faces.iter().enumerate() {
+                let Boundary::Pml { thickness, grading } = face else { continue };
+                let depth = if side == 0 { *thickness as f64 - x } else { x - (n - 1 - thickness) as f64 };
+                if depth <= 0.0 {
+                    continue;
+                }
+                let r = (depth/ *thickness as f64).min(1.0);
+                let graded = r.powf(grading.order);
+                let sigma = grading.sigma_scale*0.8*(grading.order + 1.0)/(eta0*dx)*graded;
+                let kappa = 1.0 + (grading.kappa_max - 1.0)*graded;
+                let alpha = grading.alpha_max*(1.0 - r);
+                let b = (-(sigma/kappa + alpha)*dt/EPSILON_0).exp();
+                profile.b[i] = b;
+                profile.a[i] = if sigma > 0.0 { sigma*(b - 1.0)/(sigma*kappa + kappa*kappa*alpha) } else { 0.0 };
+                profile.inv_kappa[i] = 1.0/kappa;
+            }
+        }
+        profile
+    }
+}
+
+/// Auxiliary fields and coefficients of the CPML.
+pub(crate) struct Cpml {
+    pub nodes: [Profile; 3],
+    pub halves: [Profile; 3],
+    /// ψ of the E and B updates: `[component][0]` for the derivative along the next axis
+    /// (y for x, ...), `[component][1]` along the one after.
+    pub psi_e: [[Array3<f64>; 2]; 3],
+    pub psi_b: [[Array3<f64>; 2]; 3],
+}
+
+impl Cpml {
+    pub fn new(boundaries: &Boundaries, shape: [usize; 3], dx: f64, dt: f64) -> Self {
+        let profile = |axis: usize, offset| Profile::new(&boundaries.faces[axis], shape[axis], offset, dx, dt);
+        let zeros = || Array3::zeros((shape[0], shape[1], shape[2]));
+        Cpml {
+            nodes: [profile(0, 0.0), profile(1, 0.0), profile(2, 0.0)],
+            halves: [profile(0, 0.5), profile(1, 0.5), profile(2, 0.5)],
+            psi_e: std::array::from_fn(|_| [zeros(), zeros()]),
+            psi_b: std::array::from_fn(|_| [zeros(), zeros()]),
+        }
+    }
+}
+
+/// The Mur coefficient (v Δt − Δx)/(v Δt + Δx) at phase velocity c/n.
+pub(crate) fn mur_coefficient(refractive_index: f64, dx: f64, dt: f64) -> f64 {
+    let v = C/refractive_index;
+    (v*dt - dx)/(v*dt + dx)
+}
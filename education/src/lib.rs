@@ -0,0 +1,17 @@
+//! Electromagnetic fields on a lattice: the FDTD solver and the physics around it that
+//! the `education` binary drives.
+
+pub mod atoms;
+pub mod boundary;
+pub mod material;
+pub mod source;
+pub mod yee;
+
+/// Physical constants (SI units)
+pub const C: f64 = 3.0e8;   // Speed of light
+pub const EPSILON_0: f64 = 8.854187817e-12;
+pub const MU_0: f64 = 1.0/(EPSILON_0*C*C); // so that c² = 1/(ε0 μ0) holds exactly
+pub const HBAR: f64 = 1.054571817e-34;
+
+/// Lattice spacing, 1 µm
+pub const LATTICE_SPACING: f64 = 1e-6;
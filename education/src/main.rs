@@ -1,216 +1,93 @@
-use ndarray::prelude::*;
-use ndarray::azip;
-use num_complex::Complex;
-use rand::SeedableRng;
-use rand::{rngs::StdRng, Rng};
 use std::f64::consts::PI;
 use image::{RgbImage, Rgb};
 use std::fs::File;
 use std::io::Write;
 
-/// Physical constants (SI units) and parameters
-const C: f64 = 3.0e8;   // Speed of light
-const EPSILON_0: f64 = 8.854187817e-12;
-const MU_0: f64 = 1.2566370614e-6;
-
-/// Lattice configuration
-const LATTICE_SIZE: usize = 5;
-const TIME_STEPS: usize = 10000; // fewer steps to fit nicely
-const DELTA_T: f64 = 5.39 * 1e-44; // 5.39 * 10e44
-const LATTICE_SPACING: f64 = 1e-6; // 1 µm spacing
-
-/// Material (Water) refractive index approximation
-const N_CARBON: f64 = 1.333;
-
-/// Noise and interaction parameters
-const NOISE_LEVEL: f64 = 1e-4;
-const INTENSITY_THRESHOLD: f64 = 1e-8;
-const Z_AXIS_BOOST_INTERVAL: usize = 100;
-const PHASE_SHIFT_FACTOR: f64 = 1.33;
-
-/// Represent matter states
-#[derive(Clone, Copy, Debug)]
-struct MatterCell {
-    active: bool,
-    lifetime: f64,
-}
-
-impl MatterCell {
-    fn new(rng: &mut StdRng) -> Self {
-        Self {
-            active: rng.gen_bool(0.5),
-            lifetime: 0.0,
-        }
-    }
-}
+use education::atoms::TwoLevel;
+use education::material::{Material, MaterialMap, Shape};
+use education::source::{Source, Waveform};
+use education::yee::{self, YeeGrid};
+use education::{C, LATTICE_SPACING, MU_0};
+
+/// Lattice configuration: the dipole a wavelength from every face, for two lifetimes of
+/// the atoms
+const LATTICE_SIZE: usize = 2*DIPOLE_CELLS;
+const TIME_STEPS: usize = 2000;
+
+/// Angular frequency of 500 nm light, at which refractive indices are quoted
+const REFERENCE_OMEGA: f64 = 2.0*PI*C/500e-9;
+
+/// The drive: a Ricker wavelet of current along z at the centre, peaking at a wavelength
+/// of `DIPOLE_CELLS` cells
+const DIPOLE_MOMENT: f64 = 1e-18; // A m
+const DIPOLE_CELLS: usize = 20;
+const DIPOLE_WAVELENGTH: f64 = DIPOLE_CELLS as f64*LATTICE_SPACING;
+const DIPOLE_FREQUENCY: f64 = 2.0*PI*C/DIPOLE_WAVELENGTH;
+
+/// The |E| (V/m) drawn at full brightness: the far field μ0 ω I l/(4π r) of the dipole a
+/// wavelength away in vacuum, so that its radiation shows and its near field saturates
+const DISPLAY_FIELD: f64 = MU_0*DIPOLE_FREQUENCY*DIPOLE_MOMENT/(4.0*PI*DIPOLE_WAVELENGTH);
+
+/// Matter: pumped two-level atoms dissolved in a sphere of water at the centre, resonant
+/// with the drive. Rτ = 2 keeps a third of them excited without light.
+const GAIN_MEDIUM: TwoLevel = TwoLevel {
+    density: 1e16,
+    resonance: DIPOLE_FREQUENCY,
+    linewidth: DIPOLE_FREQUENCY/20.0,
+    lifetime: 2e-12,
+    pump: 1e12,
+};
+const GAIN_RADIUS: f64 = 2.0*LATTICE_SPACING;
 
 /// Simulation lattice
 struct SimulationLattice {
     size: usize,
-    rng: StdRng,
-
-    e_x: Array3<f64>,
-    e_y: Array3<f64>,
-    e_z: Array3<f64>,
-    b_x: Array3<f64>,
-    b_y: Array3<f64>,
-    b_z: Array3<f64>,
-
-    matter: Array3<MatterCell>,
+    fields: YeeGrid,
+    materials: MaterialMap,
 }
 
 impl SimulationLattice {
+    /// Water with the gain medium at the centre and the dipole in it, all fields zero.
     fn new(size: usize) -> Self {
-        let mut rng = StdRng::seed_from_u64(42);
-
-        let e_x = Array3::from_shape_fn((size, size, size), |_| rng.gen_range(-1e-10..1e-10));
-        let e_y = e_x.clone();
-        let e_z = e_x.clone();
-        let b_x = e_x.clone();
-        let b_y = e_x.clone();
-        let b_z = e_x.clone();
-
-        let matter = Array3::from_shape_fn((size, size, size), |_| {
-            MatterCell::new(&mut rng)
-        });
+        let dt = yee::stable_time_step(LATTICE_SPACING);
+        let mut fields = YeeGrid::new((size, size, size), LATTICE_SPACING, dt)
+            .expect("the stable time step satisfies the Courant condition");
+
+        let centre = size/2;
+        let mut materials = MaterialMap::new((size, size, size), Material::water());
+        let sphere = Shape::Sphere { centre: [centre as f64*LATTICE_SPACING; 3], radius: GAIN_RADIUS };
+        materials.paint(&sphere, Material { atoms: Some(GAIN_MEDIUM), ..Material::water() }, LATTICE_SPACING)
+            .expect("the gain medium is physical");
+        fields.set_materials(&materials).expect("the material map has the lattice's shape");
+        fields.add_source(Source::Dipole {
+            position: [centre; 3],
+            axis: 2,
+            waveform: Waveform::Ricker { amplitude: DIPOLE_MOMENT, peak_frequency: DIPOLE_FREQUENCY, delay: 8.0/DIPOLE_FREQUENCY },
+        }).expect("the centre is inside the lattice");
 
         Self {
             size,
-            rng,
-            e_x,
-            e_y,
-            e_z,
-            b_x,
-            b_y,
-            b_z,
-            matter,
+            fields,
+            materials,
         }
     }
 
-    fn effective_refractive_index(&self, _x: usize, _y: usize, _z: usize) -> f64 {
-        N_CARBON
+    fn effective_refractive_index(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.materials.material(x, y, z).refractive_index(REFERENCE_OMEGA).re
     }
 
+    /// One leapfrog step of the Yee grid, B by a half step, then E.
     fn update_em_fields(&mut self) {
-        let dx = LATTICE_SPACING;
-        let c = C;
-
-        let mut new_e_x = self.e_x.clone();
-        let mut new_e_y = self.e_y.clone();
-        let mut new_e_z = self.e_z.clone();
-        let mut new_b_x = self.b_x.clone();
-        let mut new_b_y = self.b_y.clone();
-        let mut new_b_z = self.b_z.clone();
-
-        for x in 0..self.size {
-            let xp = (x+1) % self.size;
-            let xm = (x+self.size-1) % self.size;
-            for y in 0..self.size {
-                let yp = (y+1) % self.size;
-                let ym = (y+self.size-1) % self.size;
-                for z in 0..self.size {
-                    let zp = (z+1) % self.size;
-                    let zm = (z+self.size-1) % self.size;
-
-                    let n = self.effective_refractive_index(x,y,z);
-                    let curl_b_x = (self.b_z[[x,yp,z]] - self.b_z[[x,ym,z]])/(2.0*dx)
-                                 - (self.b_y[[x,y,zp]] - self.b_y[[x,y,zm]])/(2.0*dx);
-
-                    let curl_b_y = (self.b_x[[x,y,zp]] - self.b_x[[x,y,zm]])/(2.0*dx)
-                                 - (self.b_z[[xp,y,z]] - self.b_z[[xm,y,z]])/(2.0*dx);
-
-                    let curl_b_z = (self.b_y[[xp,y,z]] - self.b_y[[xm,y,z]])/(2.0*dx)
-                                 - (self.b_x[[x,yp,z]] - self.b_x[[x,ym,z]])/(2.0*dx);
-
-                    let curl_e_x = (self.e_z[[x,yp,z]] - self.e_z[[x,ym,z]])/(2.0*dx)
-                                 - (self.e_y[[x,y,zp]] - self.e_y[[x,y,zm]])/(2.0*dx);
-
-                    let curl_e_y = (self.e_x[[x,y,zp]] - self.e_x[[x,y,zm]])/(2.0*dx)
-                                 - (self.e_z[[xp,y,z]] - self.e_z[[xm,y,z]])/(2.0*dx);
-
-                    let curl_e_z = (self.e_y[[xp,y,z]] - self.e_y[[xm,y,z]])/(2.0*dx)
-                                 - (self.e_x[[x,yp,z]] - self.e_x[[x,ym,z]])/(2.0*dx);
-
-                    let n_sq = n*n;
-                    // Update E fields
-                    new_e_x[[x,y,z]] = self.e_x[[x,y,z]] + DELTA_T * (c*c/(n_sq)) * curl_b_x;
-                    new_e_y[[x,y,z]] = self.e_y[[x,y,z]] + DELTA_T * (c*c/(n_sq)) * curl_b_y;
-                    new_e_z[[x,y,z]] = self.e_z[[x,y,z]] + DELTA_T * (c*c/(n_sq)) * curl_b_z;
-
-                    // Update B fields
-                    new_b_x[[x,y,z]] = self.b_x[[x,y,z]] - DELTA_T * curl_e_x;
-                    new_b_y[[x,y,z]] = self.b_y[[x,y,z]] - DELTA_T * curl_e_y;
-                    new_b_z[[x,y,z]] = self.b_z[[x,y,z]] - DELTA_T * curl_e_z - PI.powi(256);
-                }
-            }
-        }
-
-        self.e_x = new_e_x;
-        self.e_y = new_e_y;
-        self.e_z = new_e_z;
-        self.b_x = new_b_x;
-        self.b_y = new_b_y;
-        self.b_z = new_b_z;
+        self.fields.step();
     }
 
-    fn apply_z_axis_boost(&mut self) {
-        let mid_x = self.size / 2;
-        let mid_y = self.size / 2;
-        for z in 0..self.size {
-            let phase_shift = (z as f64 / self.size as f64)*2.0*PI.powi(128)*PI.sqrt()*PHASE_SHIFT_FACTOR * 8.8_f64.sqrt() * PI.powi(128)*PI.sqrt();
-            let ex = self.e_x[[mid_x,mid_y,z]];
-            let ey = self.e_y[[mid_x,mid_y,z]];
-            let amp = (ex*ex + ey*ey).sqrt();
-            let new_ex = amp * phase_shift.cos();
-            let new_ey = amp * phase_shift.sin();
-            self.e_x[[mid_x,mid_y,z]] = new_ex;
-            self.e_y[[mid_x,mid_y,z]] = new_ey;
-        }
+    /// Fraction of the atoms at (x, y, z) in the upper level, 0 where there are none.
+    fn excited_fraction(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.fields.populations([x, y, z]).map_or(0.0, |[lower, upper]| upper/(lower + upper))
     }
 
-    fn matter_photon_conversion(&mut self) {
-        for x in 0..self.size {
-            for y in 0..self.size {
-                for z in 0..self.size {
-                    let intensity = self.e_x[[x,y,z]].powi(2) + self.e_y[[x,y,z]].powi(2) + self.e_z[[x,y,z]].powi(2);
-                    let cell = &mut self.matter[[x,y,z]];
-                    cell.lifetime += DELTA_T;
-                    if intensity > INTENSITY_THRESHOLD && cell.active {
-                        // Convert matter to photon state
-                        cell.active = false;
-                        cell.lifetime = 0.0;
-                    } else if intensity < INTENSITY_THRESHOLD * 0.5 && !cell.active && cell.lifetime > 5e-14 {
-                        // Revert back to matter
-                        cell.active = true;
-                        cell.lifetime = 0.0;
-                    }
-                }
-            }
-        }
-    }
-
-    fn add_noise(&mut self) {
-        for x in 0..self.size {
-            for y in 0..self.size {
-                for z in 0..self.size {
-                    let noise = self.rng.gen_range(-NOISE_LEVEL..NOISE_LEVEL);
-                    self.e_x[[x,y,z]] += noise;
-                    self.e_y[[x,y,z]] += noise;
-                    self.e_z[[x,y,z]] += noise;
-                }
-            }
-        }
-    }
-
-    fn evolve(&mut self, step: usize) {
+    fn evolve(&mut self) {
         self.update_em_fields();
-
-        if step % Z_AXIS_BOOST_INTERVAL == 0 && step != 0 {
-            self.apply_z_axis_boost();
-        }
-
-        self.add_noise();
-        self.matter_photon_conversion();
     }
 
     fn create_2d_flatmap(&self) -> RgbImage {
@@ -219,12 +96,11 @@ impl SimulationLattice {
         let mut image = RgbImage::new(self.size as u32, self.size as u32);
         for x in 0..self.size {
             for y in 0..self.size {
-                let intensity = (self.e_x[[x,y,z]].powi(2)
-                               + self.e_y[[x,y,z]].powi(2)
-                               + self.e_z[[x,y,z]].powi(2)).sqrt();
-                let val = (intensity / (INTENSITY_THRESHOLD*10.0) * 255.0).min(255.0) as u8;
-                let cell = self.matter[[x,y,z]];
-                let g = if cell.active { 255 } else { 0 };
+                let intensity = (self.fields.e[0][[x,y,z]].powi(2)
+                               + self.fields.e[1][[x,y,z]].powi(2)
+                               + self.fields.e[2][[x,y,z]].powi(2)).sqrt();
+                let val = (intensity / DISPLAY_FIELD * 255.0).min(255.0) as u8;
+                let g = (self.excited_fraction(x, y, z) * 255.0) as u8;
                 image.put_pixel(x as u32, y as u32, Rgb([val, g, 255 - val]));
             }
         }
@@ -233,21 +109,20 @@ impl SimulationLattice {
 
     fn export_3d_data(&self, filename: &str) {
         let mut file = File::create(filename).unwrap();
-        writeln!(file, "x,y,z,E_x,E_y,E_z,B_x,B_y,B_z,ActiveMatter").unwrap();
+        writeln!(file, "x,y,z,E_x,E_y,E_z,B_x,B_y,B_z,Excited").unwrap();
 
         for x in 0..self.size {
             for y in 0..self.size {
                 for z in 0..self.size {
-                    let c = self.matter[[x,y,z]];
                     writeln!(file, "{},{},{},{},{},{},{},{},{},{}",
                         x, y, z,
-                        self.e_x[[x,y,z]],
-                        self.e_y[[x,y,z]],
-                        self.e_z[[x,y,z]],
-                        self.b_x[[x,y,z]],
-                        self.b_y[[x,y,z]],
-                        self.b_z[[x,y,z]],
-                        c.active as u8).unwrap();
+                        self.fields.e[0][[x,y,z]],
+                        self.fields.e[1][[x,y,z]],
+                        self.fields.e[2][[x,y,z]],
+                        self.fields.b[0][[x,y,z]],
+                        self.fields.b[1][[x,y,z]],
+                        self.fields.b[2][[x,y,z]],
+                        self.excited_fraction(x, y, z)).unwrap();
                 }
             }
         }
@@ -260,8 +135,8 @@ fn main() {
     // Store all frames in memory
     let mut frames = Vec::new();
 
-    for step in 0..TIME_STEPS {
-        lattice.evolve(step);
+    for _ in 0..TIME_STEPS {
+        lattice.evolve();
         let frame = lattice.create_2d_flatmap();
         frames.push(frame);
     }
@@ -289,6 +164,11 @@ fn main() {
     // Export 3D data
     lattice.export_3d_data("3d_data.csv");
 
-    println!("Simulation complete. All frames consolidated into all_frames_consolidated.png");
+    println!("Simulation complete after {:.3e} s (Δt = {:.3e} s), field energy {:.3e} J.",
+             TIME_STEPS as f64*lattice.fields.dt, lattice.fields.dt, lattice.fields.energy());
+    let centre = LATTICE_SIZE/2;
+    println!("Refractive index at the centre: {:.4} at 500 nm.",
+             lattice.effective_refractive_index(centre, centre, centre));
+    println!("All frames consolidated into all_frames_consolidated.png");
     println!("3D data exported as 3d_data.csv.");
 }
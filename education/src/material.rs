@@ -0,0 +1,347 @@
+// Materials of the lattice: per-cell ε, μ, σ and dispersive poles.
+//
+// A material is
+//   ε(ω) = ε∞ + Σ poles − i σ/(ε0 ω),   μ
+// with the poles
+//   Drude    −ω_p²/(ω² − i γ ω)                     free electrons (metals)
+//   Lorentz  Δε ω0²/(ω0² − ω² + i γ ω)               bound resonances
+//   Debye    Δε/(1 + i ω τ)                          orientational relaxation (water)
+// (time dependence e^{iωt}). In the time domain each pole is a polarization P obeying
+//   a P'' + b P' + c P = ε0 w E
+// (Drude: a = 1, b = γ, c = 0, w = ω_p²; Lorentz: 1, γ, ω0², Δε ω0²; Debye: 0, τ, 1, Δε),
+// an auxiliary differential equation advanced next to E by the Yee grid, see `Pole::coefficients`.
+//
+// A material may also hold two-level atoms (see `atoms.rs`), a pole whose strength follows
+// their populations.
+//
+// A `MaterialMap` assigns one material to each cell, painted from primitives (spheres,
+// slabs, cylinders, later ones on top) or read from a voxel map.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use ndarray::Array3;
+use num_complex::Complex;
+
+use crate::atoms::TwoLevel;
+use crate::EPSILON_0;
+
+/// One dispersive term of ε(ω).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pole {
+    Drude { plasma_frequency: f64, collision_rate: f64 },            // ω_p, γ in rad/s
+    Lorentz { strength: f64, resonance: f64, damping: f64 },         // Δε, ω0, γ
+    Debye { strength: f64, relaxation_time: f64 },                   // Δε, τ in s
+}
+
+/// Update of one pole over a step Δt:
+///   P^{n+1} = a P^n + b P^{n−1} + g E^{n+1} + h E^n + k E^{n−1}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PoleCoefficients {
+    pub a: f64,
+    pub b: f64,
+    pub g: f64,
+    pub h: f64,
+    pub k: f64,
+}
+
+impl PoleCoefficients {
+    /// P'' + b P' + c P = ε0 w E centred on step n, E and cP averaged over n ± 1.
+    pub(crate) fn second_order(dt: f64, damping: f64, restoring: f64, weight: f64) -> Self {
+        let d = 1.0/(dt*dt) + damping/(2.0*dt) + restoring/2.0;
+        let g = EPSILON_0*weight/(2.0*d);
+        PoleCoefficients {
+            a: 2.0/(dt*dt)/d,
+            b: (damping/(2.0*dt) - 1.0/(dt*dt) - restoring/2.0)/d,
+            g,
+            h: 0.0,
+            k: g,
+        }
+    }
+}
+
+impl Pole {
+    /// Contribution to ε(ω).
+    pub fn susceptibility(&self, omega: f64) -> Complex<f64> {
+        let i = Complex::i();
+        match *self {
+            Pole::Drude { plasma_frequency, collision_rate } =>
+                -plasma_frequency.powi(2)/(omega*omega - i*collision_rate*omega),
+            Pole::Lorentz { strength, resonance, damping } =>
+                strength*resonance.powi(2)/(resonance.powi(2) - omega*omega + i*damping*omega),
+            Pole::Debye { strength, relaxation_time } => strength/(1.0 + i*omega*relaxation_time),
+        }
+    }
+
+    /// Discretization of the pole's ADE. Drude and Lorentz are centred on step n with E and
+    /// the restoring term averaged over n ± 1, Debye is the trapezoid rule over one step;
+    /// both hold E^{n+1} implicitly, so that no plasma frequency limits Δt.
+    pub fn coefficients(&self, dt: f64) -> PoleCoefficients {
+        match *self {
+            Pole::Drude { plasma_frequency, collision_rate } =>
+                PoleCoefficients::second_order(dt, collision_rate, 0.0, plasma_frequency.powi(2)),
+            Pole::Lorentz { strength, resonance, damping } =>
+                PoleCoefficients::second_order(dt, damping, resonance.powi(2), strength*resonance.powi(2)),
+            Pole::Debye { strength, relaxation_time } => {
+                let g = EPSILON_0*strength*dt/(2.0*relaxation_time + dt);
+                PoleCoefficients {
+                    a: (2.0*relaxation_time - dt)/(2.0*relaxation_time + dt),
+                    b: 0.0,
+                    g,
+                    h: g,
+                    k: 0.0,
+                }
+            }
+        }
+    }
+}
+
+/// Electromagnetic response of a cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Material {
+    pub permittivity: f64, // ε∞, relative
+    pub permeability: f64, // μ, relative
+    pub conductivity: f64, // σ, S/m
+    pub poles: Vec<Pole>,
+    pub atoms: Option<TwoLevel>,
+}
+
+impl Material {
+    pub fn vacuum() -> Self {
+        Material::dielectric(1.0)
+    }
+
+    /// A lossless, non-dispersive medium of relative permittivity ε.
+    pub fn dielectric(permittivity: f64) -> Self {
+        Material { permittivity, permeability: 1.0, conductivity: 0.0, poles: Vec::new(), atoms: None }
+    }
+
+    /// Liquid water at 20 °C: one Debye relaxation from the static ε = 80.1 down to the
+    /// optical ε∞ = 1.333².
+    pub fn water() -> Self {
+        let optical = 1.333_f64.powi(2);
+        Material {
+            permittivity: optical,
+            permeability: 1.0,
+            conductivity: 0.0,
+            poles: vec![Pole::Debye { strength: 80.1 - optical, relaxation_time: 9.4e-12 }],
+            atoms: None,
+        }
+    }
+
+    /// Gold as a Drude metal (ω_p = 1.37e16 rad/s, γ = 1.07e14 s⁻¹, ε∞ = 9.5 from the d bands).
+    pub fn gold() -> Self {
+        Material {
+            permittivity: 9.5,
+            permeability: 1.0,
+            conductivity: 0.0,
+            poles: vec![Pole::Drude { plasma_frequency: 1.37e16, collision_rate: 1.07e14 }],
+            atoms: None,
+        }
+    }
+
+    /// ε(ω), relative; atoms count with their unlit populations.
+    pub fn relative_permittivity(&self, omega: f64) -> Complex<f64> {
+        let conduction = -Complex::i()*self.conductivity/(EPSILON_0*omega);
+        let atoms = self.atoms.map_or(Complex::from(0.0), |a| a.susceptibility(omega));
+        self.permittivity + conduction + atoms + self.poles.iter().map(|p| p.susceptibility(omega)).sum::<Complex<f64>>()
+    }
+
+    /// Complex refractive index √(ε(ω) μ), on the branch with Re n ≥ 0; Im n < 0 is
+    /// absorption for e^{iωt}.
+    pub fn refractive_index(&self, omega: f64) -> Complex<f64> {
+        let n = (self.relative_permittivity(omega)*self.permeability).sqrt();
+        if n.re < 0.0 { -n } else { n }
+    }
+
+    fn dispersive(&self) -> bool {
+        !self.poles.is_empty()
+    }
+
+    fn validate(&self) -> Result<(), MaterialError> {
+        let positive = |v: f64| v > 0.0 && v.is_finite();
+        let pole_ok = |p: &Pole| match *p {
+            Pole::Drude { plasma_frequency, collision_rate } => positive(plasma_frequency) && collision_rate >= 0.0,
+            Pole::Lorentz { strength, resonance, damping } => positive(strength) && positive(resonance) && damping >= 0.0,
+            Pole::Debye { strength, relaxation_time } => positive(strength) && positive(relaxation_time),
+        };
+        if positive(self.permittivity) && positive(self.permeability) && self.conductivity >= 0.0
+            && self.poles.iter().all(pole_ok) && self.atoms.is_none_or(|a| a.valid()) {
+            Ok(())
+        } else {
+            Err(MaterialError(format!("unphysical material {:?}", self)))
+        }
+    }
+}
+
+/// A region of space, in metres from node (0, 0, 0).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+    Sphere { centre: [f64; 3], radius: f64 },
+    /// from ≤ x_axis < to
+    Slab { axis: usize, from: f64, to: f64 },
+    /// Infinite cylinder along `axis` through `centre`.
+    Cylinder { centre: [f64; 3], axis: usize, radius: f64 },
+}
+
+impl Shape {
+    pub fn contains(&self, point: [f64; 3]) -> bool {
+        match *self {
+            Shape::Sphere { centre, radius } =>
+                (0..3).map(|a| (point[a] - centre[a]).powi(2)).sum::<f64>() <= radius*radius,
+            Shape::Slab { axis, from, to } => from <= point[axis] && point[axis] < to,
+            Shape::Cylinder { centre, axis, radius } =>
+                (0..3).filter(|&a| a != axis).map(|a| (point[a] - centre[a]).powi(2)).sum::<f64>() <= radius*radius,
+        }
+    }
+}
+
+/// A material map that does not fit or parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaterialError(pub String);
+
+impl fmt::Display for MaterialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for MaterialError {}
+
+/// The material of every cell, as indices into a palette.
+#[derive(Debug, Clone)]
+pub struct MaterialMap {
+    pub materials: Vec<Material>,
+    pub cells: Array3<usize>,
+}
+
+impl MaterialMap {
+    /// Every cell of `shape` filled with `background`.
+    pub fn new(shape: (usize, usize, usize), background: Material) -> Self {
+        MaterialMap { materials: vec![background], cells: Array3::zeros(shape) }
+    }
+
+    /// Fills the cells of `region` with `material`; the grid spacing `dx` places node
+    /// (i, j, k) at (i, j, k) Δx.
+    pub fn paint(&mut self, region: &Shape, material: Material, dx: f64) -> Result<(), MaterialError> {
+        material.validate()?;
+        let index = match self.materials.iter().position(|m| *m == material) {
+            Some(index) => index,
+            None => {
+                self.materials.push(material);
+                self.materials.len() - 1
+            }
+        };
+        for ((i, j, k), cell) in self.cells.indexed_iter_mut() {
+            if region.contains([i as f64*dx, j as f64*dx, k as f64*dx]) {
+                *cell = index;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a voxel map: `nx ny nz` and then one palette index per cell, x fastest and z
+    /// slowest, separated by whitespace; `#` starts a comment.
+    pub fn from_voxels(text: &str, palette: Vec<Material>) -> Result<Self, MaterialError> {
+        for material in &palette {
+            material.validate()?;
+        }
+        let mut words = text.lines()
+            .map(|line| line.split('#').next().unwrap_or(""))
+            .flat_map(str::split_whitespace);
+        let mut number = |what: &str| -> Result<usize, MaterialError> {
+            let word = words.next().ok_or_else(|| MaterialError(format!("voxel map ends before {}", what)))?;
+            word.parse().map_err(|_| MaterialError(format!("'{}' is not a {}", word, what)))
+        };
+        let (nx, ny, nz) = (number("nx")?, number("ny")?, number("nz")?);
+        let mut cells = Array3::zeros((nx, ny, nz));
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let index = number("palette index")?;
+                    if index >= palette.len() {
+                        return Err(MaterialError(format!(
+                            "voxel ({}, {}, {}) uses material {} of a palette of {}", i, j, k, index, palette.len())));
+                    }
+                    cells[[i, j, k]] = index;
+                }
+            }
+        }
+        if words.next().is_some() {
+            return Err(MaterialError(format!("voxel map has more than {} x {} x {} cells", nx, ny, nz)));
+        }
+        Ok(MaterialMap { materials: palette, cells })
+    }
+
+    pub fn load_voxels(path: &Path, palette: Vec<Material>) -> Result<Self, MaterialError> {
+        let text = fs::read_to_string(path).map_err(|e| MaterialError(format!("{}: {}", path.display(), e)))?;
+        MaterialMap::from_voxels(&text, palette)
+    }
+
+    pub fn material(&self, x: usize, y: usize, z: usize) -> &Material {
+        &self.materials[self.cells[[x, y, z]]]
+    }
+}
+
+/// Pole state of one dispersive cell.
+pub(crate) struct DispersiveCell {
+    pub poles: Vec<PoleCoefficients>,
+    /// P^n and P^{n−1} of each pole, per component.
+    pub polarization: [Vec<f64>; 3],
+    pub previous: [Vec<f64>; 3],
+    /// E^{n−1}, per component.
+    pub field: [f64; 3],
+}
+
+impl DispersiveCell {
+    /// The pole terms of the E update of component `c` from E^n: Σ g, and
+    /// Σ [(a − 1) P^n + b P^{n−1} + h E^n + k E^{n−1}], the change of P that E^{n+1} does
+    /// not drive.
+    pub fn drive(&self, c: usize, e: f64) -> (f64, f64) {
+        let mut gain = 0.0;
+        let mut change = 0.0;
+        for (n, pole) in self.poles.iter().enumerate() {
+            gain += pole.g;
+            change += (pole.a - 1.0)*self.polarization[c][n] + pole.b*self.previous[c][n]
+                + pole.h*e + pole.k*self.field[c];
+        }
+        (gain, change)
+    }
+
+    /// Moves the poles of component `c` to step n + 1 once E went from `e` to `next`.
+    pub fn advance(&mut self, c: usize, e: f64, next: f64) {
+        for (n, pole) in self.poles.iter().enumerate() {
+            let p = self.polarization[c][n];
+            self.polarization[c][n] = pole.a*p + pole.b*self.previous[c][n]
+                + pole.g*next + pole.h*e + pole.k*self.field[c];
+            self.previous[c][n] = p;
+        }
+        self.field[c] = e;
+    }
+}
+
+/// The dispersive cells of a grid and where they are.
+pub(crate) struct Dispersion {
+    pub slots: Array3<Option<usize>>,
+    pub cells: Vec<DispersiveCell>,
+}
+
+impl Dispersion {
+    /// Zero polarization in the cells of `map` with poles, discretized for `dt`; None if
+    /// there are none.
+    pub fn new(map: &MaterialMap, dt: f64) -> Option<Self> {
+        let mut cells = Vec::new();
+        let slots = map.cells.map(|&index| {
+            let material = &map.materials[index];
+            material.dispersive().then(|| {
+                let poles: Vec<_> = material.poles.iter().map(|p| p.coefficients(dt)).collect();
+                let zeros = || std::array::from_fn(|_| vec![0.0; poles.len()]);
+                cells.push(DispersiveCell { polarization: zeros(), previous: zeros(), field: [0.0; 3], poles });
+                cells.len() - 1
+            })
+        });
+        (!cells.is_empty()).then_some(Dispersion { slots, cells })
+    }
+}
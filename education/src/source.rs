@@ -0,0 +1,332 @@
+// Sources that drive the Yee grid.
+//
+//   Dipole        a point current J = I l/ΔV on one E sample; it radiates in every direction
+//   PlaneWave     total-field/scattered-field: between two planes across an axis the grid
+//                 holds incident + scattered field, outside only the scattered one
+//   GaussianBeam  the same on a single plane, weighted by a Gaussian profile across it, so
+//                 that a beam enters the total-field side and nothing leaves the other
+//
+// The incident field of the last two comes from a one-dimensional Yee line along the axis
+// with the grid's Δx and Δt, so that it has the grid's own dispersion and cancels exactly
+// where the scattered-field region begins. At a total-field/scattered-field plane every curl
+// that straddles it mixes the two kinds of field; the incident value of the sample on the
+// other side is added or taken off (Taflove & Hagness, ch. 5). The planes must lie in vacuum,
+// and a plane wave needs periodic faces across the axis.
+//
+// Waveforms are complex: the real part is what is emitted, the imaginary part its quadrature,
+// which a polarization with a phase (circular, elliptical) or a focused beam combines with it.
+
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+use ndarray::Array2;
+use num_complex::Complex;
+
+use crate::boundary::Boundaries;
+use crate::yee::YeeGrid;
+use crate::{C, EPSILON_0};
+
+/// Time dependence of a source, e^{iωt} with ω in rad/s.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Waveform {
+    /// A exp(−((t − delay)/width)²) e^{iω(t − delay)}; ω = 0 for a baseband pulse.
+    GaussianPulse { amplitude: f64, delay: f64, width: f64, frequency: f64 },
+    /// A e^{iωt}, switched on over `ramp` seconds with a sin² edge.
+    ContinuousWave { amplitude: f64, frequency: f64, ramp: f64 },
+    /// A (1 − 2τ²) e^{−τ²} with τ = ω_p (t − delay)/2, peaking in spectrum at ω_p; real.
+    Ricker { amplitude: f64, peak_frequency: f64, delay: f64 },
+}
+
+impl Waveform {
+    pub fn signal(&self, t: f64) -> Complex<f64> {
+        match *self {
+            Waveform::GaussianPulse { amplitude, delay, width, frequency } => {
+                let s = t - delay;
+                amplitude*(-(s/width).powi(2)).exp()*Complex::from_polar(1.0, frequency*s)
+            }
+            Waveform::ContinuousWave { amplitude, frequency, ramp } => {
+                let edge = if t >= ramp { 1.0 } else if t <= 0.0 { 0.0 } else { (0.5*PI*t/ramp).sin().powi(2) };
+                amplitude*edge*Complex::from_polar(1.0, frequency*t)
+            }
+            Waveform::Ricker { amplitude, peak_frequency, delay } => {
+                let tau2 = (0.5*peak_frequency*(t - delay)).powi(2);
+                Complex::from(amplitude*(1.0 - 2.0*tau2)*(-tau2).exp())
+            }
+        }
+    }
+
+    /// The carrier frequency ω, if there is one.
+    pub fn carrier(&self) -> Option<f64> {
+        match *self {
+            Waveform::GaussianPulse { frequency, .. } | Waveform::ContinuousWave { frequency, .. } =>
+                (frequency > 0.0).then_some(frequency),
+            Waveform::Ricker { .. } => None,
+        }
+    }
+}
+
+/// A Jones vector over the two axes across the propagation axis a: (a+1) mod 3 first,
+/// (a+2) mod 3 second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Polarization(pub [Complex<f64>; 2]);
+
+impl Polarization {
+    /// Linear at `angle` from the first transverse axis towards the second.
+    pub fn linear(angle: f64) -> Self {
+        Polarization([Complex::from(angle.cos()), Complex::from(angle.sin())])
+    }
+
+    /// E turning from the first transverse axis to the second, anticlockwise as seen by
+    /// the receiver (right-handed in the IEEE convention).
+    pub fn right_circular() -> Self {
+        Polarization([Complex::from(0.5_f64.sqrt()), Complex::new(0.0, -0.5_f64.sqrt())])
+    }
+
+    pub fn left_circular() -> Self {
+        Polarization([Complex::from(0.5_f64.sqrt()), Complex::new(0.0, 0.5_f64.sqrt())])
+    }
+}
+
+/// Something that drives the grid. Positions are node indices; waves travel towards +axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    /// A current moment I l (A m) along `axis` on the E sample of `position`.
+    Dipole { position: [usize; 3], axis: usize, waveform: Waveform },
+    /// A plane wave of E amplitude `waveform` (V/m) filling the nodes `from..=to` of `axis`.
+    PlaneWave { axis: usize, from: usize, to: usize, polarization: Polarization, waveform: Waveform },
+    /// A beam entering through node `plane` of `axis`, with its waist of 1/e radius `waist`
+    /// at `focus` (metres from node 0); the peak E is `waveform` (V/m) at the focus.
+    GaussianBeam { axis: usize, plane: usize, focus: [f64; 3], waist: f64, polarization: Polarization, waveform: Waveform },
+}
+
+/// A source that does not fit the grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceError(pub String);
+
+impl fmt::Display for SourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for SourceError {}
+
+/// Cells of the incident line before the first plane and after the last, and of the
+/// absorbing layer that ends it.
+const MARGIN: usize = 10;
+const ABSORBER: usize = 30;
+
+/// The incident field along a line of nodes, E at the nodes and B half a cell above them,
+/// in the pairing E_{a+1}, B_{a+2} (B = E/c going forward).
+struct Line {
+    e: Vec<Complex<f64>>,
+    b: Vec<Complex<f64>>,
+    /// σΔt/(2ε0) of E and the matching magnetic loss of B, graded as x³ into the absorber.
+    loss_e: Vec<f64>,
+    loss_b: Vec<f64>,
+    /// Grid index of node 0 of the line; may lie before the grid.
+    origin: isize,
+    waveform: Waveform,
+}
+
+impl Line {
+    fn new(first: usize, last: usize, waveform: Waveform, courant: f64) -> Self {
+        let start = last - first + 2*MARGIN;
+        let len = start + ABSORBER + 1;
+        // σ_max = 0.8 (3 + 1)/(η0 Δx), as for the grid's PML
+        let loss = |x: f64| 1.6*courant*((x - start as f64)/ABSORBER as f64).max(0.0).powi(3);
+        Line {
+            e: vec![Complex::from(0.0); len],
+            b: vec![Complex::from(0.0); len],
+            loss_e: (0..len).map(|m| loss(m as f64)).collect(),
+            loss_b: (0..len).map(|m| loss(m as f64 + 0.5)).collect(),
+            origin: first as isize - MARGIN as isize,
+            waveform,
+        }
+    }
+
+    fn node(&self, index: usize) -> usize {
+        (index as isize - self.origin) as usize
+    }
+
+    /// B from n − ½ to n + ½.
+    fn step_b(&mut self, dt_dx: f64) {
+        for m in 0..self.e.len() - 1 {
+            let r = self.loss_b[m];
+            self.b[m] = ((1.0 - r)*self.b[m] - dt_dx*(self.e[m + 1] - self.e[m]))/(1.0 + r);
+        }
+    }
+
+    /// E from n to n + 1 = `steps`, driven at node 0 and timed to pass the first plane as
+    /// the waveform; the last node is held at 0 behind the absorber.
+    fn step_e(&mut self, dt_dx: f64, dx: f64, dt: f64, steps: usize) {
+        for m in 1..self.e.len() - 1 {
+            let r = self.loss_e[m];
+            self.e[m] = ((1.0 - r)*self.e[m] - C*C*dt_dx*(self.b[m] - self.b[m - 1]))/(1.0 + r);
+        }
+        self.e[0] = self.waveform.signal(steps as f64*dt + MARGIN as f64*dx/C);
+    }
+}
+
+/// A set of total-field/scattered-field planes across `axis` fed by one incident line.
+pub(crate) struct Injection {
+    axis: usize,
+    /// The first total-field node and, for a plane wave, the last.
+    from: usize,
+    to: Option<usize>,
+    polarization: Polarization,
+    /// Amplitude and phase across the planes, per transverse component of E: E_{a+1} and
+    /// B_{a+2} share their transverse position, as do E_{a+2} and B_{a+1}.
+    profile: [Array2<Complex<f64>>; 2],
+    line: Line,
+}
+
+/// The running state of a source.
+pub(crate) enum Injector {
+    Dipole { position: [usize; 3], axis: usize, waveform: Waveform },
+    Planes(Box<Injection>),
+}
+
+impl Injector {
+    pub fn new(source: Source, shape: [usize; 3], boundaries: &Boundaries, dx: f64, dt: f64) -> Result<Self, SourceError> {
+        let check_axis = |axis: usize| if axis < 3 { Ok(()) } else { Err(SourceError(format!("there is no axis {}", axis))) };
+        let check_plane = |axis: usize, index: usize| {
+            if (1..shape[axis].saturating_sub(1)).contains(&index) {
+                Ok(())
+            } else {
+                Err(SourceError(format!("node {} of axis {} leaves no scattered field on both sides of the {} nodes", index, axis, shape[axis])))
+            }
+        };
+        let transverse = |axis: usize| [(axis + 1) % 3, (axis + 2) % 3];
+        match source {
+            Source::Dipole { position, axis, waveform } => {
+                check_axis(axis)?;
+                if (0..3).any(|a| position[a] >= shape[a]) {
+                    return Err(SourceError(format!("dipole at {:?} lies outside the {:?} grid", position, shape)));
+                }
+                Ok(Injector::Dipole { position, axis, waveform })
+            }
+            Source::PlaneWave { axis, from, to, polarization, waveform } => {
+                check_axis(axis)?;
+                check_plane(axis, from)?;
+                check_plane(axis, to + 1)?;
+                if to < from {
+                    return Err(SourceError(format!("the total field {}..={} is empty", from, to)));
+                }
+                if let Some(&a) = transverse(axis).iter().find(|&&a| !boundaries.periodic(a)) {
+                    return Err(SourceError(format!("a plane wave along axis {} needs periodic faces on axis {}", axis, a)));
+                }
+                let [t1, t2] = transverse(axis);
+                let flat = || Array2::from_elem((shape[t1], shape[t2]), Complex::from(1.0));
+                Ok(Injector::Planes(Box::new(Injection {
+                    axis, from, to: Some(to), polarization,
+                    profile: [flat(), flat()],
+                    line: Line::new(from, to, waveform, C*dt/dx),
+                })))
+            }
+            Source::GaussianBeam { axis, plane, focus, waist, polarization, waveform } => {
+                check_axis(axis)?;
+                check_plane(axis, plane)?;
+                if waist.is_nan() || waist <= 0.0 {
+                    return Err(SourceError(format!("beam waist {} is not positive", waist)));
+                }
+                // q = z + i z_R at distance z past the waist; E ∝ (q0/q) exp(−i k r²/(2q))
+                let z = plane as f64*dx - focus[axis];
+                let k = match waveform.carrier() {
+                    Some(omega) => omega/C,
+                    None if z == 0.0 => 0.0,
+                    None => return Err(SourceError("a beam focused off its plane needs a carrier frequency".into())),
+                };
+                let rayleigh = 0.5*k*waist*waist;
+                let q = Complex::new(z, rayleigh);
+                let q0 = Complex::new(0.0, rayleigh);
+                let [t1, t2] = transverse(axis);
+                // E_{a+1} sits half a cell along a+1, E_{a+2} along a+2
+                let profile = |offset: [f64; 2]| Array2::from_shape_fn((shape[t1], shape[t2]), |(i, j)| {
+                    let r2 = ((i as f64 + offset[0])*dx - focus[t1]).powi(2) + ((j as f64 + offset[1])*dx - focus[t2]).powi(2);
+                    if k == 0.0 {
+                        Complex::from((-r2/(waist*waist)).exp())
+                    } else {
+                        q0/q*(-Complex::i()*k*r2/(2.0*q)).exp()
+                    }
+                });
+                Ok(Injector::Planes(Box::new(Injection {
+                    axis, from: plane, to: None, polarization,
+                    profile: [profile([0.5, 0.0]), profile([0.0, 0.5])],
+                    line: Line::new(plane, plane, waveform, C*dt/dx),
+                })))
+            }
+        }
+    }
+
+    /// Corrects B^{n+½} for the incident E^n, then moves the incident line on to B^{n+½}.
+    pub fn after_b(&mut self, grid: &mut YeeGrid) {
+        let Injector::Planes(injection) = self else { return };
+        let dt_dx = grid.dt/grid.dx;
+        let Injection { axis, from, to, polarization, profile, line } = &mut **injection;
+        let [t1, t2] = [(*axis + 1) % 3, (*axis + 2) % 3];
+        // Scattered B half a cell outside the total-field node `node`, with `sign` +1 below
+        // `from` and −1 above `to`: B_{a+2} += dt/dx E_{a+1}^inc, B_{a+1} −= dt/dx E_{a+2}^inc
+        let mut correct = |node: usize, outside: usize, sign: f64| {
+            let incident = line.e[line.node(node)];
+            for_plane(grid, *axis, outside, |grid, idx, [i, j]| {
+                grid.b[t2][idx] += sign*dt_dx*(polarization.0[0]*profile[0][[i, j]]*incident).re;
+                grid.b[t1][idx] -= sign*dt_dx*(polarization.0[1]*profile[1][[i, j]]*incident).re;
+            });
+        };
+        correct(*from, *from - 1, 1.0);
+        if let Some(to) = *to {
+            correct(to, to, -1.0);
+        }
+        line.step_b(dt_dx);
+    }
+
+    /// Corrects E^{n+1} for the incident B^{n+½} or drives it with the dipole current at
+    /// (n + ½) Δt; `steps` is n + 1.
+    pub fn after_e(&mut self, grid: &mut YeeGrid, steps: usize) {
+        let (dx, dt) = (grid.dx, grid.dt);
+        match self {
+            Injector::Dipole { position, axis, waveform } => {
+                let current = waveform.signal((steps as f64 - 0.5)*dt).re/dx.powi(3);
+                let eps = EPSILON_0*grid.staggered(&grid.permittivity, *position, &[*axis]);
+                grid.e[*axis][*position] -= dt*current/eps;
+            }
+            Injector::Planes(injection) => {
+                let dt_dx = dt/dx;
+                let Injection { axis, from, to, polarization, profile, line } = &mut **injection;
+                let [t1, t2] = [(*axis + 1) % 3, (*axis + 2) % 3];
+                // The first and last total-field E, next to the scattered B at `outside`:
+                // E_{a+1} += c² dt/dx B_{a+2}^inc, E_{a+2} −= c² dt/dx B_{a+1}^inc
+                let mut correct = |node: usize, outside: usize, sign: f64| {
+                    let incident = line.b[line.node(outside)];
+                    for_plane(grid, *axis, node, |grid, idx, [i, j]| {
+                        grid.e[t1][idx] += sign*C*C*dt_dx*(polarization.0[0]*profile[0][[i, j]]*incident).re;
+                        grid.e[t2][idx] += sign*C*C*dt_dx*(polarization.0[1]*profile[1][[i, j]]*incident).re;
+                    });
+                };
+                correct(*from, *from - 1, 1.0);
+                if let Some(to) = *to {
+                    correct(to, to, -1.0);
+                }
+                line.step_e(dt_dx, dx, dt, steps);
+            }
+        }
+    }
+}
+
+/// Calls `visit` with the index and transverse position of every node of the plane at
+/// node `index` of `axis`.
+fn for_plane(grid: &mut YeeGrid, axis: usize, index: usize, mut visit: impl FnMut(&mut YeeGrid, [usize; 3], [usize; 2])) {
+    let shape = grid.shape();
+    let [t1, t2] = [(axis + 1) % 3, (axis + 2) % 3];
+    for i in 0..shape[t1] {
+        for j in 0..shape[t2] {
+            let mut idx = [0; 3];
+            idx[axis] = index;
+            idx[t1] = i;
+            idx[t2] = j;
+            visit(grid, idx, [i, j]);
+        }
+    }
+}
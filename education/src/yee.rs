@@ -0,0 +1,419 @@
+// Staggered Yee grid with leapfrog time stepping (FDTD).
+//
+// Cell (i, j, k) holds the components at
+//   E_x (i+½, j, k)    E_y (i, j+½, k)    E_z (i, j, k+½)      at t = n Δt
+//   B_x (i, j+½, k+½)  B_y (i+½, j, k+½)  B_z (i+½, j+½, k)    at t = (n+½) Δt
+// so every curl is a centred one-cell difference, and one step is
+//   B^{n+½} = B^{n−½} − Δt ∇×E^n
+//   E^{n+1} = E^n + Δt c²/ε_r ∇×B^{n+½}
+// This is second order in space and time and, without losses, symplectic: the discrete
+// energy ½ Σ (ε E^n·E^n + B^{n−½}·B^{n+½}/μ) ΔV is conserved to rounding, and waves keep
+// their amplitude. It is stable for c Δt ≤ Δx/√3 (the Courant condition on a cubic grid).
+// The faces are periodic unless `set_boundaries` says otherwise (see `boundary.rs`).
+//
+// Materials are given at the nodes (see `material.rs`); a component between nodes sees the
+// mean of those around it, so that ε and μ of a sample change where the material does.
+// Poles and atoms are not averaged: their polarization is state of one node, so E_c at
+// idx + ½ê_c is driven by those of node idx alone while its ε∞ and σ are the mean with
+// idx + ê_c. A dispersive surface thus starts its poles half a cell past its ε∞, which
+// moves its reflection far less than the grid's own dispersion does (tests/material.rs).
+// Sources (see `source.rs`) act on B and on E right after each moves.
+// Cells of other materials solve ε0 ε∞ ∂E/∂t + ∂P/∂t = ∇×(B/μ) − σE
+// instead, with σE averaged over the step and each pole's P advanced implicitly in E^{n+1}:
+//   E^{n+1} = [(ε0 ε∞ − σΔt/2) E^n − Σ ((a−1) P^n + b P^{n−1} + h E^n + k E^{n−1})
+//              + Δt ∇×(B/μ)] / (ε0 ε∞ + σΔt/2 + Σ g)
+// Two-level atoms (see `atoms.rs`) are one more such pole; their populations move once
+// all of E has.
+//
+// Component c has the curl ∂_p F_q − ∂_q F_p with p = c+1 and q = c+2 (mod 3); E_c is
+// staggered along c, B_c along p and q.
+
+use std::error::Error;
+use std::fmt;
+
+use ndarray::prelude::*;
+
+use crate::atoms::Atoms;
+use crate::boundary::{self, Boundaries, Boundary, BoundaryError, Cpml, Side};
+use crate::material::{Dispersion, MaterialError, MaterialMap};
+use crate::source::{Injector, Source, SourceError};
+use crate::{C, EPSILON_0, MU_0};
+
+/// Fraction of the Courant limit used by `stable_time_step`.
+pub const COURANT_SAFETY: f64 = 0.99;
+
+/// The largest stable Δt on a cubic grid of spacing `dx`: Δx/(c √3).
+pub fn courant_limit(dx: f64) -> f64 {
+    dx/(C*3.0_f64.sqrt())
+}
+
+/// A safe Δt for spacing `dx`, just below the Courant limit.
+pub fn stable_time_step(dx: f64) -> f64 {
+    COURANT_SAFETY*courant_limit(dx)
+}
+
+/// A time step the grid would not survive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CourantError {
+    pub dt: f64,
+    pub limit: f64,
+}
+
+impl fmt::Display for CourantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Δt = {:e} s exceeds the Courant limit {:e} s of the grid", self.dt, self.limit)
+    }
+}
+
+impl Error for CourantError {}
+
+/// E and B on a Yee grid.
+pub struct YeeGrid {
+    pub dx: f64,
+    pub dt: f64,
+
+    /// x, y and z components of E (V/m) and B (T).
+    pub e: [Array3<f64>; 3],
+    pub b: [Array3<f64>; 3],
+
+    /// Relative permittivity ε_r = n² at each node (ε∞ where dispersive), 1 in vacuum.
+    pub permittivity: Array3<f64>,
+    /// Relative permeability μ_r, 1 in vacuum.
+    pub permeability: Array3<f64>,
+    /// Conductivity σ in S/m, 0 in vacuum.
+    pub conductivity: Array3<f64>,
+
+    boundaries: Boundaries,
+    cpml: Option<Cpml>,
+    dispersion: Option<Dispersion>,
+    atoms: Option<Atoms>,
+    sources: Vec<Injector>,
+    steps: usize,
+    energy: f64,
+}
+
+/// The index one step along `axis`, or None past the end.
+fn shifted(idx: [usize; 3], axis: usize, up: bool, shape: [usize; 3], periodic: bool) -> Option<[usize; 3]> {
+    let mut next = idx;
+    let n = shape[axis];
+    next[axis] = match (up, idx[axis]) {
+        (true, i) if i + 1 < n => i + 1,
+        (true, _) => if periodic { 0 } else { return None },
+        (false, 0) => if periodic { n - 1 } else { return None },
+        (false, i) => i - 1,
+    };
+    Some(next)
+}
+
+/// Whether a sample staggered along `half_axes` lies past the last node of a non-periodic axis.
+fn outside(boundaries: &Boundaries, shape: [usize; 3], idx: [usize; 3], half_axes: &[usize]) -> bool {
+    half_axes.iter().any(|&a| !boundaries.periodic(a) && idx[a] + 1 == shape[a])
+}
+
+impl YeeGrid {
+    /// A periodic vacuum grid of `shape` cells with zero fields; fails if `dt` breaks the
+    /// Courant condition for `dx`.
+    pub fn new(shape: (usize, usize, usize), dx: f64, dt: f64) -> Result<Self, CourantError> {
+        let limit = courant_limit(dx);
+        if !(dt > 0.0 && dt <= limit) {
+            return Err(CourantError { dt, limit });
+        }
+        Ok(YeeGrid {
+            dx,
+            dt,
+            e: std::array::from_fn(|_| Array3::zeros(shape)),
+            b: std::array::from_fn(|_| Array3::zeros(shape)),
+            permittivity: Array3::ones(shape),
+            permeability: Array3::ones(shape),
+            conductivity: Array3::zeros(shape),
+            boundaries: Boundaries::default(),
+            cpml: None,
+            dispersion: None,
+            atoms: None,
+            sources: Vec::new(),
+            steps: 0,
+            energy: 0.0,
+        })
+    }
+
+    pub fn shape(&self) -> [usize; 3] {
+        let (nx, ny, nz) = self.permittivity.dim();
+        [nx, ny, nz]
+    }
+
+    pub fn boundaries(&self) -> &Boundaries {
+        &self.boundaries
+    }
+
+    /// Sets the faces; samples they leave outside the domain are cleared.
+    pub fn set_boundaries(&mut self, boundaries: Boundaries) -> Result<(), BoundaryError> {
+        let shape = self.shape();
+        boundaries.validate(shape)?;
+        self.boundaries = boundaries;
+        self.cpml = boundaries.any(|f| matches!(f, Boundary::Pml { .. }))
+            .then(|| Cpml::new(&boundaries, shape, self.dx, self.dt));
+        for c in 0..3 {
+            for ((i, j, k), v) in self.e[c].indexed_iter_mut() {
+                if outside(&boundaries, shape, [i, j, k], &[c]) {
+                    *v = 0.0;
+                }
+            }
+            for ((i, j, k), v) in self.b[c].indexed_iter_mut() {
+                if outside(&boundaries, shape, [i, j, k], &[(c + 1) % 3, (c + 2) % 3]) {
+                    *v = 0.0;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gives every cell the material `map` assigns it, with its poles at rest and its atoms
+    /// in their unlit populations.
+    pub fn set_materials(&mut self, map: &MaterialMap) -> Result<(), MaterialError> {
+        let shape = self.shape();
+        let (nx, ny, nz) = map.cells.dim();
+        if [nx, ny, nz] != shape {
+            return Err(MaterialError(format!("a {} x {} x {} material map does not fit a {:?} grid", nx, ny, nz, shape)));
+        }
+        let material = |i: &usize| &map.materials[*i];
+        self.permittivity = map.cells.map(|i| material(i).permittivity);
+        self.permeability = map.cells.map(|i| material(i).permeability);
+        self.conductivity = map.cells.map(|i| material(i).conductivity);
+        self.dispersion = Dispersion::new(map, self.dt);
+        self.atoms = Atoms::new(map, self.dt);
+        Ok(())
+    }
+
+    /// Adds a source; its waveform is read on the grid's clock, `time`.
+    pub fn add_source(&mut self, source: Source) -> Result<(), SourceError> {
+        let injector = Injector::new(source, self.shape(), &self.boundaries, self.dx, self.dt)?;
+        self.sources.push(injector);
+        Ok(())
+    }
+
+    /// Densities N1 and N2 (m⁻³) of the lower and upper level of the atoms at node `idx`.
+    pub fn populations(&self, idx: [usize; 3]) -> Option<[f64; 2]> {
+        let atoms = self.atoms.as_ref()?;
+        let atom = &atoms.cells[atoms.slots[idx]?];
+        Some([atom.density() - atom.upper, atom.upper])
+    }
+
+    /// Time of E, n Δt after n steps.
+    pub fn time(&self) -> f64 {
+        self.steps as f64*self.dt
+    }
+
+    fn outside(&self, idx: [usize; 3], half_axes: &[usize]) -> bool {
+        outside(&self.boundaries, self.shape(), idx, half_axes)
+    }
+
+    /// The mean of `f` over the nodes around a sample staggered along `half_axes`.
+    pub(crate) fn staggered(&self, f: &Array3<f64>, idx: [usize; 3], half_axes: &[usize]) -> f64 {
+        let shape = self.shape();
+        let mut corners = [idx; 4];
+        let mut count = 1;
+        for &axis in half_axes {
+            for n in 0..count {
+                corners[count + n] = shifted(corners[n], axis, true, shape, true).expect("periodic shifts always exist");
+            }
+            count *= 2;
+        }
+        corners[..count].iter().map(|&corner| f[corner]).sum::<f64>()/count as f64
+    }
+
+    /// The face of a non-periodic `axis` that a node sample at `idx` lies on.
+    fn face(&self, idx: [usize; 3], axis: usize) -> Option<Boundary> {
+        if self.boundaries.periodic(axis) {
+            None
+        } else if idx[axis] == 0 {
+            Some(self.boundaries.face(axis, Side::Low))
+        } else if idx[axis] + 1 == self.shape()[axis] {
+            Some(self.boundaries.face(axis, Side::High))
+        } else {
+            None
+        }
+    }
+
+    /// F(idx + ê_axis) − F(idx) for a sample of B staggered along `axis`.
+    fn forward(&self, f: &Array3<f64>, idx: [usize; 3], axis: usize) -> f64 {
+        let shape = self.shape();
+        let next = shifted(idx, axis, true, shape, true).expect("periodic shifts always exist");
+        f[next] - f[idx]
+    }
+
+    /// F(idx) − F(idx − ê_axis) for an E sample at a node along `axis`; beyond a magnetic
+    /// conductor, tangential B is the odd image of the sample inside.
+    fn backward(&self, f: &Array3<f64>, idx: [usize; 3], axis: usize) -> f64 {
+        let shape = self.shape();
+        match self.face(idx, axis) {
+            Some(Boundary::MagneticConductor) if idx[axis] == 0 => 2.0*f[idx],
+            Some(Boundary::MagneticConductor) => {
+                let below = shifted(idx, axis, false, shape, false).expect("the high face has a node below");
+                -2.0*f[below]
+            }
+            _ => {
+                let previous = shifted(idx, axis, false, shape, true).expect("periodic shifts always exist");
+                f[idx] - f[previous]
+            }
+        }
+    }
+
+    /// Advances B by a half step past E and then E by a full step.
+    pub fn step(&mut self) {
+        let shape = self.shape();
+        let (nx, ny, nz) = (shape[0], shape[1], shape[2]);
+        let dt_dx = self.dt/self.dx;
+
+        // E^n·E^n is taken before E moves, B^{n−½}·B^{n+½} while B does.
+        let mut electric = 0.0;
+        let mut magnetic = 0.0;
+        for ((i, j, k), _) in self.permittivity.indexed_iter() {
+            let idx = [i, j, k];
+            electric += (0..3).map(|c| self.staggered(&self.permittivity, idx, &[c])*self.e[c][idx].powi(2)).sum::<f64>();
+        }
+
+        let mut cpml = self.cpml.take();
+        for c in 0..3 {
+            let (p, q) = ((c + 1) % 3, (c + 2) % 3);
+            for i in 0..nx {
+                for j in 0..ny {
+                    for k in 0..nz {
+                        let idx = [i, j, k];
+                        if self.outside(idx, &[p, q]) {
+                            continue;
+                        }
+                        let mut d_p = self.forward(&self.e[q], idx, p);
+                        let mut d_q = self.forward(&self.e[p], idx, q);
+                        if let Some(cpml) = cpml.as_mut() {
+                            let (hp, hq) = (&cpml.halves[p], &cpml.halves[q]);
+                            let psi = &mut cpml.psi_b[c];
+                            psi[0][idx] = hp.b[idx[p]]*psi[0][idx] + hp.a[idx[p]]*d_p;
+                            psi[1][idx] = hq.b[idx[q]]*psi[1][idx] + hq.a[idx[q]]*d_q;
+                            d_p = d_p*hp.inv_kappa[idx[p]] + psi[0][idx];
+                            d_q = d_q*hq.inv_kappa[idx[q]] + psi[1][idx];
+                        }
+                        let old = self.b[c][idx];
+                        self.b[c][idx] = old - dt_dx*(d_p - d_q);
+                        magnetic += old*self.b[c][idx]/self.staggered(&self.permeability, idx, &[p, q]);
+                    }
+                }
+            }
+        }
+        self.energy = 0.5*self.dx.powi(3)*(EPSILON_0*electric + magnetic/MU_0);
+
+        let mut sources = std::mem::take(&mut self.sources);
+        for source in sources.iter_mut() {
+            source.after_b(self);
+        }
+
+        // The curl of H = B/μ; μ0 enters with Δt below
+        let h: [Array3<f64>; 3] = std::array::from_fn(|c| {
+            let half_axes = [(c + 1) % 3, (c + 2) % 3];
+            Array3::from_shape_fn(self.b[c].dim(), |(i, j, k)| {
+                self.b[c][[i, j, k]]/self.staggered(&self.permeability, [i, j, k], &half_axes)
+            })
+        });
+        let mut dispersion = self.dispersion.take();
+        let mut atoms = self.atoms.take();
+        let mur = self.boundaries.any(|f| *f == Boundary::Mur);
+        let previous = if mur { Some(self.e.clone()) } else { None };
+        for c in 0..3 {
+            let (p, q) = ((c + 1) % 3, (c + 2) % 3);
+            for i in 0..nx {
+                for j in 0..ny {
+                    for k in 0..nz {
+                        let idx = [i, j, k];
+                        if self.outside(idx, &[c]) {
+                            continue;
+                        }
+                        // Tangential E on a face is held (conductors, PML) or set below (Mur)
+                        let held = [p, q].iter().any(|&a| matches!(self.face(idx, a),
+                            Some(Boundary::ElectricConductor | Boundary::Pml { .. } | Boundary::Mur)));
+                        if held {
+                            continue;
+                        }
+                        let mut d_p = self.backward(&h[q], idx, p);
+                        let mut d_q = self.backward(&h[p], idx, q);
+                        if let Some(cpml) = cpml.as_mut() {
+                            let (np, nq) = (&cpml.nodes[p], &cpml.nodes[q]);
+                            let psi = &mut cpml.psi_e[c];
+                            psi[0][idx] = np.b[idx[p]]*psi[0][idx] + np.a[idx[p]]*d_p;
+                            psi[1][idx] = nq.b[idx[q]]*psi[1][idx] + nq.a[idx[q]]*d_q;
+                            d_p = d_p*np.inv_kappa[idx[p]] + psi[0][idx];
+                            d_q = d_q*nq.inv_kappa[idx[q]] + psi[1][idx];
+                        }
+                        let e = self.e[c][idx];
+                        let eps = EPSILON_0*self.staggered(&self.permittivity, idx, &[c]);
+                        let loss = 0.5*self.staggered(&self.conductivity, idx, &[c])*self.dt;
+                        // The poles and atoms of node idx, see the header
+                        let cell = dispersion.as_mut()
+                            .and_then(|d| d.slots[idx].map(|slot| &mut d.cells[slot]));
+                        let atom = atoms.as_mut()
+                            .and_then(|a| a.slots[idx].map(|slot| &mut a.cells[slot]));
+                        let (g_poles, poles) = cell.as_ref().map_or((0.0, 0.0), |cell| cell.drive(c, e));
+                        let (g_atoms, atomic) = atom.as_ref().map_or((0.0, 0.0), |atom| atom.drive(c, e));
+                        let next = ((eps - loss)*e - poles - atomic + dt_dx*(d_p - d_q)/MU_0)
+                            /(eps + loss + g_poles + g_atoms);
+                        if let Some(cell) = cell {
+                            cell.advance(c, e, next);
+                        }
+                        if let Some(atom) = atom {
+                            atom.advance(c, e, next);
+                        }
+                        self.e[c][idx] = next;
+                    }
+                }
+            }
+        }
+        self.cpml = cpml;
+        self.dispersion = dispersion;
+        if let Some(atoms) = atoms.as_mut() {
+            for atom in atoms.cells.iter_mut() {
+                atom.relax(self.dt);
+            }
+        }
+        self.atoms = atoms;
+        if let Some(previous) = previous {
+            self.apply_mur(&previous);
+        }
+        self.steps += 1;
+        for source in sources.iter_mut() {
+            source.after_e(self, self.steps);
+        }
+        self.sources = sources;
+    }
+
+    /// Mur's first-order condition on the Mur faces:
+    /// F_0^{n+1} = F_1^n + (vΔt − Δx)/(vΔt + Δx) (F_1^{n+1} − F_0^n).
+    fn apply_mur(&mut self, previous: &[Array3<f64>; 3]) {
+        let shape = self.shape();
+        for axis in 0..3 {
+            for side in [Side::Low, Side::High] {
+                if self.boundaries.face(axis, side) != Boundary::Mur {
+                    continue;
+                }
+                let face = if side == Side::Low { 0 } else { shape[axis] - 1 };
+                for c in (0..3).filter(|&c| c != axis) {
+                    for ((i, j, k), &mu) in self.permeability.indexed_iter() {
+                        let idx = [i, j, k];
+                        if idx[axis] != face || self.outside(idx, &[c]) {
+                            continue;
+                        }
+                        let n = (self.staggered(&self.permittivity, idx, &[c])*mu).sqrt();
+                        let inner = shifted(idx, axis, side == Side::Low, shape, false)
+                            .expect("a Mur face has a node inside");
+                        let coefficient = boundary::mur_coefficient(n, self.dx, self.dt);
+                        self.e[c][idx] = previous[c][inner] + coefficient*(self.e[c][inner] - previous[c][idx]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Field energy in J at the start of the last step, in the leapfrog form
+    /// ½ Σ (ε E^n·E^n + B^{n−½}·B^{n+½}/μ) ΔV that the scheme conserves without losses;
+    /// energy held by poles and atoms is not counted. 0 before the first step.
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+}
@@ -0,0 +1,141 @@
+use std::f64::consts::PI;
+
+use education::atoms::TwoLevel;
+use education::boundary::{Boundaries, Boundary, PmlGrading};
+use education::material::{Material, MaterialMap, Shape};
+use education::source::{Polarization, Source, Waveform};
+use education::yee::{self, YeeGrid};
+use education::{C, EPSILON_0, HBAR, LATTICE_SPACING};
+
+const DX: f64 = LATTICE_SPACING;
+
+/// Resonant with light 20 cells long.
+const OMEGA: f64 = 2.0*PI*C/(20.0*DX);
+
+/// Atoms of line width ω0/10 and lifetime `lifetime`, pumped to Rτ = `pumping`, dense
+/// enough that their unlit inversion N2 − N1 is `inversion`.
+fn atoms(lifetime: f64, pumping: f64, inversion: f64) -> TwoLevel {
+    let mut atoms = TwoLevel { density: 1.0, resonance: OMEGA, linewidth: 0.1*OMEGA, lifetime, pump: pumping/lifetime };
+    atoms.density = inversion/((pumping - 1.0)/(pumping + 1.0));
+    atoms
+}
+
+/// A periodic ring of `cells` filled with `atoms` in a medium of conductivity `conductivity`,
+/// holding a wave of E_y = `seed` sin(2π x/(20 Δx)) running along x. A running wave
+/// lights every cell alike, where a standing one would leave the atoms at its nodes unused.
+fn ring(cells: usize, atoms: TwoLevel, conductivity: f64, seed: f64) -> YeeGrid {
+    let mut grid = YeeGrid::new((cells, 1, 1), DX, yee::stable_time_step(DX)).unwrap();
+    let medium = Material { conductivity, atoms: Some(atoms), ..Material::vacuum() };
+    grid.set_materials(&MaterialMap::new((cells, 1, 1), medium)).unwrap();
+    let wave = |x: f64| seed*(2.0*PI*x/20.0).sin();
+    let shift = 0.5*C*grid.dt/DX;
+    for i in 0..cells {
+        grid.e[1][[i, 0, 0]] = wave(i as f64);
+        grid.b[2][[i, 0, 0]] = wave(i as f64 + 0.5 + shift)/C;
+    }
+    grid
+}
+
+/// N2 − N1 averaged over the ring.
+fn mean_inversion(grid: &YeeGrid) -> f64 {
+    let cells = grid.shape()[0];
+    (0..cells).map(|i| {
+        let [lower, upper] = grid.populations([i, 0, 0]).unwrap();
+        upper - lower
+    }).sum::<f64>()/cells as f64
+}
+
+#[test]
+fn slabs_absorb_or_amplify_by_their_population_difference() {
+    // Weak resonant light through 100 cells of atoms with |α| L = 1: e^{−1} in the ground
+    // state, e^{+1} nearly fully inverted
+    let length = 100.0*DX;
+    let target = 1.0/length;
+    let ground = {
+        let mut a = atoms(1e-9, 0.0, 0.0);
+        a.density = 2.0*EPSILON_0*a.linewidth*C*target/a.coupling();
+        a
+    };
+    let inverted = atoms(1e-9, 99.0, ground.density);
+    assert!((ground.attenuation()*length - 1.0).abs() < 1e-12);
+    assert!((inverted.attenuation()*length + 1.0).abs() < 1e-12);
+    assert!(Material { atoms: Some(ground), ..Material::vacuum() }.refractive_index(OMEGA).im < 0.0);
+
+    for (species, exponent) in [(ground, -1.0), (inverted, 1.0)] {
+        let cells = 400;
+        let mut grid = YeeGrid::new((cells, 1, 1), DX, yee::stable_time_step(DX)).unwrap();
+        let pml = Boundary::Pml { thickness: 20, grading: PmlGrading::default() };
+        grid.set_boundaries(Boundaries::default().with_axis(0, pml)).unwrap();
+        let mut map = MaterialMap::new((cells, 1, 1), Material::vacuum());
+        let slab = Shape::Slab { axis: 0, from: 150.0*DX, to: 150.0*DX + length };
+        map.paint(&slab, Material { atoms: Some(species), ..Material::vacuum() }, DX).unwrap();
+        grid.set_materials(&map).unwrap();
+        grid.add_source(Source::PlaneWave {
+            axis: 0, from: 30, to: 370,
+            polarization: Polarization::linear(0.0),
+            waveform: Waveform::ContinuousWave { amplitude: 1.0, frequency: OMEGA, ramp: 5.0*2.0*PI/OMEGA },
+        }).unwrap();
+        // Light crawls through the gain: give it time to settle
+        for _ in 0..2000 {
+            grid.step();
+        }
+        let mut transmitted = 0.0_f64;
+        for _ in 0..40 {
+            grid.step();
+            transmitted = transmitted.max(grid.e[1][[300, 0, 0]].abs());
+        }
+        assert!((transmitted.ln() - exponent).abs() < 0.05, "{:?}: transmitted {}", species, transmitted);
+    }
+}
+
+#[test]
+fn absorbed_light_ends_up_in_the_upper_level() {
+    // No pump, no losses: what the field loses is ħω0 per atom lifted
+    let cells = 40;
+    let species = {
+        let mut a = atoms(1e-9, 0.0, 0.0);
+        // Amplitude decays at α c = 1/(200 Δt)
+        a.density = 2.0*EPSILON_0*a.linewidth/(200.0*yee::stable_time_step(DX)*a.coupling());
+        a
+    };
+    let mut grid = ring(cells, species, 0.0, 1.0);
+    grid.step();
+    let initial = grid.energy();
+    for _ in 0..3000 {
+        grid.step();
+    }
+    assert!(grid.energy() < 1e-2*initial, "{} of the field is left", grid.energy()/initial);
+    let lifted: f64 = (0..cells).map(|i| grid.populations([i, 0, 0]).unwrap()[1]).sum();
+    let atomic = lifted*DX.powi(3)*HBAR*OMEGA;
+    assert!(((atomic + grid.energy())/initial - 1.0).abs() < 0.05, "atoms took {:e} J of {:e} J", atomic, initial);
+}
+
+#[test]
+fn lasing_starts_at_threshold_and_clamps_the_inversion() {
+    // Loss σ/(2ε0) = 1/(200 Δt) balances gain κ(N2 − N1)/(2ε0γ) at N2 − N1 = σγ/κ
+    let cells = 40;
+    let dt = yee::stable_time_step(DX);
+    let lifetime = 1000.0*dt;
+    let conductivity = 2.0*EPSILON_0/(200.0*dt);
+    let threshold = conductivity*0.1*OMEGA/atoms(lifetime, 3.0, 1.0).coupling();
+    let seed = 1e-6;
+
+    // Pumped to 2/3 of threshold the seed dies away
+    let mut below = ring(cells, atoms(lifetime, 3.0, 2.0*threshold/3.0), conductivity, seed);
+    for _ in 0..5000 {
+        below.step();
+    }
+    let field = |grid: &YeeGrid| (0..cells).map(|i| grid.e[1][[i, 0, 0]].abs()).fold(0.0, f64::max);
+    assert!(field(&below) < 1e-2*seed, "E = {:e} below threshold", field(&below));
+    assert!((mean_inversion(&below)/threshold - 2.0/3.0).abs() < 1e-3);
+
+    // Pumped to twice threshold it grows until stimulated emission pulls the inversion
+    // down to threshold
+    let mut above = ring(cells, atoms(lifetime, 3.0, 2.0*threshold), conductivity, seed);
+    for _ in 0..30000 {
+        above.step();
+    }
+    assert!(field(&above) > 1e3*seed, "E = {:e} above threshold", field(&above));
+    let clamped = mean_inversion(&above)/threshold;
+    assert!((clamped - 1.0).abs() < 0.1, "inversion at {} of threshold", clamped);
+}
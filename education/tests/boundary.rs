@@ -0,0 +1,94 @@
+use education::boundary::{Boundaries, Boundary, PmlGrading, Side};
+use education::yee::{self, YeeGrid};
+use education::{C, LATTICE_SPACING};
+use ndarray::Array3;
+
+/// A grid of `cells` along x (one cell across y and z, periodic) with `faces` on x.
+fn line(cells: usize, faces: Boundary) -> YeeGrid {
+    let mut grid = YeeGrid::new((cells, 1, 1), LATTICE_SPACING, yee::stable_time_step(LATTICE_SPACING)).unwrap();
+    grid.set_boundaries(Boundaries::default().with_axis(0, faces)).unwrap();
+    grid
+}
+
+/// A Gaussian E_y pulse of `width` cells centred on cell `centre`, travelling towards +x.
+fn launch(grid: &mut YeeGrid, centre: f64, width: f64) {
+    let pulse = |x: f64| (-((x - centre)/width).powi(2)).exp();
+    let cells = grid.shape()[0];
+    let shift = 0.5*C*grid.dt/grid.dx; // B lags E by half a step
+    for i in 1..cells - 1 {
+        grid.e[1][[i, 0, 0]] = pulse(i as f64);
+        grid.b[2][[i, 0, 0]] = pulse(i as f64 + 0.5 + shift)/C;
+    }
+}
+
+/// Field energy after `steps`, relative to the first step's.
+fn energy_left(grid: &mut YeeGrid, steps: usize) -> f64 {
+    grid.step();
+    let initial = grid.energy();
+    for _ in 0..steps {
+        grid.step();
+    }
+    grid.energy()/initial
+}
+
+const PML: Boundary = Boundary::Pml { thickness: 20, grading: PmlGrading { order: 3.0, sigma_scale: 1.0, kappa_max: 1.0, alpha_max: 0.0 } };
+
+#[test]
+fn pml_reflects_a_pulse_below_one_part_in_ten_thousand() {
+    // The pulse enters the layer after ~320 steps; at 650 its reflection is back in the interior
+    let mut grid = line(400, PML);
+    launch(&mut grid, 200.0, 8.0);
+    let reflection = energy_left(&mut grid, 650).sqrt();
+    assert!(reflection < 1e-4, "reflection coefficient {:e}", reflection);
+}
+
+#[test]
+fn pml_absorbs_waves_at_all_angles() {
+    // A point-like blob radiates in every direction of the xy plane
+    let cells = 80;
+    let mut grid = YeeGrid::new((cells, cells, 1), LATTICE_SPACING, yee::stable_time_step(LATTICE_SPACING)).unwrap();
+    grid.set_boundaries(Boundaries::default().with_axis(0, PML).with_axis(1, PML)).unwrap();
+    grid.e[2] = Array3::from_shape_fn((cells, cells, 1), |(i, j, _)| {
+        let r2 = (i as f64 - 40.0).powi(2) + (j as f64 - 40.0).powi(2);
+        (-r2/16.0).exp()
+    });
+    let left = energy_left(&mut grid, 600);
+    assert!(left < 1e-5, "{:e} of the energy is left", left);
+}
+
+#[test]
+fn mur_boundary_absorbs_at_normal_incidence() {
+    let mut grid = line(400, Boundary::Mur);
+    launch(&mut grid, 200.0, 8.0);
+    let reflection = energy_left(&mut grid, 650).sqrt();
+    assert!(reflection < 1e-2, "reflection coefficient {:e}", reflection);
+}
+
+#[test]
+fn conductors_reflect_everything_with_opposite_signs() {
+    // A pulse that has bounced once off an electric wall comes back with E inverted, off a
+    // magnetic wall with E unchanged; a closed cavity keeps its energy.
+    for (wall, sign) in [(Boundary::ElectricConductor, -1.0), (Boundary::MagneticConductor, 1.0)] {
+        let mut grid = line(200, wall);
+        launch(&mut grid, 100.0, 6.0);
+        // 100 cells to the wall and 100 back at 0.572 cells per step
+        let left = energy_left(&mut grid, 349);
+        assert!((left - 1.0).abs() < 1e-10, "{:?}: energy {}", wall, left);
+        let (peak, value) = (0..200).map(|i| (i, grid.e[1][[i, 0, 0]]))
+            .max_by(|a, b| a.1.abs().total_cmp(&b.1.abs())).unwrap();
+        assert!((90..=110).contains(&peak), "{:?}: peak at {}", wall, peak);
+        assert!(value*sign > 0.9, "{:?}: E_y = {} at the peak", wall, value);
+    }
+}
+
+#[test]
+fn faces_must_fit_the_grid() {
+    let mut grid = YeeGrid::new((30, 30, 1), LATTICE_SPACING, yee::stable_time_step(LATTICE_SPACING)).unwrap();
+    let one_sided = Boundaries::default().with_face(0, Side::Low, Boundary::Mur);
+    assert!(grid.set_boundaries(one_sided).unwrap_err().0.contains("periodic on one face"));
+    let thick = Boundaries::default().with_axis(1, Boundary::Pml { thickness: 15, grading: PmlGrading::default() });
+    assert!(grid.set_boundaries(thick).unwrap_err().0.contains("no interior"));
+    let flat = Boundaries::default().with_axis(2, Boundary::ElectricConductor);
+    assert!(grid.set_boundaries(flat).is_err());
+    assert!(grid.set_boundaries(Boundaries::uniform(Boundary::Periodic)).is_ok());
+}
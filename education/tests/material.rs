@@ -0,0 +1,231 @@
+use std::f64::consts::PI;
+
+use education::boundary::{Boundaries, Boundary, PmlGrading};
+use education::material::{Material, MaterialMap, Pole, Shape};
+use education::yee::{self, YeeGrid};
+use education::{C, EPSILON_0, LATTICE_SPACING};
+use num_complex::Complex;
+
+const DX: f64 = LATTICE_SPACING;
+
+/// A small periodic grid of `material` with a uniform E_x = 1, so that every curl vanishes
+/// and E evolves only through the material's response.
+fn uniform(material: Material) -> YeeGrid {
+    let shape = (2, 2, 2);
+    let mut grid = YeeGrid::new(shape, DX, yee::stable_time_step(DX)).unwrap();
+    grid.set_materials(&MaterialMap::new(shape, material)).unwrap();
+    grid.e[0].fill(1.0);
+    grid
+}
+
+/// E_x of a uniform grid after each of `steps` steps.
+fn history(grid: &mut YeeGrid, steps: usize) -> Vec<f64> {
+    (0..steps).map(|_| {
+        grid.step();
+        grid.e[0][[0, 0, 0]]
+    }).collect()
+}
+
+/// Angular frequency of an oscillation around `centre`, from its sign changes.
+fn frequency(samples: &[f64], centre: f64, dt: f64) -> f64 {
+    let crossings: Vec<f64> = samples.windows(2).enumerate()
+        .filter(|(_, w)| (w[0] - centre)*(w[1] - centre) < 0.0)
+        .map(|(n, w)| n as f64 + (w[0] - centre)/(w[0] - w[1]))
+        .collect();
+    let (first, last) = (crossings[0], crossings[crossings.len() - 1]);
+    PI*(crossings.len() - 1) as f64/((last - first)*dt)
+}
+
+#[test]
+fn primitives_paint_cells_in_order() {
+    let mut map = MaterialMap::new((10, 10, 10), Material::vacuum());
+    map.paint(&Shape::Slab { axis: 2, from: 5.0*DX, to: 1.0 }, Material::dielectric(2.0), DX).unwrap();
+    map.paint(&Shape::Sphere { centre: [5.0*DX; 3], radius: 2.5*DX }, Material::water(), DX).unwrap();
+    map.paint(&Shape::Cylinder { centre: [0.0; 3], axis: 1, radius: 1.5*DX }, Material::gold(), DX).unwrap();
+
+    assert_eq!(*map.material(8, 8, 4), Material::vacuum());
+    assert_eq!(*map.material(8, 8, 5), Material::dielectric(2.0));
+    assert_eq!(*map.material(5, 5, 3), Material::water());
+    assert_eq!(*map.material(5, 5, 7), Material::water());
+    assert_eq!(*map.material(5, 5, 8), Material::dielectric(2.0));
+    assert_eq!(*map.material(1, 9, 1), Material::gold());
+    assert_eq!(*map.material(2, 9, 1), Material::vacuum());
+
+    // Painting a material again reuses its palette entry
+    map.paint(&Shape::Slab { axis: 0, from: 0.0, to: DX }, Material::water(), DX).unwrap();
+    assert_eq!(map.materials.len(), 4);
+    assert!(map.paint(&Shape::Slab { axis: 0, from: 0.0, to: DX }, Material::dielectric(-1.0), DX).is_err());
+}
+
+#[test]
+fn voxel_maps_are_read_x_fastest() {
+    let text = "# two by two by one\n2 2 1\n0 1  # y = 0\n1 1\n";
+    let map = MaterialMap::from_voxels(text, vec![Material::vacuum(), Material::water()]).unwrap();
+    assert_eq!(map.cells.dim(), (2, 2, 1));
+    assert_eq!(*map.material(0, 0, 0), Material::vacuum());
+    assert_eq!(*map.material(1, 0, 0), Material::water());
+    assert_eq!(*map.material(0, 1, 0), Material::water());
+
+    let palette = || vec![Material::vacuum(), Material::water()];
+    let error = |text: &str| MaterialMap::from_voxels(text, palette()).unwrap_err().0;
+    assert!(error("2 2 1\n0 1 1").contains("ends before"));
+    assert!(error("2 2 1\n0 1 1 2").contains("palette of 2"));
+    assert!(error("2 2 1\n0 1 1 x").contains("'x'"));
+    assert!(error("1 1 1\n0 0").contains("more than"));
+
+    let mut grid = YeeGrid::new((3, 3, 3), DX, yee::stable_time_step(DX)).unwrap();
+    let map = MaterialMap::from_voxels("2 2 1\n0 1 1 1", palette()).unwrap();
+    assert!(grid.set_materials(&map).unwrap_err().0.contains("does not fit"));
+}
+
+#[test]
+fn water_index_falls_from_static_to_optical() {
+    let water = Material::water();
+    assert!((water.refractive_index(1e6).re - 80.1_f64.sqrt()).abs() < 1e-3);
+    let optical = water.refractive_index(2.0*PI*C/500e-9);
+    assert!((optical.re - 1.333).abs() < 1e-4, "n = {}", optical);
+    // Gold reflects visible light: its index is mostly imaginary
+    let gold = Material::gold().refractive_index(2.0*PI*C/800e-9);
+    assert!(gold.im.abs() > 3.0*gold.re, "n = {}", gold);
+}
+
+#[test]
+fn slabs_reflect_by_their_impedance() {
+    // A pulse from vacuum onto ε = 4 comes back with amplitude (1 − 2)/(1 + 2); onto
+    // ε = μ = 2, of vacuum's impedance, it does not come back at all. The pulse is wide
+    // enough to stay resolved at the slower speed inside.
+    let pml = Boundary::Pml { thickness: 20, grading: PmlGrading::default() };
+    for (slab, expected) in [(Material::dielectric(4.0), -1.0/3.0),
+                             (Material { permeability: 2.0, ..Material::dielectric(2.0) }, 0.0)] {
+        let cells = 600;
+        let mut grid = YeeGrid::new((cells, 1, 1), DX, yee::stable_time_step(DX)).unwrap();
+        grid.set_boundaries(Boundaries::default().with_axis(0, pml)).unwrap();
+        let mut map = MaterialMap::new((cells, 1, 1), Material::vacuum());
+        map.paint(&Shape::Slab { axis: 0, from: 350.0*DX, to: 1.0 }, slab.clone(), DX).unwrap();
+        grid.set_materials(&map).unwrap();
+
+        let pulse = |x: f64| (-((x - 200.0)/16.0).powi(2)).exp();
+        let shift = 0.5*C*grid.dt/DX;
+        for i in 0..cells {
+            grid.e[1][[i, 0, 0]] = pulse(i as f64);
+            grid.b[2][[i, 0, 0]] = pulse(i as f64 + 0.5 + shift)/C;
+        }
+        // 150 cells to the slab and ~100 back
+        for _ in 0..450 {
+            grid.step();
+        }
+        let reflected = (30..340).map(|i| grid.e[1][[i, 0, 0]])
+            .max_by(|a, b| a.abs().total_cmp(&b.abs())).unwrap();
+        assert!((reflected - expected).abs() < 5e-3, "{:?}: reflected {}", slab, reflected);
+    }
+}
+
+#[test]
+fn dispersive_surfaces_reflect_by_fresnel() {
+    // A pulse onto a Lorentz half-space comes back at ω with r = (1 − n)/(1 + n) of n(ω), as
+    // from a plain dielectric of that index, though the pole starts half a cell past ε∞.
+    // The reflection is the run with the slab less the run without.
+    let dt = yee::stable_time_step(DX);
+    let omega = 2.0*PI*C/(40.0*DX);
+    let lorentz = Material {
+        poles: vec![Pole::Lorentz { strength: 2.0, resonance: 2.0*omega, damping: 0.0 }],
+        ..Material::dielectric(2.0)
+    };
+    let n = lorentz.refractive_index(omega);
+    let pml = Boundary::Pml { thickness: 20, grading: PmlGrading::default() };
+    let cells = 1000;
+    let probe = |slab: Option<&Material>| -> Complex<f64> {
+        let mut grid = YeeGrid::new((cells, 1, 1), DX, dt).unwrap();
+        grid.set_boundaries(Boundaries::default().with_axis(0, pml)).unwrap();
+        let mut map = MaterialMap::new((cells, 1, 1), Material::vacuum());
+        if let Some(slab) = slab {
+            map.paint(&Shape::Slab { axis: 0, from: 500.0*DX, to: 1.0 }, slab.clone(), DX).unwrap();
+        }
+        grid.set_materials(&map).unwrap();
+
+        let pulse = |x: f64| (-((x - 250.0)/30.0).powi(2)).exp()*(2.0*PI*x/40.0).cos();
+        let shift = 0.5*C*dt/DX;
+        for i in 0..cells {
+            grid.e[1][[i, 0, 0]] = pulse(i as f64);
+            grid.b[2][[i, 0, 0]] = pulse(i as f64 + 0.5 + shift)/C;
+        }
+        // Past the probe at 400 and back from 500, before the transmitted wave returns
+        (0..1000).map(|step| {
+            grid.step();
+            Complex::from_polar(grid.e[1][[400, 0, 0]], -omega*step as f64*dt)
+        }).sum()
+    };
+    let incident = probe(None);
+    let lorentz_r = (probe(Some(&lorentz)) - incident)/incident;
+    let dielectric_r = (probe(Some(&Material::dielectric(n.re*n.re))) - incident)/incident;
+    // Both carry the grid's dispersion at 18 cells per wavelength inside
+    let expected = ((1.0 - n)/(1.0 + n)).norm();
+    assert!((lorentz_r.norm() - expected).abs() < 1e-2, "|r| = {} against {}", lorentz_r.norm(), expected);
+    assert!((lorentz_r - dielectric_r).norm() < 1e-3, "r = {} against {} for ε = n²", lorentz_r, dielectric_r);
+}
+
+#[test]
+fn conductors_drain_the_field() {
+    // ε0 ε dE/dt = −σE: E decays as exp(−σt/ε0ε)
+    let permittivity = 2.0;
+    let dt = yee::stable_time_step(DX);
+    let conductivity = EPSILON_0*permittivity/(100.0*dt);
+    let mut grid = uniform(Material { conductivity, ..Material::dielectric(permittivity) });
+    let e = history(&mut grid, 200);
+    let expected = (-2.0_f64).exp();
+    assert!((e[199]/expected - 1.0).abs() < 1e-3, "E = {} against {}", e[199], expected);
+}
+
+#[test]
+fn drude_electrons_oscillate_at_the_screened_plasma_frequency() {
+    // With no curl, P + ε0 ε∞ E is conserved and E rings at ω_p/√ε∞ around 0
+    let dt = yee::stable_time_step(DX);
+    let plasma_frequency = 0.05/dt;
+    let permittivity = 4.0;
+    let metal = Material {
+        poles: vec![Pole::Drude { plasma_frequency, collision_rate: 0.0 }],
+        ..Material::dielectric(permittivity)
+    };
+    let e = history(&mut uniform(metal), 4000);
+    let expected = plasma_frequency/permittivity.sqrt();
+    assert!((frequency(&e, 0.0, dt)/expected - 1.0).abs() < 1e-3);
+    assert!(e.iter().all(|v| v.abs() <= 1.0 + 1e-9));
+
+    // Gold's ω_p Δt ≈ 26 is far past any explicit scheme; the implicit one stays bounded
+    // and its collisions damp the field to 0
+    let e = history(&mut uniform(Material::gold()), 20000);
+    assert!(e.iter().all(|v| v.abs() <= 1.0 + 1e-9));
+    assert!(e[19999].abs() < 1e-3, "E = {}", e[19999]);
+}
+
+#[test]
+fn lorentz_resonance_is_pushed_up_by_its_own_polarization() {
+    // P'' + ω0² (1 + Δε/ε∞) P = ω0² Δε E0 ε0: oscillation at ω0 √(1 + Δε/ε∞)
+    let dt = yee::stable_time_step(DX);
+    let (strength, resonance, permittivity) = (3.0, 0.02/dt, 2.0);
+    let material = Material {
+        poles: vec![Pole::Lorentz { strength, resonance, damping: 0.0 }],
+        ..Material::dielectric(permittivity)
+    };
+    let e = history(&mut uniform(material), 4000);
+    // E swings about E0 ε∞/(ε∞ + Δε)
+    let centre = permittivity/(permittivity + strength);
+    let expected = resonance*(1.0 + strength/permittivity).sqrt();
+    assert!((frequency(&e, centre, dt)/expected - 1.0).abs() < 1e-3);
+}
+
+#[test]
+fn debye_relaxation_screens_the_field() {
+    // τ P' + (1 + Δε/ε∞) P = ε0 Δε E0: E relaxes to E0 ε∞/(ε∞ + Δε) at (1 + Δε/ε∞)/τ
+    let water = Material::water();
+    let Pole::Debye { strength, relaxation_time } = water.poles[0] else { unreachable!() };
+    let permittivity = water.permittivity;
+    let dt = yee::stable_time_step(DX);
+    let e = history(&mut uniform(water), 1000);
+    let rate = (1.0 + strength/permittivity)/relaxation_time;
+    let screened = permittivity/(permittivity + strength);
+    for n in [50, 200, 999] {
+        let expected = screened + (1.0 - screened)*(-rate*(n + 1) as f64*dt).exp();
+        assert!((e[n]/expected - 1.0).abs() < 1e-3, "step {}: E = {} against {}", n, e[n], expected);
+    }
+}
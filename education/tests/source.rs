@@ -0,0 +1,187 @@
+use std::f64::consts::PI;
+
+use education::boundary::{Boundaries, Boundary, PmlGrading};
+use education::material::{Material, MaterialMap, Shape};
+use education::source::{Polarization, Source, Waveform};
+use education::yee::{self, YeeGrid};
+use education::{C, EPSILON_0, LATTICE_SPACING};
+
+const DX: f64 = LATTICE_SPACING;
+const PML: Boundary = Boundary::Pml { thickness: 10, grading: PmlGrading { order: 3.0, sigma_scale: 1.0, kappa_max: 1.0, alpha_max: 0.0 } };
+
+/// A grid of `shape` with PML on the axes in `absorbing` and periodic faces elsewhere.
+fn grid(shape: (usize, usize, usize), absorbing: &[usize]) -> YeeGrid {
+    let mut grid = YeeGrid::new(shape, DX, yee::stable_time_step(DX)).unwrap();
+    let faces = absorbing.iter().fold(Boundaries::default(), |faces, &axis| faces.with_axis(axis, PML));
+    grid.set_boundaries(faces).unwrap();
+    grid
+}
+
+/// A baseband Gaussian pulse `cells` wide in space, starting from (almost) zero.
+fn pulse(amplitude: f64, cells: f64) -> Waveform {
+    let width = cells*DX/C;
+    Waveform::GaussianPulse { amplitude, delay: 5.0*width, width, frequency: 0.0 }
+}
+
+fn largest(values: impl Iterator<Item = f64>) -> f64 {
+    values.fold(0.0, |m, v| m.max(v.abs()))
+}
+
+#[test]
+fn waveforms_have_their_shapes() {
+    let ricker = Waveform::Ricker { amplitude: 2.0, peak_frequency: 1e14, delay: 1e-13 };
+    assert_eq!(ricker.signal(1e-13).re, 2.0);
+    assert_eq!(ricker.carrier(), None);
+    // A Ricker wavelet has no DC content
+    let dt = 1e-17;
+    let area: f64 = (0..20000).map(|n| ricker.signal(n as f64*dt).re*dt).sum();
+    assert!(area.abs() < 1e-9*2.0*1e-13, "∫ = {:e}", area);
+
+    let cw = Waveform::ContinuousWave { amplitude: 3.0, frequency: 1e15, ramp: 1e-14 };
+    assert_eq!(cw.signal(0.0).norm(), 0.0);
+    assert!((cw.signal(0.5e-14).norm() - 1.5).abs() < 1e-12);
+    assert!((cw.signal(2e-14).norm() - 3.0).abs() < 1e-12);
+    assert_eq!(cw.carrier(), Some(1e15));
+
+    let gaussian = Waveform::GaussianPulse { amplitude: 1.5, delay: 1e-14, width: 2e-15, frequency: 0.0 };
+    assert_eq!(gaussian.signal(1e-14).re, 1.5);
+    assert!((gaussian.signal(1.2e-14).re - 1.5*(-1.0_f64).exp()).abs() < 1e-12);
+}
+
+#[test]
+fn a_dipole_sheet_radiates_half_its_current_each_way() {
+    // One cell across y and z, periodic: the dipole is a sheet of current K = I l/Δx² and
+    // sends E = −η0 K/2 both ways
+    let moment = 1e-12;
+    let mut grid = grid((400, 1, 1), &[0]);
+    grid.add_source(Source::Dipole { position: [200, 0, 0], axis: 1, waveform: pulse(moment, 20.0) }).unwrap();
+    let (mut left, mut right) = (0.0_f64, 0.0_f64);
+    for _ in 0..400 {
+        grid.step();
+        left = left.max(-grid.e[1][[150, 0, 0]]);
+        right = right.max(-grid.e[1][[250, 0, 0]]);
+    }
+    let expected = moment/(EPSILON_0*C)/(2.0*DX*DX);
+    assert!((left/expected - 1.0).abs() < 1e-2, "E = {:e} against {:e}", left, expected);
+    assert!((right/left - 1.0).abs() < 1e-12);
+    assert!((grid.time() - 400.0*grid.dt).abs() < 1e-12*grid.time());
+}
+
+#[test]
+fn plane_waves_stay_in_the_total_field() {
+    // Along y: E_z is the first transverse component, E_x the second
+    let angle = 0.3;
+    let source = Source::PlaneWave {
+        axis: 1, from: 100, to: 200,
+        polarization: Polarization::linear(angle),
+        waveform: pulse(1.0, 15.0),
+    };
+    let mut grid = grid((1, 300, 1), &[1]);
+    grid.add_source(source).unwrap();
+    let scattered = |grid: &YeeGrid| largest((12..99).chain(202..288)
+        .flat_map(|j| [grid.e[0][[0, j, 0]], grid.e[2][[0, j, 0]]]));
+
+    // The peak passes node 100 after 131 steps and is halfway across at 218
+    for _ in 0..218 {
+        grid.step();
+    }
+    assert!(scattered(&grid) < 1e-12, "{:e} leaks out", scattered(&grid));
+    let ez = largest((100..=200).map(|j| grid.e[2][[0, j, 0]]));
+    let ex = largest((100..=200).map(|j| grid.e[0][[0, j, 0]]));
+    assert!((ez - angle.cos()).abs() < 1e-2 && (ex - angle.sin()).abs() < 1e-2, "E_z = {}, E_x = {}", ez, ex);
+
+    // Once it has left through node 200 the scattered field is still empty, and the total
+    // field holds no more than what the incident line's absorber sends back
+    for _ in 218..450 {
+        grid.step();
+    }
+    assert!(scattered(&grid) < 1e-12, "{:e} leaks out", scattered(&grid));
+    let everywhere = largest((12..288).flat_map(|j| [grid.e[0][[0, j, 0]], grid.e[2][[0, j, 0]]]));
+    assert!(everywhere < 1e-5, "{:e} is left", everywhere);
+
+    // A dielectric inside the total field throws some of it back into the scattered field
+    let mut grid = self::grid((1, 300, 1), &[1]);
+    let mut map = MaterialMap::new((1, 300, 1), Material::vacuum());
+    map.paint(&Shape::Slab { axis: 1, from: 150.0*DX, to: 170.0*DX }, Material::dielectric(4.0), DX).unwrap();
+    grid.set_materials(&map).unwrap();
+    grid.add_source(source).unwrap();
+    for _ in 0..400 {
+        grid.step();
+    }
+    assert!(largest((12..99).map(|j| grid.e[2][[0, j, 0]])) > 0.1);
+}
+
+#[test]
+fn circular_polarization_turns_at_constant_strength() {
+    let omega = 2.0*PI*C/(20.0*DX);
+    for (polarization, turn) in [(Polarization::right_circular(), 1.0), (Polarization::left_circular(), -1.0)] {
+        let mut grid = grid((200, 1, 1), &[0]);
+        grid.add_source(Source::PlaneWave {
+            axis: 0, from: 50, to: 150, polarization,
+            waveform: Waveform::ContinuousWave { amplitude: 1.0, frequency: omega, ramp: 5.0*2.0*PI/omega },
+        }).unwrap();
+        for _ in 0..500 {
+            grid.step();
+        }
+        let field = |grid: &YeeGrid| (grid.e[1][[100, 0, 0]], grid.e[2][[100, 0, 0]]);
+        let mut angle = field(&grid).1.atan2(field(&grid).0);
+        for _ in 0..20 {
+            grid.step();
+            let (ey, ez) = field(&grid);
+            assert!(((ey*ey + ez*ez).sqrt() - 0.5_f64.sqrt()).abs() < 2e-2, "|E| = {}", (ey*ey + ez*ez).sqrt());
+            let next = ez.atan2(ey);
+            let step = (next - angle + PI).rem_euclid(2.0*PI) - PI;
+            assert!(step*turn > 0.0, "E turns by {} from y towards z", step);
+            angle = next;
+        }
+    }
+}
+
+#[test]
+fn gaussian_beams_keep_their_waist_and_their_direction() {
+    // A beam along x focused on its entry plane, E along z, a waist of one wavelength
+    let (cells_x, cells_y) = (100, 100);
+    let omega = 2.0*PI*C/(10.0*DX);
+    let waist = 10.0*DX;
+    let mut grid = grid((cells_x, cells_y, 1), &[0, 1]);
+    grid.add_source(Source::GaussianBeam {
+        axis: 0, plane: 25, focus: [25.0*DX, 50.0*DX, 0.0], waist,
+        polarization: Polarization::linear(0.5*PI),
+        waveform: Waveform::ContinuousWave { amplitude: 1.0, frequency: omega, ramp: 3.0*2.0*PI/omega },
+    }).unwrap();
+    for _ in 0..300 {
+        grid.step();
+    }
+    // Amplitudes over one period
+    let mut amplitude = vec![0.0_f64; cells_y];
+    let mut behind = 0.0_f64;
+    for _ in 0..20 {
+        grid.step();
+        for (j, a) in amplitude.iter_mut().enumerate() {
+            *a = a.max(grid.e[2][[27, j, 0]].abs());
+        }
+        behind = behind.max(largest((12..24).flat_map(|i| (12..88).map(move |j| (i, j))).map(|(i, j)| grid.e[2][[i, j, 0]])));
+    }
+    assert!((amplitude[50] - 1.0).abs() < 0.02, "on axis {}", amplitude[50]);
+    for offset in [10, -10] {
+        let ratio = amplitude[(50 + offset) as usize]/amplitude[50];
+        assert!((ratio - (-1.0_f64).exp()).abs() < 0.02, "{} cells off axis: {}", offset, ratio);
+    }
+    // Only the part of the beam the incident line's plane wave misses goes the wrong way
+    assert!(behind < 0.03, "{} leaks backwards", behind);
+}
+
+#[test]
+fn sources_must_fit_the_grid() {
+    let mut grid = grid((30, 30, 1), &[0]);
+    let waveform = Waveform::Ricker { amplitude: 1.0, peak_frequency: 1e14, delay: 0.0 };
+    let plane = |axis, from, to| Source::PlaneWave { axis, from, to, polarization: Polarization::linear(0.0), waveform };
+    assert!(grid.add_source(plane(1, 5, 20)).unwrap_err().0.contains("periodic faces on axis 0"));
+    assert!(grid.add_source(plane(0, 0, 20)).unwrap_err().0.contains("no scattered field"));
+    assert!(grid.add_source(plane(0, 5, 28)).is_err());
+    assert!(grid.add_source(plane(0, 5, 20)).is_ok());
+    assert!(grid.add_source(Source::Dipole { position: [3, 30, 0], axis: 0, waveform }).is_err());
+    let beam = |focus| Source::GaussianBeam { axis: 0, plane: 5, focus, waist: 4.0*DX, polarization: Polarization::linear(0.0), waveform };
+    assert!(grid.add_source(beam([15.0*DX, 15.0*DX, 0.0])).unwrap_err().0.contains("carrier"));
+    assert!(grid.add_source(beam([5.0*DX, 15.0*DX, 0.0])).is_ok());
+}
@@ -0,0 +1,80 @@
+use std::f64::consts::PI;
+
+use education::yee::{self, YeeGrid};
+use education::{C, LATTICE_SPACING};
+use ndarray::Array3;
+use num_complex::Complex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn time_step_must_satisfy_the_courant_condition() {
+    let limit = yee::courant_limit(LATTICE_SPACING);
+    assert!((limit - LATTICE_SPACING/(C*3.0_f64.sqrt())).abs() < 1e-12*limit);
+    assert!(YeeGrid::new((4, 4, 4), LATTICE_SPACING, yee::stable_time_step(LATTICE_SPACING)).is_ok());
+    let error = YeeGrid::new((4, 4, 4), LATTICE_SPACING, 1.01*limit).err().unwrap();
+    assert_eq!(error.limit, limit);
+    assert!(error.to_string().contains("Courant limit"), "{}", error);
+}
+
+#[test]
+fn energy_is_conserved_in_vacuum_and_dielectrics() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for dielectric in [false, true] {
+        let shape = (8, 8, 8);
+        let mut grid = YeeGrid::new(shape, LATTICE_SPACING, yee::stable_time_step(LATTICE_SPACING)).unwrap();
+        for component in grid.e.iter_mut() {
+            *component = Array3::from_shape_fn(shape, |_| rng.gen_range(-1.0..1.0));
+        }
+        for component in grid.b.iter_mut() {
+            *component = Array3::from_shape_fn(shape, |_| rng.gen_range(-1.0..1.0)/C);
+        }
+        if dielectric {
+            grid.permittivity = Array3::from_shape_fn(shape, |_| rng.gen_range(1.0..4.0));
+        }
+
+        grid.step();
+        let initial = grid.energy();
+        let mut worst: f64 = 0.0;
+        for _ in 0..2000 {
+            grid.step();
+            worst = worst.max((grid.energy()/initial - 1.0).abs());
+        }
+        assert!(worst < 1e-10, "dielectric {}: energy drifts by {:e}", dielectric, worst);
+    }
+}
+
+#[test]
+fn plane_waves_travel_at_c_over_n() {
+    for n in [1.0, 1.333] {
+        let cells = 64;
+        let dx = LATTICE_SPACING;
+        let mut grid = YeeGrid::new((cells, 1, 1), dx, yee::stable_time_step(dx)).unwrap();
+        grid.permittivity.fill(n*n);
+
+        // E_y = sin(kx − ωt) at t = 0 and B_z = (n/c) E_y at t = −Δt/2, half a cell over
+        let k = 2.0*PI/(cells as f64*dx);
+        let omega = k*C/n;
+        for i in 0..cells {
+            grid.e[1][[i, 0, 0]] = (k*i as f64*dx).sin();
+            grid.b[2][[i, 0, 0]] = n/C*(k*(i as f64 + 0.5)*dx + 0.5*omega*grid.dt).sin();
+        }
+
+        // The mode amplitude Σ E_y e^{−ikx} turns as e^{−iωt}
+        let amplitude = |grid: &YeeGrid| (0..cells)
+            .map(|i| grid.e[1][[i, 0, 0]]*Complex::from_polar(1.0, -k*i as f64*dx))
+            .sum::<Complex<f64>>();
+        let start = amplitude(&grid);
+        let (mut previous, mut turned) = (start, 0.0);
+        let steps = 1000;
+        for _ in 0..steps {
+            grid.step();
+            let current = amplitude(&grid);
+            turned += (current/previous).arg();
+            previous = current;
+        }
+        let speed = -turned/(steps as f64*grid.dt)/k;
+        assert!((speed*n/C - 1.0).abs() < 1e-3, "n = {}: v = {:e} m/s", n, speed);
+        assert!((previous.norm()/start.norm() - 1.0).abs() < 1e-3, "n = {}: amplitude {}", n, previous.norm()/start.norm());
+    }
+}
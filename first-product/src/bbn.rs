@@ -0,0 +1,508 @@
+// Big Bang nucleosynthesis in the expansion history of the run.
+//
+// The run is integrated through the MeV era and tabulated as T(t); the light elements
+// then follow from a nuclear network in the mole fractions Y_i = n_i/n_b of
+// n, p, d, t, ³He, ⁴He, ⁷Li and ⁷Be. A reaction i + j → k + l proceeds at
+//   dY_k/dt = ρ_b N_A<σv> Y_i Y_j/(1 + δ_ij) − (reverse),
+// with ρ_b = η n_γ m_u g*s(T)/g*s(today) the baryon density (n_b/s is constant) and the
+// forward N_A<σv>(T9) read from a rate table, `data/bbn_rates.csv` by default. Reverse rates
+// follow by detailed balance: N_A<σv>_rev = a e^{−Q9/T9} N_A<σv> for two-body exit channels
+// and the photodisintegration rate λ_γ = a 10¹⁰ T9^{3/2} e^{−Q9/T9} N_A<σv> per nucleus.
+//
+// Neutrons and protons interconvert by n ν ↔ p e, n e⁺ ↔ p ν̄ and n → p e ν̄ at the Born
+// rates, with x = E_e/m_e, q = Q/m_e, f_e(x) = 1/(e^{x m_e/T} + 1), f_ν(x) = 1/(e^{x m_e/T_ν} + 1)
+//   λ_{n→p} = K ∫_1^∞ x (x² − 1)^{1/2} [(x − q)² f_ν(x − q) (1 − f_e(x)) + (x + q)² f_e(x) (1 − f_ν(x + q))] dx
+// and λ_{p→n} the same with q → −q; K is fixed by λ_{n→p}(T → 0) = 1/τ_n.
+//
+// With ν > 0 the vacuum decays into the radiation, which then cools at −d ln T/dt =
+// (1 − ν)^{1/2} H_r rather than H_r of a standard radiation era at the same T. The weak
+// rates keep n/p in equilibrium down to a lower temperature and the neutrons have longer to
+// decay before deuterium forms, which leaves less helium; `bbn` confronts the yields with
+// the observed Y_p and D/H.
+
+use std::f64::consts::PI;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::integrator::{self, IntegrationError, IntegrationStats, OdeSystem, StiffMethod, Tolerances};
+use crate::model::{RunningVacuum, State, MAX_EFOLDS};
+use crate::params::RunConfig;
+use crate::plasma::{self, Plasma};
+
+/// Network window in photon temperature, GeV: from n/p equilibrium to the end of fusion.
+const T_START: f64 = 1.0e-2;
+pub const T_END: f64 = 5.0e-6;
+
+/// Neutron lifetime, s, and the n − p mass difference and electron mass, GeV.
+const NEUTRON_LIFETIME: f64 = 879.4;
+const Q_NP: f64 = 1.29333e-3;
+const ELECTRON_MASS: f64 = 0.51099895e-3;
+
+/// ħ in GeV s, (ħc)⁻³ in cm⁻³ GeV⁻³, the atomic mass unit in g and T9 per GeV.
+const HBAR: f64 = 6.582119569e-25;
+const PER_CM3: f64 = 1.302_3e41;
+const ATOMIC_MASS: f64 = 1.660_539e-24;
+const T9_PER_GEV: f64 = 1.160_452e4;
+
+/// ζ(3)
+const ZETA3: f64 = 1.202_056_903;
+
+/// Weak rates: table points per e-fold in T and Simpson panels of the Born integral.
+const WEAK_POINTS_PER_EFOLD: f64 = 32.0;
+const WEAK_PANELS: usize = 512;
+
+/// Observed primordial abundances with their errors, the network's own uncertainty
+/// (Born weak rates, the rate fits) added in quadrature.
+const HELIUM_OBSERVED: (f64, f64) = (0.245, 0.003);
+const HELIUM_THEORY: f64 = 0.002;
+const DEUTERIUM_OBSERVED: (f64, f64) = (2.547e-5, 0.025e-5);
+const DEUTERIUM_THEORY: f64 = 0.06e-5;
+
+/// Δχ² of 95% confidence for one degree of freedom.
+pub const CHI2_95: f64 = 3.84;
+
+/// Subdivisions of each η interval when minimising χ² over the interpolated yields.
+const REFINE: usize = 100;
+
+const DEFAULT_RATES: &str = include_str!("../data/bbn_rates.csv");
+
+/// Error raised while reading the rate table or the bbn flags.
+#[derive(Debug)]
+pub enum BbnError {
+    Io(PathBuf, std::io::Error),
+    Data { path: PathBuf, line: usize, message: String },
+    Usage(String),
+}
+
+impl fmt::Display for BbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BbnError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            BbnError::Data { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            BbnError::Usage(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for BbnError {}
+
+/// Indices of the nuclides in the network state.
+const N: usize = 0;
+const P: usize = 1;
+const D: usize = 2;
+const T: usize = 3;
+const HE3: usize = 4;
+const HE4: usize = 5;
+const LI7: usize = 6;
+const BE7: usize = 7;
+const NUCLIDES: usize = 8;
+
+/// A two-body reaction: reactants → products, with the detailed-balance coefficient a and
+/// Q9 = Q/(10⁹ K) of the reverse; a single product means a radiative capture.
+struct Reaction {
+    name: &'static str,
+    reactants: [usize; 2],
+    products: &'static [usize],
+    reverse: f64,
+    q9: f64,
+}
+
+const REACTIONS: [Reaction; 12] = [
+    Reaction { name: "p+n->d+g", reactants: [P, N], products: &[D], reverse: 0.471, q9: 25.82 },
+    Reaction { name: "d+p->3He+g", reactants: [D, P], products: &[HE3], reverse: 1.63, q9: 63.75 },
+    Reaction { name: "d+d->3He+n", reactants: [D, D], products: &[N, HE3], reverse: 1.73, q9: 37.94 },
+    Reaction { name: "d+d->t+p", reactants: [D, D], products: &[P, T], reverse: 1.73, q9: 46.80 },
+    Reaction { name: "t+d->4He+n", reactants: [T, D], products: &[N, HE4], reverse: 5.54, q9: 204.1 },
+    Reaction { name: "t+p->4He+g", reactants: [T, P], products: &[HE4], reverse: 2.61, q9: 229.9 },
+    Reaction { name: "3He+n->t+p", reactants: [HE3, N], products: &[P, T], reverse: 1.001, q9: 8.864 },
+    Reaction { name: "3He+d->4He+p", reactants: [HE3, D], products: &[P, HE4], reverse: 5.55, q9: 212.4 },
+    Reaction { name: "3He+4He->7Be+g", reactants: [HE3, HE4], products: &[BE7], reverse: 1.113, q9: 18.42 },
+    Reaction { name: "t+4He->7Li+g", reactants: [T, HE4], products: &[LI7], reverse: 1.113, q9: 28.63 },
+    Reaction { name: "7Be+n->7Li+p", reactants: [BE7, N], products: &[P, LI7], reverse: 1.001, q9: 19.07 },
+    Reaction { name: "7Li+p->4He+4He", reactants: [LI7, P], products: &[HE4, HE4], reverse: 4.69, q9: 201.3 },
+];
+
+/// Forward rates N_A<σv> of `REACTIONS`, tabulated in ln T9.
+#[derive(Debug, Clone)]
+pub struct RateTable {
+    ln_t9: Vec<f64>,
+    ln_rates: Vec<[f64; 12]>,
+}
+
+impl RateTable {
+    /// The rate table shipped in `data/bbn_rates.csv`.
+    pub fn bundled() -> Result<Self, BbnError> {
+        RateTable::parse(DEFAULT_RATES, Path::new("data/bbn_rates.csv"))
+    }
+
+    /// Parses a table with a `T9` column and one column per reaction; lines starting with
+    /// '#' are comments.
+    pub fn parse(text: &str, path: &Path) -> Result<Self, BbnError> {
+        let error = |line: usize, message: String| BbnError::Data { path: path.to_path_buf(), line, message };
+        let mut lines = text.lines().enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+        let (header_line, header) = lines.next().ok_or_else(|| error(1, "empty table".to_string()))?;
+        let names: Vec<&str> = header.split(',').map(str::trim).collect();
+        let column = |name: &str| names.iter().position(|n| *n == name)
+            .ok_or_else(|| error(header_line, format!("missing column '{}'", name)));
+        let t9_column = column("T9")?;
+        let mut columns = [0; 12];
+        for (c, reaction) in columns.iter_mut().zip(&REACTIONS) {
+            *c = column(reaction.name)?;
+        }
+
+        let (mut ln_t9, mut ln_rates) = (Vec::new(), Vec::new());
+        for (line, text) in lines {
+            let fields: Vec<f64> = text.split(',')
+                .map(|f| f.trim().parse::<f64>())
+                .collect::<Result<_, _>>()
+                .map_err(|_| error(line, format!("expected {} numeric columns", names.len())))?;
+            if fields.len() != names.len() || fields.iter().any(|&v| !(v > 0.0 && v.is_finite())) {
+                return Err(error(line, format!("expected {} positive numbers", names.len())));
+            }
+            if ln_t9.last().is_some_and(|&last| fields[t9_column].ln() <= last) {
+                return Err(error(line, "T9 must increase down the table".to_string()));
+            }
+            ln_t9.push(fields[t9_column].ln());
+            ln_rates.push(columns.map(|c| fields[c].ln()));
+        }
+        if ln_t9.len() < 2 {
+            return Err(error(header_line, "need at least two temperatures".to_string()));
+        }
+        Ok(RateTable { ln_t9, ln_rates })
+    }
+
+    /// N_A<σv> of every reaction at T9, interpolated in logarithms and held at the
+    /// values of the table edges beyond them.
+    fn rates(&self, t9: f64) -> [f64; 12] {
+        let (i, u) = bracket(&self.ln_t9, t9.ln());
+        let (a, b) = (&self.ln_rates[i], &self.ln_rates[i + 1]);
+        std::array::from_fn(|k| (a[k] + u*(b[k] - a[k])).exp())
+    }
+}
+
+/// Interval i of an increasing grid holding x and the fraction u ∈ [0, 1] across it;
+/// x is clamped to the grid.
+fn bracket(grid: &[f64], x: f64) -> (usize, f64) {
+    let last = grid.len() - 1;
+    let x = x.clamp(grid[0], grid[last]);
+    let i = grid.partition_point(|&g| g <= x).clamp(1, last) - 1;
+    (i, (x - grid[i])/(grid[i + 1] - grid[i]))
+}
+
+fn simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, panels: usize) -> f64 {
+    let h = (b - a)/(2*panels) as f64;
+    let inner: f64 = (1..2*panels).map(|k| f(a + k as f64*h)*if k % 2 == 1 { 4.0 } else { 2.0 }).sum();
+    h/3.0*(f(a) + inner + f(b))
+}
+
+/// Born integral of λ_{n→p} (q > 0) or λ_{p→n} (q < 0) without K, at photon and
+/// neutrino temperatures T and T_ν; x = cosh u removes the square root at x = 1.
+fn born_integral(q: f64, t: f64, t_nu: f64) -> f64 {
+    let (z, z_nu) = (ELECTRON_MASS/t, ELECTRON_MASS/t_nu);
+    let fermi = |e: f64, z: f64| 1.0/((e*z).exp() + 1.0);
+    let integrand = |u: f64| {
+        let (x, s) = (u.cosh(), u.sinh());
+        let capture = (x - q).powi(2)*fermi(x - q, z_nu)*(1.0 - fermi(x, z));
+        let positron = (x + q).powi(2)*fermi(x, z)*(1.0 - fermi(x + q, z_nu));
+        x*s*s*(capture + positron)
+    };
+    let x_max = q.abs() + 60.0/z.min(z_nu) + 2.0;
+    simpson(integrand, 0.0, x_max.acosh(), WEAK_PANELS)
+}
+
+/// n ↔ p rates in s⁻¹, tabulated in ln T over the network window.
+#[derive(Debug, Clone)]
+struct WeakRates {
+    ln_t: Vec<f64>,
+    ln_rates: Vec<[f64; 2]>, // [n → p, p → n]
+}
+
+impl WeakRates {
+    fn new() -> Self {
+        let q = Q_NP/ELECTRON_MASS;
+        // Free decay: T → 0 leaves ∫_1^q x (x² − 1)^{1/2} (q − x)² dx
+        let k = 1.0/(NEUTRON_LIFETIME*born_integral(q, 1e-3*ELECTRON_MASS, 1e-3*ELECTRON_MASS));
+        let (lo, hi) = ((0.5*T_END).ln(), (2.0*T_START).ln());
+        let count = ((hi - lo)*WEAK_POINTS_PER_EFOLD).ceil() as usize + 1;
+        let ln_t: Vec<f64> = (0..count).map(|i| lo + (hi - lo)*i as f64/(count - 1) as f64).collect();
+        let ln_rates = ln_t.iter().map(|l| {
+            let (t, t_nu) = (l.exp(), plasma::neutrino_temperature(l.exp()));
+            [(k*born_integral(q, t, t_nu)).ln(), (k*born_integral(-q, t, t_nu)).ln()]
+        }).collect();
+        WeakRates { ln_t, ln_rates }
+    }
+
+    fn rates(&self, t: f64) -> [f64; 2] {
+        let (i, u) = bracket(&self.ln_t, t.ln());
+        let (a, b) = (self.ln_rates[i], self.ln_rates[i + 1]);
+        [(a[0] + u*(b[0] - a[0])).exp(), (a[1] + u*(b[1] - a[1])).exp()]
+    }
+}
+
+/// Photon temperature of the run against cosmic time through the MeV era, with the
+/// expansion rate of its radiation alone, H_r = (ρ_r/3 MP²)^{1/2}, and ρ_m/ρ_r.
+#[derive(Debug, Clone)]
+pub struct ExpansionHistory {
+    ln_time: Vec<f64>, // ln(t/s)
+    ln_temperature: Vec<f64>,
+    radiation_hubble: Vec<f64>, // GeV
+    pub matter_ratio: Vec<f64>,
+}
+
+impl ExpansionHistory {
+    /// Integrates the run from the primeval phase until T falls below the network window.
+    pub fn of_run(model: &RunningVacuum, config: &RunConfig) -> Result<Self, IntegrationError> {
+        let mut history = ExpansionHistory {
+            ln_time: Vec::new(),
+            ln_temperature: Vec::new(),
+            radiation_hubble: Vec::new(),
+            matter_ratio: Vec::new(),
+        };
+        let start = model.initial_state();
+        let mut stats = IntegrationStats::default();
+        integrator::integrate(model, &config.integration.method(), start.n, &start.to_vec(), MAX_EFOLDS,
+                              &mut stats, |n, y| {
+            let state = State::from_vec(n, y);
+            let temperature = model.radiation(&state).temperature;
+            if temperature < 2.0*T_START {
+                history.ln_time.push((state.t*HBAR).ln());
+                history.ln_temperature.push(temperature.ln());
+                history.radiation_hubble.push(model.fluid_fractions(&state).0.sqrt()*model.hubble(state.z));
+                history.matter_ratio.push((state.ln_rho_m - state.ln_rho_r).exp());
+            }
+            temperature >= 0.5*T_END
+        })?;
+        Ok(history)
+    }
+
+    /// Row interval holding temperature T; ln T falls along the table, so bracket in −ln T.
+    fn bracket_temperature(&self, temperature: f64) -> (usize, f64) {
+        let falling: Vec<f64> = self.ln_temperature.iter().map(|l| -l).collect();
+        bracket(&falling, -temperature.ln())
+    }
+
+    /// ln(t/s) when the photon temperature is T.
+    pub fn ln_time_at(&self, temperature: f64) -> f64 {
+        let (i, u) = self.bracket_temperature(temperature);
+        self.ln_time[i] + u*(self.ln_time[i + 1] - self.ln_time[i])
+    }
+
+    /// Cooling rate −d ln T/dt in units of H_r at temperature T; 1 in a standard radiation
+    /// era while g*s is constant.
+    pub fn cooling_at(&self, temperature: f64) -> f64 {
+        let (i, u) = self.bracket_temperature(temperature);
+        let slope = (self.ln_temperature[i + 1] - self.ln_temperature[i])/(self.ln_time[i + 1] - self.ln_time[i]);
+        let time = (self.ln_time[i] + u*(self.ln_time[i + 1] - self.ln_time[i])).exp()/HBAR;
+        -slope/time/(self.radiation_hubble[i] + u*(self.radiation_hubble[i + 1] - self.radiation_hubble[i]))
+    }
+
+    fn temperature(&self, ln_time: f64) -> f64 {
+        let (i, u) = bracket(&self.ln_time, ln_time);
+        (self.ln_temperature[i] + u*(self.ln_temperature[i + 1] - self.ln_temperature[i])).exp()
+    }
+}
+
+/// The network in x = ln(t/s) at one baryon-to-photon ratio.
+pub struct Network<'a> {
+    pub eta: f64,
+    expansion: &'a ExpansionHistory,
+    plasma: &'a Plasma,
+    rates: &'a RateTable,
+    weak: WeakRates,
+    g_star_s_today: f64,
+}
+
+/// Reaction rates per unit of each reactant monomial at one time.
+struct Rates {
+    forward: [f64; 12], // ρ_b N_A<σv>/(1 + δ_ij), s⁻¹
+    reverse: [f64; 12], // λ_γ or ρ_b N_A<σv>_rev/(1 + δ_kl), s⁻¹
+    weak: [f64; 2],
+}
+
+/// Π Y over `species`.
+fn monomial(y: &[f64], species: &[usize]) -> f64 {
+    species.iter().map(|&s| y[s]).product()
+}
+
+/// ∂(Π Y over `species`)/∂Y_j.
+fn monomial_derivative(y: &[f64], species: &[usize], j: usize) -> f64 {
+    (0..species.len())
+        .filter(|&k| species[k] == j)
+        .map(|k| species.iter().enumerate().filter(|&(l, _)| l != k).map(|(_, &s)| y[s]).product::<f64>())
+        .sum()
+}
+
+/// 1/2 for two identical particles, else 1.
+fn symmetry(species: &[usize]) -> f64 {
+    if species.len() == 2 && species[0] == species[1] { 0.5 } else { 1.0 }
+}
+
+impl<'a> Network<'a> {
+    pub fn new(eta: f64, expansion: &'a ExpansionHistory, plasma: &'a Plasma, rates: &'a RateTable) -> Self {
+        Network { eta, expansion, plasma, rates, weak: WeakRates::new(), g_star_s_today: plasma.points[0].g_star_s() }
+    }
+
+    /// Baryon density in g/cm³ at photon temperature T.
+    fn baryon_density(&self, temperature: f64) -> f64 {
+        let photons = 2.0*ZETA3/(PI*PI)*temperature.powi(3)*PER_CM3;
+        self.eta*photons*self.plasma.entropy_dof(temperature)/self.g_star_s_today*ATOMIC_MASS
+    }
+
+    fn rates(&self, ln_time: f64) -> Rates {
+        let temperature = self.expansion.temperature(ln_time);
+        let t9 = temperature*T9_PER_GEV;
+        let rho_b = self.baryon_density(temperature);
+        let forward_rates = self.rates.rates(t9);
+        let mut rates = Rates { forward: [0.0; 12], reverse: [0.0; 12], weak: self.weak.rates(temperature) };
+        for (k, reaction) in REACTIONS.iter().enumerate() {
+            let f = forward_rates[k];
+            let balance = reaction.reverse*(-reaction.q9/t9).exp()*f;
+            rates.forward[k] = rho_b*f*symmetry(&reaction.reactants);
+            rates.reverse[k] = if reaction.products.len() == 1 {
+                balance*1e10*t9.powf(1.5)
+            } else {
+                rho_b*balance*symmetry(reaction.products)
+            };
+        }
+        rates
+    }
+
+    /// Mole fractions at the start of the window: free nucleons in weak equilibrium.
+    pub fn initial_abundances(&self) -> Vec<f64> {
+        let neutrons = 1.0/(1.0 + (Q_NP/T_START).exp());
+        let mut y = vec![0.0; NUCLIDES];
+        y[N] = neutrons;
+        y[P] = 1.0 - neutrons;
+        y
+    }
+
+    /// Integrates the network across the window.
+    pub fn evolve(&self) -> Result<Abundances, IntegrationError> {
+        let method = StiffMethod { tolerances: Tolerances { rtol: 1e-5, atol: 1e-16 }, initial_step: 1e-3 };
+        let (x0, x1) = (self.expansion.ln_time_at(T_START), self.expansion.ln_time_at(T_END));
+        let mut stats = IntegrationStats::default();
+        let (_, y) = integrator::integrate_stiff(self, &method, x0, &self.initial_abundances(), x1, &mut stats,
+                                                 |_, _| true)?;
+        Ok(Abundances::of(&y))
+    }
+}
+
+impl OdeSystem for Network<'_> {
+    fn dim(&self) -> usize {
+        NUCLIDES
+    }
+
+    fn derivatives(&self, x: f64, y: &[f64], dydx: &mut [f64]) {
+        let rates = self.rates(x);
+        let time = x.exp();
+        dydx.fill(0.0);
+        let weak = rates.weak[0]*y[N] - rates.weak[1]*y[P];
+        dydx[N] -= weak;
+        dydx[P] += weak;
+        for (k, reaction) in REACTIONS.iter().enumerate() {
+            let net = rates.forward[k]*monomial(y, &reaction.reactants) - rates.reverse[k]*monomial(y, reaction.products);
+            for &s in &reaction.reactants {
+                dydx[s] -= net;
+            }
+            for &s in reaction.products {
+                dydx[s] += net;
+            }
+        }
+        for d in dydx.iter_mut() {
+            *d *= time;
+        }
+    }
+
+    fn jacobian(&self, x: f64, y: &[f64], jac: &mut [f64]) -> usize {
+        let rates = self.rates(x);
+        let time = x.exp();
+        jac.fill(0.0);
+        let mut add = |row: usize, column: usize, value: f64| jac[row*NUCLIDES + column] += time*value;
+        for (column, rate) in [(N, rates.weak[0]), (P, -rates.weak[1])] {
+            add(N, column, -rate);
+            add(P, column, rate);
+        }
+        for (k, reaction) in REACTIONS.iter().enumerate() {
+            for j in 0..NUCLIDES {
+                let net = rates.forward[k]*monomial_derivative(y, &reaction.reactants, j)
+                    - rates.reverse[k]*monomial_derivative(y, reaction.products, j);
+                if net == 0.0 {
+                    continue;
+                }
+                for &s in &reaction.reactants {
+                    add(s, j, -net);
+                }
+                for &s in reaction.products {
+                    add(s, j, net);
+                }
+            }
+        }
+        0
+    }
+}
+
+/// Primordial yields once tritium has decayed to ³He and ⁷Be to ⁷Li.
+#[derive(Debug, Clone, Copy)]
+pub struct Abundances {
+    pub helium: f64,     // Y_p, mass fraction of ⁴He
+    pub deuterium: f64,  // D/H
+    pub helium3: f64,    // ³He/H
+    pub lithium7: f64,   // ⁷Li/H
+}
+
+impl Abundances {
+    pub const CSV_HEADER: &'static str = "eta,Yp,D_H,He3_H,Li7_H,chi2";
+
+    fn of(y: &[f64]) -> Self {
+        Abundances {
+            helium: 4.0*y[HE4],
+            deuterium: y[D]/y[P],
+            helium3: (y[HE3] + y[T])/y[P],
+            lithium7: (y[LI7] + y[BE7])/y[P],
+        }
+    }
+
+    /// χ² of Y_p and D/H against the observed values.
+    pub fn chi2(&self) -> f64 {
+        let term = |value: f64, (observed, sigma): (f64, f64), theory: f64| {
+            (value - observed).powi(2)/(sigma*sigma + theory*theory)
+        };
+        term(self.helium, HELIUM_OBSERVED, HELIUM_THEORY) + term(self.deuterium, DEUTERIUM_OBSERVED, DEUTERIUM_THEORY)
+    }
+
+    /// Whether Y_p and D/H exclude these yields at 95% confidence.
+    pub fn excluded(&self) -> bool {
+        self.chi2() > CHI2_95
+    }
+
+    /// Yields between scanned η, interpolated linearly in ln η and the log of each yield.
+    fn interpolate(etas: &[f64], yields: &[Abundances], eta: f64) -> Self {
+        if etas.len() == 1 {
+            return yields[0];
+        }
+        let ln_etas: Vec<f64> = etas.iter().map(|e| e.ln()).collect();
+        let (i, u) = bracket(&ln_etas, eta.ln());
+        let (a, b) = (&yields[i], &yields[i + 1]);
+        let lerp = |x: f64, y: f64| (x.ln() + u*(y.ln() - x.ln())).exp();
+        Abundances {
+            helium: lerp(a.helium, b.helium),
+            deuterium: lerp(a.deuterium, b.deuterium),
+            helium3: lerp(a.helium3, b.helium3),
+            lithium7: lerp(a.lithium7, b.lithium7),
+        }
+    }
+}
+
+/// η of least χ² and its yields, interpolated between the yields scanned at the increasing
+/// `etas`.
+pub fn best_fit(etas: &[f64], yields: &[Abundances]) -> (f64, Abundances) {
+    let fine = ((etas.len() - 1)*REFINE).max(1);
+    let (ln_lo, ln_hi) = (etas[0].ln(), etas[etas.len() - 1].ln());
+    (0..=fine)
+        .map(|k| (ln_lo + (ln_hi - ln_lo)*k as f64/fine as f64).exp())
+        .map(|eta| (eta, Abundances::interpolate(etas, yields, eta)))
+        .min_by(|a, b| a.1.chi2().total_cmp(&b.1.chi2()))
+        .unwrap()
+}
@@ -0,0 +1,160 @@
+// Restart files of a run.
+//
+//   b"FPCKPT1\n"
+//   u32 length, TOML of the run configuration
+//   integrator   f64 N, f64 next step, u32 dimension, f64 per component
+//   statistics   u64 accepted, rejected, evaluations
+//   recorder     u64 step, u32 epochs of (u8 component, f64 t_start, f64 t_end),
+//                f64 max residual, f64 max fluid fraction, u64 flagged,
+//                then first flagged step (u64), crossover (f64 t, f64 H) and N of the last
+//                written row (f64), each behind a u8 presence byte
+//   u64 length of the trajectory file at the checkpoint
+// Little-endian like the binary trajectory. The file is written next to its target and
+// renamed over it, so an interrupted write leaves the previous checkpoint intact.
+
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::integrator::{IntegrationStats, IntegratorState};
+use crate::model::Component;
+use crate::output::{invalid, ByteReader};
+use crate::params::RunConfig;
+use crate::{Epoch, Progress};
+
+const MAGIC: &[u8; 8] = b"FPCKPT1\n";
+
+/// Everything a run needs to carry on from where it was saved.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub config: RunConfig,
+    pub integrator: IntegratorState,
+    pub progress: Progress,
+    pub output_length: u64, // bytes of the trajectory written up to the checkpoint
+}
+
+fn component_code(component: Component) -> u8 {
+    match component {
+        Component::Vacuum => 0,
+        Component::Radiation => 1,
+        Component::Matter => 2,
+    }
+}
+
+fn component_of(code: u8) -> io::Result<Component> {
+    match code {
+        0 => Ok(Component::Vacuum),
+        1 => Ok(Component::Radiation),
+        2 => Ok(Component::Matter),
+        other => Err(invalid(format!("unknown component code {}", other))),
+    }
+}
+
+impl Checkpoint {
+    /// Writes the checkpoint to `path`, replacing any earlier one atomically.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut bytes = Vec::new();
+        let f64s = |bytes: &mut Vec<u8>, values: &[f64]| {
+            for v in values {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        };
+        let u64s = |bytes: &mut Vec<u8>, values: &[u64]| {
+            for v in values {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        };
+
+        bytes.extend_from_slice(MAGIC);
+        let config = toml::to_string(&self.config).expect("run configuration always serializes");
+        bytes.extend_from_slice(&(config.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(config.as_bytes());
+
+        f64s(&mut bytes, &[self.integrator.x, self.integrator.step]);
+        bytes.extend_from_slice(&(self.integrator.y.len() as u32).to_le_bytes());
+        f64s(&mut bytes, &self.integrator.y);
+        let stats = &self.integrator.stats;
+        u64s(&mut bytes, &[stats.accepted as u64, stats.rejected as u64, stats.evaluations as u64]);
+
+        let p = &self.progress;
+        u64s(&mut bytes, &[p.step as u64]);
+        bytes.extend_from_slice(&(p.epochs.len() as u32).to_le_bytes());
+        for epoch in &p.epochs {
+            bytes.push(component_code(epoch.component));
+            f64s(&mut bytes, &[epoch.t_start, epoch.t_end]);
+        }
+        f64s(&mut bytes, &[p.max_residual, p.max_fluid_fraction]);
+        u64s(&mut bytes, &[p.flagged as u64]);
+        bytes.push(p.first_flagged.is_some() as u8);
+        if let Some(step) = p.first_flagged {
+            u64s(&mut bytes, &[step as u64]);
+        }
+        bytes.push(p.crossover.is_some() as u8);
+        if let Some((t, h)) = p.crossover {
+            f64s(&mut bytes, &[t, h]);
+        }
+        bytes.push(p.last_written.is_some() as u8);
+        if let Some(n) = p.last_written {
+            f64s(&mut bytes, &[n]);
+        }
+        u64s(&mut bytes, &[self.output_length]);
+
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        fs::write(&partial, &bytes)?;
+        fs::rename(&partial, path)
+    }
+
+    /// Reads a checkpoint written by `save`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+        let mut reader = ByteReader::new(&bytes, path);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(invalid(format!("{} is not a first-product checkpoint", path.display())));
+        }
+        let config: RunConfig = toml::from_str(reader.text()?).map_err(|e| invalid(e.to_string()))?;
+        config.validate().map_err(|e| invalid(e.to_string()))?;
+
+        let (x, step) = (reader.f64()?, reader.f64()?);
+        let dim = reader.u32()? as usize;
+        let y = (0..dim).map(|_| reader.f64()).collect::<io::Result<Vec<f64>>>()?;
+        let stats = IntegrationStats {
+            accepted: reader.u64()? as usize,
+            rejected: reader.u64()? as usize,
+            evaluations: reader.u64()? as usize,
+        };
+
+        let recorded = reader.u64()? as usize;
+        let epochs = (0..reader.u32()?).map(|_| Ok(Epoch {
+            component: component_of(reader.u8()?)?,
+            t_start: reader.f64()?,
+            t_end: reader.f64()?,
+        })).collect::<io::Result<Vec<Epoch>>>()?;
+        let (max_residual, max_fluid_fraction) = (reader.f64()?, reader.f64()?);
+        let flagged = reader.u64()? as usize;
+        let first_flagged = if reader.u8()? == 1 { Some(reader.u64()? as usize) } else { None };
+        let crossover = if reader.u8()? == 1 { Some((reader.f64()?, reader.f64()?)) } else { None };
+        let last_written = if reader.u8()? == 1 { Some(reader.f64()?) } else { None };
+        let output_length = reader.u64()?;
+        if !reader.at_end() {
+            return Err(invalid(format!("{} has trailing bytes", path.display())));
+        }
+
+        Ok(Checkpoint {
+            config,
+            integrator: IntegratorState { x, y, step, stats },
+            progress: Progress {
+                step: recorded,
+                epochs,
+                max_residual,
+                max_fluid_fraction,
+                flagged,
+                first_flagged,
+                crossover,
+                last_written,
+            },
+            output_length,
+        })
+    }
+}
@@ -0,0 +1,110 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::bbn::{self, Abundances, BbnError, ExpansionHistory, Network, RateTable, CHI2_95, T_END};
+use first_product::integrator::IntegrationError;
+use first_product::model::RunningVacuum;
+use first_product::params::RunConfig;
+
+pub const USAGE: &str = "\
+usage: first-product bbn [--eta LO,HI] [--points N] [--rates FILE.csv] [--table FILE.csv]
+                         [run flags...]
+
+Runs the light-element network in the expansion history of the run configured by the
+run flags (see first-product --help) for N baryon-to-photon ratios, log-spaced over
+[LO, HI] (default 3e-10,1e-9, 12 points), and writes Y_p, D/H, 3He/H and 7Li/H to the
+table (default bbn.csv). --rates replaces the bundled rate table (columns T9 and one per
+reaction, N_A<sigma v> in cm^3/mol/s). The default run reaches matter domination long
+before BBN; --h0 1.44e-42 --omega-r0 9.1e-5 give the radiation era of our universe.";
+
+/// Temperature of the reported cooling rate, GeV, where e± are still relativistic.
+const COOLING_REPORT: f64 = 5.0e-3;
+
+/// ρ_m/ρ_r at the end of the window above which the run is not a radiation era.
+const MATTER_NEGLIGIBLE: f64 = 1e-3;
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, BbnError> {
+    value.parse().map_err(|_| BbnError::Usage(format!("{} expects a number, got '{}'", flag, value)))
+}
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(BbnError::Usage("first-product bbn: light-element yields".to_string()).into());
+    }
+    let (mut eta_range, mut points) = ([3e-10, 1e-9], 12);
+    let mut rates = None;
+    let mut table = PathBuf::from("bbn.csv");
+    let mut rest = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        if !matches!(flag.as_str(), "--eta" | "--points" | "--rates" | "--table") {
+            rest.push(flag);
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| BbnError::Usage(format!("{} needs a value", flag)))?;
+        match flag.as_str() {
+            "--eta" => {
+                let bounds: Vec<f64> = value.split(',').map(|v| parse_value(&flag, v)).collect::<Result<_, _>>()?;
+                match bounds[..] {
+                    [lo, hi] if lo > 0.0 && hi >= lo => eta_range = [lo, hi],
+                    [eta] if eta > 0.0 => eta_range = [eta, eta],
+                    _ => return Err(BbnError::Usage(format!("--eta expects ETA or LO,HI with 0 < LO <= HI, got '{}'",
+                                                            value)).into()),
+                }
+            }
+            "--points" => points = parse_value(&flag, &value)?,
+            "--rates" => rates = Some(PathBuf::from(value)),
+            _ => table = PathBuf::from(value),
+        }
+    }
+    if points == 0 {
+        return Err(BbnError::Usage("--points must be at least 1".to_string()).into());
+    }
+    if eta_range[0] == eta_range[1] {
+        points = 1;
+    }
+    let rates = match rates {
+        Some(path) => {
+            let text = fs::read_to_string(&path).map_err(|e| BbnError::Io(path.clone(), e))?;
+            RateTable::parse(&text, &path)?
+        }
+        None => RateTable::bundled()?,
+    };
+    let config = RunConfig::from_args(rest)?;
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+    let expansion = ExpansionHistory::of_run(&model, &config)?;
+
+    let etas: Vec<f64> = (0..points).map(|i| {
+        let u = if points > 1 { i as f64/(points - 1) as f64 } else { 0.0 };
+        (eta_range[0].ln() + u*(eta_range[1]/eta_range[0]).ln()).exp()
+    }).collect();
+    let yields = etas.iter()
+        .map(|&eta| Network::new(eta, &expansion, &model.plasma, &rates).evolve())
+        .collect::<Result<Vec<Abundances>, IntegrationError>>()?;
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "{}", Abundances::CSV_HEADER)?;
+    for (eta, a) in etas.iter().zip(&yields) {
+        writeln!(file, "{},{},{},{},{},{}", eta, a.helium, a.deuterium, a.helium3, a.lithium7, a.chi2())?;
+    }
+
+    println!("Cooling at T = {} GeV: -dlnT/dt = {:.5} H_radiation (nu = {})",
+             COOLING_REPORT, expansion.cooling_at(COOLING_REPORT), config.cosmology.nu);
+    let matter = expansion.matter_ratio.last().copied().unwrap_or(f64::NAN);
+    if matter.is_nan() || matter >= MATTER_NEGLIGIBLE {
+        println!("WARNING: rho_m/rho_r = {:.3e} at T = {:e} GeV; h0 and omega_r0 of the run are far from \
+                  those of our universe", matter, T_END);
+    }
+    let (eta, best) = bbn::best_fit(&etas, &yields);
+    println!("Best eta = {:.4e}: Y_p = {:.5}, D/H = {:.4e}, 3He/H = {:.4e}, 7Li/H = {:.4e}",
+             eta, best.helium, best.deuterium, best.helium3, best.lithium7);
+    if points > 1 && (eta <= etas[0] || eta >= etas[points - 1]) {
+        println!("WARNING: the best eta lies at the edge of the scanned range");
+    }
+    let verdict = if best.excluded() { "excluded at 95% CL" } else { "allowed" };
+    println!("Helium and deuterium: nu = {} is {} (chi2_min = {:.3}, 95% limit {}). Data in {}",
+             config.cosmology.nu, verdict, best.chi2(), CHI2_95, table.display());
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use std::path::{Path, PathBuf};
+
+use first_product::fit::{fit, read_table, write_chain, FitError, FitModel, FitResult, Likelihood, SamplerSettings};
+
+pub const USAGE: &str = "\
+usage: first-product fit [--sn FILE.csv] [--hz FILE.csv] [--walkers N] [--steps N] [--burn N]
+                         [--seed N] [--chains PREFIX] [--omega-r0 X]
+                         [--nu-prior LO,HI] [--omega-m-prior LO,HI] [--h0-prior LO,HI]
+
+--sn reads supernova distance moduli (columns z,mu,sigma), --hz cosmic-chronometer
+expansion rates in km/s/Mpc (columns z,H,sigma); at least one is required. Chains are
+written to PREFIX.running_vacuum.chain.csv and PREFIX.lcdm.chain.csv.";
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, FitError> {
+    value.trim().parse()
+        .map_err(|_| FitError::Usage(format!("{} got an invalid value '{}'", flag, value)))
+}
+
+fn parse_range(flag: &str, value: &str) -> Result<[f64; 2], FitError> {
+    match value.split(',').collect::<Vec<&str>>()[..] {
+        [lo, hi] => {
+            let range = [parse_value(flag, lo)?, parse_value(flag, hi)?];
+            if range[0] < range[1] {
+                Ok(range)
+            } else {
+                Err(FitError::Usage(format!("{} needs LO < HI, got '{}'", flag, value)))
+            }
+        }
+        _ => Err(FitError::Usage(format!("{} expects LO,HI, got '{}'", flag, value))),
+    }
+}
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(FitError::Usage("first-product fit: posterior of (nu, omega_m0, H0)".to_string()).into());
+    }
+    let mut likelihood = Likelihood::default();
+    let mut settings = SamplerSettings { walkers: 32, steps: 2000, burn: 0, seed: 7 };
+    let mut burn = None;
+    let mut prefix = String::from("fit");
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        let value = iter.next().ok_or_else(|| FitError::Usage(format!("{} needs a value", flag)))?;
+        match flag.as_str() {
+            "--sn" => likelihood.supernovae = read_table(Path::new(&value), "mu")?,
+            "--hz" => likelihood.chronometers = read_table(Path::new(&value), "H")?,
+            "--walkers" => settings.walkers = parse_value(&flag, &value)?,
+            "--steps" => settings.steps = parse_value(&flag, &value)?,
+            "--burn" => burn = Some(parse_value(&flag, &value)?),
+            "--seed" => settings.seed = parse_value(&flag, &value)?,
+            "--chains" => prefix = value,
+            "--omega-r0" => likelihood.omega_r0 = parse_value(&flag, &value)?,
+            "--nu-prior" => likelihood.nu_prior = parse_range(&flag, &value)?,
+            "--omega-m-prior" => likelihood.omega_m_prior = parse_range(&flag, &value)?,
+            "--h0-prior" => likelihood.h0_prior = parse_range(&flag, &value)?,
+            other => return Err(FitError::Usage(format!("unknown flag '{}'", other)).into()),
+        }
+    }
+    settings.burn = burn.unwrap_or(settings.steps/2);
+    if likelihood.data_count() == 0 {
+        return Err(FitError::Usage("no data: give --sn and/or --hz".to_string()).into());
+    }
+    if settings.walkers < 6 || settings.steps < 2 || settings.burn >= settings.steps {
+        return Err(FitError::Usage(
+            "need at least 6 walkers, 2 steps and a burn-in shorter than the run".to_string()).into());
+    }
+
+    println!("Fitting {} supernovae and {} H(z) points: {} walkers x {} steps, burn-in {}",
+             likelihood.supernovae.len(), likelihood.chronometers.len(),
+             settings.walkers, settings.steps, settings.burn);
+    let results: Vec<FitResult> = [FitModel::RunningVacuum, FitModel::LambdaCdm].iter()
+        .map(|&model| fit(&likelihood, model, &settings))
+        .collect::<Result<_, _>>()?;
+
+    for result in &results {
+        let path = PathBuf::from(format!("{}.{}.chain.csv", prefix, result.model.label()));
+        write_chain(&path, result)?;
+        println!("\n{} (acceptance {:.2}, chain in {})",
+                 result.model.label(), result.chain.acceptance(), path.display());
+        println!("  {:>9} {:>11} {:>10} {:>11} {:>11} {:>23} {:>23}",
+                 "", "mean", "std", "median", "best fit", "68%", "95%");
+        for ((name, m), best) in result.model.parameter_names().iter().zip(&result.marginals).zip(&result.best_fit) {
+            println!("  {:>9} {:>11.5} {:>10.5} {:>11.5} {:>11.5} [{:>10.5}, {:>10.5}] [{:>10.5}, {:>10.5}]",
+                     name, m.mean, m.std, m.median, best,
+                     m.interval_68[0], m.interval_68[1], m.interval_95[0], m.interval_95[1]);
+        }
+        println!("  chi2_min = {:.3} for {} points, AIC = {:.3}, BIC = {:.3}",
+                 result.chi2_min, likelihood.data_count(), result.aic, result.bic);
+    }
+    let (rvm, lcdm) = (&results[0], &results[1]);
+    println!("\nrunning_vacuum - lcdm: delta chi2 = {:.3}, delta AIC = {:.3}, delta BIC = {:.3}",
+             rvm.chi2_min - lcdm.chi2_min, rvm.aic - lcdm.aic, rvm.bic - lcdm.bic);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+//! The subcommands of the binary: each parses its own flags, calls the library and prints the
+//! result. Each also has its `USAGE`, printed after an error in the flags.
+
+use std::error::Error;
+
+use first_product::bbn::BbnError;
+use first_product::fit::FitError;
+use first_product::params::ParamsError;
+use first_product::plot::PlotError;
+
+pub mod bbn;
+pub mod fit;
+pub mod plasma;
+pub mod plot;
+pub mod recombination;
+pub mod reheating;
+pub mod relic;
+pub mod spectrum;
+pub mod sweep;
+
+/// Prints `e`, followed by `usage` if it is an error in the flags.
+pub fn report(e: &(dyn Error + 'static), usage: &str) {
+    let flags = matches!(e.downcast_ref(), Some(ParamsError::Usage(_)))
+        || matches!(e.downcast_ref(), Some(BbnError::Usage(_)))
+        || matches!(e.downcast_ref(), Some(FitError::Usage(_)))
+        || matches!(e.downcast_ref(), Some(PlotError::Usage(_)));
+    if flags {
+        eprintln!("{}\n\n{}", e, usage);
+    } else {
+        eprintln!("{}", e);
+    }
+}
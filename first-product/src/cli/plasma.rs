@@ -0,0 +1,49 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::params::{ParamsError, RunConfig};
+use first_product::plasma::Plasma;
+
+pub const USAGE: &str = "\
+usage: first-product plasma [--table FILE.csv] [run flags...]
+
+Tabulates the plasma equation of state set by --plasma, --bag-root and --crossover-width
+(see first-product --help): T, g*, g*s, p/T^4, rho/T^4, s/T^3 and w from 1e-5 to 1e5 GeV.";
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(ParamsError::Usage("first-product plasma: equation of state of the primordial plasma".to_string()).into());
+    }
+    let mut table = PathBuf::from("plasma.csv");
+    let mut rest = Vec::new();
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        if flag == "--table" {
+            table = PathBuf::from(iter.next().ok_or_else(|| ParamsError::Usage("--table needs a value".to_string()))?);
+        } else {
+            rest.push(flag);
+        }
+    }
+    let config = RunConfig::from_args(rest)?;
+    let plasma = Plasma::new(&config.plasma)?;
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "T,g_star,g_star_s,p_over_T4,rho_over_T4,s_over_T3,w")?;
+    for p in &plasma.points {
+        let t = p.temperature;
+        writeln!(file, "{},{},{},{},{},{},{}", t, p.g_star(), p.g_star_s(), p.thermo.p/t.powi(4),
+                 p.thermo.rho/t.powi(4), p.thermo.s/t.powi(3), p.w())?;
+    }
+    let (first, last) = (plasma.points[0], plasma.points[plasma.points.len() - 1]);
+    let softest = plasma.points.iter()
+        .filter(|p| (p.temperature/plasma.crossover - 1.0).abs() < 0.5)
+        .min_by(|a, b| a.w().total_cmp(&b.w()))
+        .unwrap_or(&first);
+    println!("QCD crossover at Tc = {:.4} GeV (bag constant B^1/4 = {} GeV, width {} GeV)",
+             plasma.crossover, config.plasma.bag_root, config.plasma.crossover_width);
+    println!("g* = {:.3} at {:e} GeV, {:.3} at {:e} GeV; softest w = {:.4} at T = {:.4} GeV",
+             last.g_star(), last.temperature, first.g_star(), first.temperature, softest.w(), softest.temperature);
+    println!("Table of {} temperatures in {}", plasma.points.len(), table.display());
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use first_product::plot::{plot, Format, PlotError};
+
+pub const USAGE: &str = "\
+usage: first-product plot FILE.csv [--out-dir DIR] [--format png|svg|both]
+
+Draws every figure the columns of FILE.csv allow: the phase portrait (phi, Potential,
+Kinetic, Era), H(t) (t, H), w(t) (t, w), the energy fractions (t, Potential, Radiation,
+Matter) and a P-V diagram (V, P). Figures are written as DIR/FILE.<figure>.png and .svg;
+DIR defaults to the directory of FILE.csv.";
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(PlotError::Usage("first-product plot: figures of a run".to_string()).into());
+    }
+    let mut csv = None;
+    let mut out_dir = None;
+    let mut formats = vec![Format::Png, Format::Svg];
+
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        if !arg.starts_with("--") {
+            if csv.replace(PathBuf::from(&arg)).is_some() {
+                return Err(PlotError::Usage(format!("unexpected argument '{}'", arg)).into());
+            }
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| PlotError::Usage(format!("{} needs a value", arg)))?;
+        match arg.as_str() {
+            "--out-dir" => out_dir = Some(PathBuf::from(value)),
+            "--format" => formats = match value.as_str() {
+                "png" => vec![Format::Png],
+                "svg" => vec![Format::Svg],
+                "both" => vec![Format::Png, Format::Svg],
+                other => return Err(PlotError::Usage(format!("unknown format '{}'", other)).into()),
+            },
+            other => return Err(PlotError::Usage(format!("unknown flag '{}'", other)).into()),
+        }
+    }
+    let csv = csv.ok_or_else(|| PlotError::Usage("no table: give FILE.csv".to_string()))?;
+    let out_dir = out_dir.unwrap_or_else(|| csv.parent().map(Path::to_path_buf).unwrap_or_default());
+    let out_dir = if out_dir.as_os_str().is_empty() { PathBuf::from(".") } else { out_dir };
+    fs::create_dir_all(&out_dir).map_err(|e| PlotError::Io(out_dir.clone(), e))?;
+
+    for path in plot(&csv, &out_dir, &formats)? {
+        println!("{}", path.display());
+    }
+    Ok(())
+}
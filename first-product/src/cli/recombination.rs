@@ -0,0 +1,79 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::model::RunningVacuum;
+use first_product::observables;
+use first_product::params::{ParamsError, RunConfig};
+use first_product::recombination::{recombine, LateHistory, Recombination};
+
+pub const USAGE: &str = "\
+usage: first-product recombination [--omega-b0 X] [--helium Y] [--table FILE.csv]
+                                   [run flags...]
+
+Ionisation history, visibility, last scattering, drag epoch and sound horizon in the
+late expansion of the run configured by the run flags (see first-product --help).
+--omega-b0 is the baryon part of omega_m0 (default 0.049), --helium the primordial
+helium mass fraction (default 0.245). The table (default recombination.csv) lists
+z, x_e, x_H, T, tau and the visibility g = e^-tau dtau/dz. The default run is still
+ionised today; --h0 1.44e-42 --omega-r0 9.1e-5 give our universe.";
+
+/// CMB temperature today, K, and the relative departure of T0 from it worth a warning.
+const CMB_TEMPERATURE: f64 = 2.725;
+const CMB_TOLERANCE: f64 = 0.05;
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(ParamsError::Usage("first-product recombination: ionisation history and acoustic scales".to_string()).into());
+    }
+    let (mut omega_b0, mut helium) = (0.049, 0.245);
+    let mut table = PathBuf::from("recombination.csv");
+    let mut rest = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        if !matches!(flag.as_str(), "--omega-b0" | "--helium" | "--table") {
+            rest.push(flag);
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+        let number = || value.parse::<f64>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a number, got '{}'", flag, value)));
+        match flag.as_str() {
+            "--omega-b0" => omega_b0 = number()?,
+            "--helium" => helium = number()?,
+            _ => table = PathBuf::from(value),
+        }
+    }
+    let config = RunConfig::from_args(rest)?;
+    if !(omega_b0 > 0.0 && omega_b0 <= config.cosmology.omega_m0) {
+        return Err(ParamsError::Invalid(format!(
+            "omega_b0 = {} must be positive and at most omega_m0 = {}", omega_b0, config.cosmology.omega_m0)).into());
+    }
+    if !(0.0..1.0).contains(&helium) {
+        return Err(ParamsError::Invalid(format!("helium = {} must satisfy 0 <= Y_p < 1", helium)).into());
+    }
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+    let history = LateHistory::of_run(&model, &config, omega_b0, helium)?;
+    let recombination = recombine(&history)?;
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "{}", Recombination::CSV_HEADER)?;
+    for p in &recombination.points {
+        writeln!(file, "{},{},{},{},{},{}", p.z, p.x_e, p.x_h, p.temperature, p.tau, p.visibility)?;
+    }
+
+    let frozen = recombination.points.last().map_or(f64::NAN, |p| p.x_e);
+    let t0 = observables::to_kelvin(history.temperature_today());
+    println!("T0 = {:.4} K; residual ionisation x_e = {:.3e} today", t0, frozen);
+    if (t0/CMB_TEMPERATURE - 1.0).abs() >= CMB_TOLERANCE {
+        println!("WARNING: T0 = {:.4} K is far from the {} K of the CMB; h0 and omega_r0 of the run are far from \
+                  those of our universe", t0, CMB_TEMPERATURE);
+    }
+    println!("Last scattering z_* = {:.2}, drag epoch z_d = {:.2}", recombination.last_scattering, recombination.drag);
+    println!("Sound horizon r_s(z_d) = {:.3} Mpc, r_s(z_*) = {:.3} Mpc, D_M(z_*) = {:.1} Mpc, 100 theta_* = {:.5}",
+             observables::to_mpc(recombination.sound_horizon_drag), observables::to_mpc(recombination.sound_horizon_star),
+             observables::to_mpc(recombination.angular_distance_star), 100.0*recombination.acoustic_angle());
+    println!("Data in {}", table.display());
+    Ok(())
+}
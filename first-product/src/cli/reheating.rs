@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::model::RunningVacuum;
+use first_product::params::{ParamsError, RunConfig};
+use first_product::reheating::{initial_state, primeval_end, reheat, Reheating, ReheatingHistory, Resonance,
+                               MAX_REHEATING_EFOLDS};
+
+pub const USAGE: &str = "\
+usage: first-product reheat [--gamma X] [--mass X] [--coupling G] [--floquet MU]
+                            [--table FILE.csv] [run flags...]
+
+The condensate left at the end of the primeval phase of the run configured by the run
+flags (see first-product --help) decays at the rate gamma (GeV, below H_end). The mass
+m (GeV) defaults to H_end. A coupling G > 0 adds the broad parametric resonance of
+g^2 phi^2 chi^2 with Floquet exponent MU (default 0.175).";
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(ParamsError::Usage("first-product reheat: decay of the condensate after the primeval phase".to_string()).into());
+    }
+    let mut gamma = 1.0e12;
+    let mut mass = None;
+    let (mut coupling, mut floquet) = (0.0, 0.175);
+    let mut table = PathBuf::from("reheating.csv");
+    let mut rest = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        let own = matches!(flag.as_str(), "--gamma" | "--mass" | "--coupling" | "--floquet" | "--table");
+        if !own {
+            rest.push(flag);
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+        let number = || value.parse::<f64>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a number, got '{}'", flag, value)));
+        match flag.as_str() {
+            "--gamma" => gamma = number()?,
+            "--mass" => mass = Some(number()?),
+            "--coupling" => coupling = number()?,
+            "--floquet" => floquet = number()?,
+            _ => table = PathBuf::from(value),
+        }
+    }
+    let config = RunConfig::from_args(rest)?;
+    let method = config.integration.method();
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+
+    let end = primeval_end(&model, &method)?;
+    let h_end = model.hubble(end.z);
+    let mass = mass.unwrap_or(h_end);
+    if !(gamma > 0.0 && gamma < h_end) {
+        return Err(ParamsError::Invalid(format!(
+            "gamma = {:e} must be positive and below H_end = {:e} GeV for a reheating stage", gamma, h_end)).into());
+    }
+    if !(mass > 0.0 && mass.is_finite() && coupling >= 0.0 && floquet > 0.0) {
+        return Err(ParamsError::Invalid("reheating needs mass > 0, coupling >= 0 and floquet > 0".to_string()).into());
+    }
+    let resonance = (coupling > 0.0).then_some(Resonance { coupling, floquet });
+    let system = Reheating { mp: config.cosmology.mp, gamma, mass, resonance, plasma: model.plasma.clone() };
+    let y0 = initial_state(&model, &end, mass, resonance.is_some());
+    let history = reheat(&system, &method, &y0)?;
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "{}", ReheatingHistory::CSV_HEADER)?;
+    for row in &history.rows {
+        let fields: Vec<String> = row.iter().map(f64::to_string).collect();
+        writeln!(file, "{}", fields.join(","))?;
+    }
+
+    println!("Primeval phase ends at t = {:.4e} GeV^-1 with H_end = {:.4e} GeV; gamma = {:e} GeV, m = {:e} GeV",
+             end.t, h_end, gamma, mass);
+    if let Some(n) = history.resonance_end {
+        println!("Parametric resonance ends after {:.3} e-folds; chi carries {:.3} of the radiation",
+                 n, history.chi_share);
+    }
+    match history.reheating_temperature {
+        Some(t) => println!("Reheating temperature T_reh = {:.4e} GeV (estimate {:.4e} GeV), T_max = {:.4e} GeV",
+                            t, history.estimate, history.max_temperature),
+        None => println!("WARNING: H did not fall to gamma within {} e-folds", MAX_REHEATING_EFOLDS),
+    }
+    println!("Condensate decayed after {:.3} e-folds, {} steps. Data in {}",
+             history.efolds, history.stats.accepted, table.display());
+    Ok(())
+}
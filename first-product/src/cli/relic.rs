@@ -0,0 +1,94 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::model::RunningVacuum;
+use first_product::observables;
+use first_product::params::{ParamsError, RunConfig};
+use first_product::relic::{from_cm3_per_s, freeze_out, Background, Relic, Species, X_MIN};
+
+pub const USAGE: &str = "\
+usage: first-product relic [--mass M] [--sigma-v A] [--p-wave B] [--dof G] [--x LO,HI]
+                           [--table FILE.csv] [run flags...]
+
+Freeze-out of a species of mass M (GeV, default 100) with G internal states (default 2)
+annihilating at <sigma v> = A + B/x (cm^3/s, default A = 2.2e-26, B = 0) in the expansion
+history of the run configured by the run flags (see first-product --help). The yield is
+followed from x = m/T = LO to HI (default 3,1000) and carried to today; the table (default
+relic.csv) lists x, T, g*s, Y and Y_eq. --h0 1.44e-42 --omega-r0 9.1e-5 give our universe.";
+
+/// Reads "LO,HI" with X_MIN <= LO < HI.
+fn parse_range(flag: &str, value: &str) -> Result<[f64; 2], ParamsError> {
+    let bounds: Vec<f64> = value.split(',')
+        .map(|v| v.parse().map_err(|_| ParamsError::Usage(format!("{} expects numbers, got '{}'", flag, value))))
+        .collect::<Result<_, _>>()?;
+    match bounds[..] {
+        [lo, hi] if lo >= X_MIN && hi > lo => Ok([lo, hi]),
+        _ => Err(ParamsError::Usage(format!("{} expects LO,HI with {} <= LO < HI, got '{}'", flag, X_MIN, value))),
+    }
+}
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(ParamsError::Usage("first-product relic: freeze-out of a thermal relic".to_string()).into());
+    }
+    let (mut mass, mut s_wave, mut p_wave, mut dof) = (100.0, 2.2e-26, 0.0, 2.0);
+    let mut x_range = [X_MIN, 1000.0];
+    let mut table = PathBuf::from("relic.csv");
+    let mut rest = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        if !matches!(flag.as_str(), "--mass" | "--sigma-v" | "--p-wave" | "--dof" | "--x" | "--table") {
+            rest.push(flag);
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+        let number = || value.parse::<f64>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a number, got '{}'", flag, value)));
+        match flag.as_str() {
+            "--mass" => mass = number()?,
+            "--sigma-v" => s_wave = number()?,
+            "--p-wave" => p_wave = number()?,
+            "--dof" => dof = number()?,
+            "--x" => x_range = parse_range(&flag, &value)?,
+            _ => table = PathBuf::from(value),
+        }
+    }
+    for (name, value) in [("mass", mass), ("dof", dof)] {
+        if !(value > 0.0 && value.is_finite()) {
+            return Err(ParamsError::Invalid(format!("{} = {} must be positive and finite", name, value)).into());
+        }
+    }
+    if !(s_wave >= 0.0 && p_wave >= 0.0 && s_wave + p_wave > 0.0) {
+        return Err(ParamsError::Invalid(format!(
+            "sigma_v = {} + {}/x must have non-negative terms, not both zero", s_wave, p_wave)).into());
+    }
+    let config = RunConfig::from_args(rest)?;
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+    let background = Background::of_run(&model, &config, 2.0*mass/x_range[0])?;
+    let species = Species { mass, dof, s_wave: from_cm3_per_s(s_wave), p_wave: from_cm3_per_s(p_wave) };
+    let relic = freeze_out(species, &background, config.cosmology.mp, x_range)?;
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "{}", Relic::CSV_HEADER)?;
+    for p in &relic.points {
+        writeln!(file, "{},{},{},{},{}", p.x, p.temperature, p.g_star_s, p.y, p.y_eq)?;
+    }
+
+    match relic.freeze_out {
+        Some(x_f) => println!("Freeze-out at x_f = {:.3} (T_f = {:.4e} GeV, g*s = {:.2})",
+                              x_f, mass/x_f, model.plasma.entropy_dof(mass/x_f)),
+        None => println!("WARNING: the species stays in equilibrium up to x = {}", x_range[1]),
+    }
+    let params = &config.cosmology;
+    let omega = relic.density_today/(3.0*params.mp*params.mp*params.h0*params.h0);
+    println!("Relic yield Y = {:.4e}; Omega h^2 = {:.4e} (Omega = {:.4e} of omega_m0 = {}, T0 = {:.4} K)",
+             relic.final_yield(), relic.omega_h2, omega, params.omega_m0,
+             observables::to_kelvin(background.at(background.n_today).1));
+    if omega > params.omega_m0 {
+        println!("WARNING: the relic exceeds the matter density of the run");
+    }
+    println!("Data in {}", table.display());
+    Ok(())
+}
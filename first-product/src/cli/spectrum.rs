@@ -0,0 +1,92 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+
+use first_product::model::RunningVacuum;
+use first_product::params::{ParamsError, RunConfig};
+use first_product::perturbations::{spectrum, Inflaton, Spectrum};
+
+pub const USAGE: &str = "\
+usage: first-product spectrum [--background inflaton|running-vacuum] [--power P] [--v0 X]
+                              [--phi0 X] [--efolds N] [--modes N] [--span X]
+                              [--table FILE.csv] [run flags...]
+
+The inflaton test background is V = v0 φ^P in units MP = 1, started at φ = phi0 on
+the slow-roll attractor; running-vacuum uses the primeval de Sitter phase of the run
+configured by the run flags (see first-product --help), which also set the integrator.
+The pivot leaves the horizon `efolds` e-folds before the end of inflation (ε = 1); the
+modes span ln(k/k*) in [-span, span].";
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        return Err(ParamsError::Usage("first-product spectrum: primordial power spectra".to_string()).into());
+    }
+    let mut running_vacuum = false;
+    let mut inflaton = Inflaton::default();
+    let (mut efolds, mut modes, mut span) = (60.0, 9, 2.0);
+    let mut table = PathBuf::from("spectrum.csv");
+    let mut rest = Vec::new();
+
+    let mut iter = args.into_iter();
+    while let Some(flag) = iter.next() {
+        let own = matches!(flag.as_str(), "--background" | "--power" | "--v0" | "--phi0" | "--efolds"
+                                          | "--modes" | "--span" | "--table");
+        if !own {
+            rest.push(flag);
+            continue;
+        }
+        let value = iter.next().ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+        let number = || value.parse::<f64>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a number, got '{}'", flag, value)));
+        match flag.as_str() {
+            "--background" => running_vacuum = match value.as_str() {
+                "inflaton" => false,
+                "running-vacuum" => true,
+                other => return Err(ParamsError::Usage(format!(
+                    "unknown background '{}', expected inflaton or running-vacuum", other)).into()),
+            },
+            "--power" => inflaton.power = number()?,
+            "--v0" => inflaton.v0 = number()?,
+            "--phi0" => inflaton.phi0 = number()?,
+            "--efolds" => efolds = number()?,
+            "--modes" => modes = value.parse()
+                .map_err(|_| ParamsError::Usage(format!("--modes expects a count, got '{}'", value)))?,
+            "--span" => span = number()?,
+            _ => table = PathBuf::from(value),
+        }
+    }
+    if modes < 3 || span.is_nan() || span <= 0.0 {
+        return Err(ParamsError::Invalid("the fit needs --modes >= 3 and --span > 0".to_string()).into());
+    }
+    if !(inflaton.v0 > 0.0 && inflaton.power > 0.0 && inflaton.phi0 > 0.0) {
+        return Err(ParamsError::Invalid("the inflaton needs v0, power and phi0 > 0".to_string()).into());
+    }
+    let config = RunConfig::from_args(rest)?;
+    let method = config.integration.method();
+
+    let (spectrum, potential) = if running_vacuum {
+        (spectrum(&RunningVacuum::new(&config.cosmology, &config.plasma)?, &method, efolds, modes, span)?, None)
+    } else {
+        let result = spectrum(&inflaton, &method, efolds, modes, span)?;
+        let phi_pivot = inflaton.field_at(&method, result.pivot_n)?;
+        (result, Some(inflaton.slow_roll(phi_pivot)))
+    };
+
+    let mut file = File::create(&table)?;
+    writeln!(file, "{}", Spectrum::CSV_HEADER)?;
+    for row in spectrum.csv_rows() {
+        writeln!(file, "{}", row)?;
+    }
+
+    println!("Inflation ends at N = {:.4}; pivot k* = {:e} leaves the horizon at N = {:.4}",
+             spectrum.end_n, spectrum.pivot_k, spectrum.pivot_n);
+    println!("{:>8} {:>14} {:>14} {:>14}", "", "numerical", "Hubble flow", "potential");
+    let potential = potential.map(|p| p.values());
+    for (i, name) in ["A_s", "n_s", "alpha_s", "r"].into_iter().enumerate() {
+        let potential = potential.map_or("-".to_string(), |p| format!("{:.6e}", p[i]));
+        println!("{:>8} {:>14.6e} {:>14.6e} {:>14}",
+                 name, spectrum.numerical.values()[i], spectrum.slow_roll.values()[i], potential);
+    }
+    println!("{} modes in {}, {} steps", spectrum.modes.len(), table.display(), spectrum.stats.accepted);
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use first_product::sweep::{self, SweepConfig};
+
+pub const USAGE: &str = "\
+usage: first-product sweep [--grid NU_POINTS,M_POINTS | --lhs SAMPLES]
+                           [--nu-range LO,HI] [--m-range LO,HI] [--seed N]
+                           [--threads N] [--table FILE.csv] [run flags...]
+
+Run flags (see first-product --help) set the parameters shared by every run.";
+
+pub fn main(args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let config = SweepConfig::from_args(args)?;
+    let points = config.points();
+    let mut finished = sweep::resume_table(&config, points.len())?;
+    let pending = points.len() - finished.len();
+    println!("Sweep of {} runs: {} already in {}, {} to go on {} threads",
+             points.len(), finished.len(), config.table.display(), pending, config.threads);
+
+    let mut done = 0;
+    sweep::run_missing(&config, &points, &mut finished, |index, outcome| {
+        match outcome {
+            Ok(summary) => if let Some((step, column)) = summary.non_finite {
+                eprintln!("  run {}: {} is not finite at step {}", index, column, step);
+            },
+            Err(e) => eprintln!("  run {} failed: {}", index, e),
+        }
+        done += 1;
+        println!("  [{}/{}] run {} done", done, pending, index);
+    })?;
+    println!("Sweep complete. Table in {}, parameters in {}",
+             config.table.display(), config.sidecar_path().display());
+    Ok(())
+}
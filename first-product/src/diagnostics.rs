@@ -0,0 +1,47 @@
+// Constraint monitor and background diagnostics for the running-vacuum run.
+//
+// Everything is evaluated on the integrated state (H, ρ_r, ρ_m), so that the residuals
+// measure how far the trajectory has drifted from the model equations:
+//   Friedmann:     ρ_r + ρ_m + V(H) = 3 MP² H²
+//   acceleration:  Ḣ = −(ρ + p)/(2 MP²),  p = w_r ρ_r − V
+// Both residuals are normalised by the Hubble scale (3 MP² H² and H² respectively) and
+// evaluated by `numeric` in the precision of the run; w, q and ε are formed from the
+// dimensionless fractions Ω_i = ρ_i/(3 MP² H²), which stay finite when ρ and H² do not.
+
+use crate::model::{RunningVacuum, State};
+use crate::numeric::{self, Precision};
+
+/// Diagnostics of one recorded step.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostics {
+    pub friedmann_residual: f64,
+    pub acceleration_residual: f64,
+    pub w: f64,       // effective equation of state p/ρ
+    pub q: f64,       // deceleration parameter −1 − Ḣ/H²
+    pub epsilon: f64, // ε = −Ḣ/H²
+    pub eta: f64,     // η = d ln ε/dN
+    pub flagged: bool,
+}
+
+impl Diagnostics {
+    /// Evaluates the diagnostics of `state` and flags residuals above `threshold`.
+    pub fn compute(model: &RunningVacuum, state: &State, threshold: f64, precision: Precision) -> Self {
+        let (omega_r, omega_m) = model.fluid_fractions(state);
+        let omega_v = model.vacuum_fraction(state.z);
+        let epsilon = model.epsilon(state);
+        let friedmann_residual = numeric::friedmann_residual(model, state, precision);
+        let acceleration_residual = numeric::acceleration_residual(model, state, epsilon, precision);
+
+        let flagged = !(friedmann_residual.abs() <= threshold && acceleration_residual.abs() <= threshold);
+
+        Diagnostics {
+            friedmann_residual,
+            acceleration_residual,
+            w: (model.radiation(state).w*omega_r - omega_v)/(omega_r + omega_m + omega_v),
+            q: -1.0 + epsilon,
+            epsilon,
+            eta: model.slow_roll_eta(state),
+            flagged,
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Forward-mode automatic differentiation on dual numbers a + b ε, ε² = 0.
+//
+// Evaluating f on x + ε gives f(x) + f'(x) ε, so a formula written once on `Dual`
+// yields its value and its exact first derivative together, with no step size. The
+// era solutions H(φ) and the potential V(H) are written this way in `model.rs`, and
+// `hubble_of_phi` is the value of that same formula.
+//
+// No evolution path differentiates on duals. The run's state (z, ρ_r, ρ_m, t) goes
+// through the interpolated plasma table, which has no dual form, so a stiff method
+// differences its Jacobian; the field equation uses the hand-derived dH/dφ and dφ̇/dφ,
+// which are cheaper than a dual evaluation at every step. The derivatives here are the
+// oracle for those hand forms: coming from the formula that defines H(φ), they hold
+// them to round-off at any φ, where a finite difference only checks to its step.
+
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+/// A value with its derivative along one variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Dual {
+    pub value: f64,
+    pub derivative: f64,
+}
+
+impl Dual {
+    /// The independent variable x, dx/dx = 1.
+    pub fn variable(x: f64) -> Self {
+        Dual { value: x, derivative: 1.0 }
+    }
+
+    /// A constant of the differentiation.
+    pub fn constant(x: f64) -> Self {
+        Dual { value: x, derivative: 0.0 }
+    }
+
+    /// f(x) with f'(x) = `slope`, by the chain rule.
+    fn chain(self, value: f64, slope: f64) -> Self {
+        Dual { value, derivative: slope*self.derivative }
+    }
+
+    pub fn sqrt(self) -> Self {
+        let root = self.value.sqrt();
+        self.chain(root, 0.5/root)
+    }
+
+    pub fn powi(self, n: i32) -> Self {
+        self.chain(self.value.powi(n), n as f64*self.value.powi(n - 1))
+    }
+
+    pub fn powf(self, p: f64) -> Self {
+        self.chain(self.value.powf(p), p*self.value.powf(p - 1.0))
+    }
+
+    pub fn exp(self) -> Self {
+        let e = self.value.exp();
+        self.chain(e, e)
+    }
+
+    pub fn ln(self) -> Self {
+        self.chain(self.value.ln(), 1.0/self.value)
+    }
+}
+
+impl From<f64> for Dual {
+    fn from(x: f64) -> Self {
+        Dual::constant(x)
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+
+    fn neg(self) -> Dual {
+        Dual { value: -self.value, derivative: -self.derivative }
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+
+    fn add(self, other: Dual) -> Dual {
+        Dual { value: self.value + other.value, derivative: self.derivative + other.derivative }
+    }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+
+    fn sub(self, other: Dual) -> Dual {
+        Dual { value: self.value - other.value, derivative: self.derivative - other.derivative }
+    }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+
+    fn mul(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value*other.value,
+            derivative: self.derivative*other.value + self.value*other.derivative,
+        }
+    }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+
+    fn div(self, other: Dual) -> Dual {
+        Dual {
+            value: self.value/other.value,
+            derivative: (self.derivative*other.value - self.value*other.derivative)/(other.value*other.value),
+        }
+    }
+}
+
+// Mixed arithmetic with constants, on either side.
+
+impl Add<f64> for Dual {
+    type Output = Dual;
+
+    fn add(self, c: f64) -> Dual {
+        Dual { value: self.value + c, ..self }
+    }
+}
+
+impl Add<Dual> for f64 {
+    type Output = Dual;
+
+    fn add(self, x: Dual) -> Dual {
+        x + self
+    }
+}
+
+impl Sub<f64> for Dual {
+    type Output = Dual;
+
+    fn sub(self, c: f64) -> Dual {
+        Dual { value: self.value - c, ..self }
+    }
+}
+
+impl Sub<Dual> for f64 {
+    type Output = Dual;
+
+    fn sub(self, x: Dual) -> Dual {
+        -x + self
+    }
+}
+
+impl Mul<f64> for Dual {
+    type Output = Dual;
+
+    fn mul(self, c: f64) -> Dual {
+        Dual { value: self.value*c, derivative: self.derivative*c }
+    }
+}
+
+impl Mul<Dual> for f64 {
+    type Output = Dual;
+
+    fn mul(self, x: Dual) -> Dual {
+        x*self
+    }
+}
+
+impl Div<f64> for Dual {
+    type Output = Dual;
+
+    fn div(self, c: f64) -> Dual {
+        Dual { value: self.value/c, derivative: self.derivative/c }
+    }
+}
+
+impl Div<Dual> for f64 {
+    type Output = Dual;
+
+    fn div(self, x: Dual) -> Dual {
+        Dual::constant(self)/x
+    }
+}
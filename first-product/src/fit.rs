@@ -0,0 +1,415 @@
+// Parameter fit of the running-vacuum model to supernova and expansion-rate data.
+//
+// `first-product fit --sn SN.csv --hz HZ.csv [...]` samples the posterior of (ν, Ω_m0, H0)
+// with the affine-invariant ensemble sampler of Goodman & Weare (stretch move), and the
+// posterior of (Ω_m0, H0) of ΛCDM (ν = 0) on the same data, then compares the two by
+// χ²_min, AIC = χ²_min + 2k and BIC = χ²_min + k ln n.
+//
+// The data lie at z ≲ few, where H ≪ HI and the H⁴/HI² term of V(H) is negligible; the
+// continuity equations of model.rs then integrate in closed form, ρ_m ∝ a^{−3(1−ν)},
+// ρ_r ∝ a^{−4(1−ν)}, and the Friedmann equation gives
+//   E²(z) = H²/H0² = [Ω_Λ0 − ν + Ω_m0 (1+z)^{3(1−ν)} + Ω_r0 (1+z)^{4(1−ν)}]/(1−ν)
+// with Ω_Λ0 = 1 − Ω_m0 − Ω_r0. With H0 in km/s/Mpc, dχ/dz = c/H gives distances in Mpc:
+//   d_L = (1+z) χ,  μ = 5 log10 d_L + 25.
+// Priors are uniform on each parameter, Ω_r0 is held fixed.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::integrator::{self, IntegrationError, IntegrationStats, Method, OdeSystem, Tolerances};
+
+/// Speed of light in km/s.
+const C_KM_S: f64 = 299_792.458;
+
+/// Stretch-move scale a of the proposal g(z) ∝ z^{-1/2} on [1/a, a].
+const STRETCH: f64 = 2.0;
+
+/// Draws from the prior box allowed for each walker before the prior is taken to exclude
+/// the whole box.
+const START_DRAWS: usize = 10_000;
+
+/// Error raised while reading the data or the fit flags, or when the sampler cannot start.
+#[derive(Debug)]
+pub enum FitError {
+    Io(PathBuf, std::io::Error),
+    Data { path: PathBuf, line: usize, message: String },
+    Usage(String),
+    Sampler(String),
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            FitError::Data { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            FitError::Usage(msg) => write!(f, "{}", msg),
+            FitError::Sampler(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for FitError {}
+
+/// One measurement: a value with its 1σ error at redshift z.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataPoint {
+    pub z: f64,
+    pub value: f64,
+    pub sigma: f64,
+}
+
+/// Reads a CSV table with a header naming `z`, `value_column` and `sigma`; lines
+/// starting with '#' are comments.
+pub fn read_table(path: &Path, value_column: &str) -> Result<Vec<DataPoint>, FitError> {
+    let text = fs::read_to_string(path).map_err(|e| FitError::Io(path.to_path_buf(), e))?;
+    let error = |line: usize, message: String| FitError::Data { path: path.to_path_buf(), line, message };
+
+    let mut lines = text.lines().enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+    let (header_line, header) = lines.next().ok_or_else(|| error(1, "empty table".to_string()))?;
+    let names: Vec<&str> = header.split(',').map(str::trim).collect();
+    let column = |name: &str| names.iter().position(|n| *n == name)
+        .ok_or_else(|| error(header_line, format!("missing column '{}'", name)));
+    let columns = [column("z")?, column(value_column)?, column("sigma")?];
+
+    let mut points = Vec::new();
+    for (line, text) in lines {
+        let fields: Vec<&str> = text.split(',').map(str::trim).collect();
+        let mut values = [0.0; 3];
+        for (value, &c) in values.iter_mut().zip(&columns) {
+            *value = fields.get(c)
+                .and_then(|f| f.parse::<f64>().ok())
+                .ok_or_else(|| error(line, format!("expected {} numeric columns", names.len())))?;
+        }
+        let [z, value, sigma] = values;
+        if !(z >= 0.0 && sigma > 0.0) {
+            return Err(error(line, format!("need z >= 0 and sigma > 0, got z = {}, sigma = {}", z, sigma)));
+        }
+        points.push(DataPoint { z, value, sigma });
+    }
+    Ok(points)
+}
+
+/// Late-time expansion of the running-vacuum model; ν = 0 is flat ΛCDM.
+#[derive(Debug, Clone, Copy)]
+pub struct LateExpansion {
+    pub nu: f64,
+    pub omega_m0: f64,
+    pub omega_r0: f64,
+    pub h0: f64, // km/s/Mpc
+}
+
+impl LateExpansion {
+    pub fn hubble(&self, z: f64) -> f64 {
+        let omega_lambda0 = 1.0 - self.omega_m0 - self.omega_r0;
+        let one_minus_nu = 1.0 - self.nu;
+        let e2 = (omega_lambda0 - self.nu
+                  + self.omega_m0*(1.0 + z).powf(3.0*one_minus_nu)
+                  + self.omega_r0*(1.0 + z).powf(4.0*one_minus_nu))/one_minus_nu;
+        self.h0*e2.sqrt()
+    }
+
+    /// Luminosity distances in Mpc at `redshifts` (in any order).
+    pub fn luminosity_distances(&self, redshifts: &[f64]) -> Result<Vec<f64>, IntegrationError> {
+        let method = Method::DormandPrince45 {
+            tolerances: Tolerances { rtol: 1e-9, atol: 0.0 },
+            initial_step: 1e-2,
+        };
+        let mut order: Vec<usize> = (0..redshifts.len()).collect();
+        order.sort_by(|&i, &j| redshifts[i].total_cmp(&redshifts[j]));
+
+        let mut stats = IntegrationStats::default();
+        let (mut z, mut chi) = (0.0, vec![0.0]);
+        let mut distances = vec![0.0; redshifts.len()];
+        for i in order {
+            chi = integrator::integrate(self, &method, z, &chi, redshifts[i], &mut stats, |_, _| true)?.1;
+            z = redshifts[i];
+            distances[i] = (1.0 + z)*chi[0];
+        }
+        Ok(distances)
+    }
+}
+
+/// Comoving distance in redshift, dχ/dz = c/H(z).
+impl OdeSystem for LateExpansion {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, z: f64, _y: &[f64], dydz: &mut [f64]) {
+        dydz[0] = C_KM_S/self.hubble(z);
+    }
+}
+
+/// The model being fitted and the meaning of its parameter vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FitModel {
+    RunningVacuum, // θ = (ν, Ω_m0, H0)
+    LambdaCdm,     // θ = (Ω_m0, H0)
+}
+
+impl FitModel {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FitModel::RunningVacuum => "running_vacuum",
+            FitModel::LambdaCdm => "lcdm",
+        }
+    }
+
+    pub fn parameter_names(&self) -> &'static [&'static str] {
+        match self {
+            FitModel::RunningVacuum => &["nu", "omega_m0", "h0"],
+            FitModel::LambdaCdm => &["omega_m0", "h0"],
+        }
+    }
+
+    pub fn expansion(&self, theta: &[f64], omega_r0: f64) -> LateExpansion {
+        match self {
+            FitModel::RunningVacuum => LateExpansion { nu: theta[0], omega_m0: theta[1], omega_r0, h0: theta[2] },
+            FitModel::LambdaCdm => LateExpansion { nu: 0.0, omega_m0: theta[0], omega_r0, h0: theta[1] },
+        }
+    }
+}
+
+/// Data, priors and fixed parameters of a fit.
+#[derive(Debug, Clone)]
+pub struct Likelihood {
+    pub supernovae: Vec<DataPoint>,
+    pub chronometers: Vec<DataPoint>,
+    pub omega_r0: f64,
+    pub nu_prior: [f64; 2],
+    pub omega_m_prior: [f64; 2],
+    pub h0_prior: [f64; 2],
+}
+
+/// No data yet, the radiation of our universe and priors wide enough for the data sets.
+impl Default for Likelihood {
+    fn default() -> Self {
+        Likelihood {
+            supernovae: Vec::new(),
+            chronometers: Vec::new(),
+            omega_r0: 9.0e-5,
+            nu_prior: [0.0, 0.1],
+            omega_m_prior: [0.05, 0.7],
+            h0_prior: [50.0, 90.0],
+        }
+    }
+}
+
+impl Likelihood {
+    pub fn data_count(&self) -> usize {
+        self.supernovae.len() + self.chronometers.len()
+    }
+
+    /// Prior box of each parameter of `model`.
+    pub fn bounds(&self, model: FitModel) -> Vec<[f64; 2]> {
+        match model {
+            FitModel::RunningVacuum => vec![self.nu_prior, self.omega_m_prior, self.h0_prior],
+            FitModel::LambdaCdm => vec![self.omega_m_prior, self.h0_prior],
+        }
+    }
+
+    pub fn chi_squared(&self, expansion: &LateExpansion) -> Result<f64, IntegrationError> {
+        let redshifts: Vec<f64> = self.supernovae.iter().map(|p| p.z).collect();
+        let distances = expansion.luminosity_distances(&redshifts)?;
+        let sn: f64 = self.supernovae.iter().zip(distances)
+            .map(|(p, d_l)| ((5.0*d_l.log10() + 25.0 - p.value)/p.sigma).powi(2))
+            .sum();
+        let hz: f64 = self.chronometers.iter()
+            .map(|p| ((expansion.hubble(p.z) - p.value)/p.sigma).powi(2))
+            .sum();
+        Ok(sn + hz)
+    }
+
+    /// ln posterior up to a constant: −χ²/2 inside the prior box (with Ω_Λ0 > ν), −∞ outside
+    /// and where the distances cannot be integrated.
+    pub fn log_posterior(&self, model: FitModel, theta: &[f64]) -> f64 {
+        let inside = self.bounds(model).iter().zip(theta).all(|(b, &x)| x >= b[0] && x <= b[1]);
+        let expansion = model.expansion(theta, self.omega_r0);
+        if !inside || expansion.omega_m0 + expansion.omega_r0 + expansion.nu >= 1.0 {
+            return f64::NEG_INFINITY;
+        }
+        let chi2 = self.chi_squared(&expansion).unwrap_or(f64::INFINITY);
+        if chi2.is_finite() { -0.5*chi2 } else { f64::NEG_INFINITY }
+    }
+}
+
+/// Walker positions of every step, position of walker k at step s at index s*walkers + k.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub walkers: usize,
+    pub positions: Vec<Vec<f64>>,
+    pub log_posterior: Vec<f64>,
+    pub accepted: usize,
+    pub proposed: usize,
+}
+
+impl Chain {
+    pub fn acceptance(&self) -> f64 {
+        self.accepted as f64/self.proposed.max(1) as f64
+    }
+
+    /// Samples after discarding the first `burn` steps.
+    pub fn samples(&self, burn: usize) -> &[Vec<f64>] {
+        &self.positions[(burn*self.walkers).min(self.positions.len())..]
+    }
+
+    /// Highest-posterior sample and its ln posterior.
+    pub fn best(&self) -> (&[f64], f64) {
+        let (index, &lp) = self.log_posterior.iter().enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("a chain has at least one step");
+        (&self.positions[index], lp)
+    }
+}
+
+/// Affine-invariant ensemble sampling with the stretch move: walker k moves to
+/// y = x_j + Z (x_k − x_j) for a random other walker j, Z ~ g(z) ∝ z^{-1/2} on
+/// [1/a, a], accepted with probability min(1, Z^{d−1} p(y)/p(x_k)). The move needs at
+/// least two walkers.
+pub fn ensemble_sample<F>(log_posterior: F, start: Vec<Vec<f64>>, steps: usize, rng: &mut StdRng)
+    -> Result<Chain, FitError>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let walkers = start.len();
+    if walkers < 2 {
+        return Err(FitError::Sampler(format!("the stretch move needs at least 2 walkers, got {}", walkers)));
+    }
+    let dim = start[0].len();
+    let mut current = start;
+    let mut current_lp: Vec<f64> = current.iter().map(|x| log_posterior(x)).collect();
+    let mut chain = Chain {
+        walkers,
+        positions: current.clone(),
+        log_posterior: current_lp.clone(),
+        accepted: 0,
+        proposed: 0,
+    };
+
+    for _ in 1..steps {
+        for k in 0..walkers {
+            let j = (k + rng.gen_range(1..walkers)) % walkers;
+            let z = ((STRETCH - 1.0)*rng.gen::<f64>() + 1.0).powi(2)/STRETCH;
+            let proposal: Vec<f64> = (0..dim)
+                .map(|i| current[j][i] + z*(current[k][i] - current[j][i]))
+                .collect();
+            let lp = log_posterior(&proposal);
+            let log_ratio = (dim as f64 - 1.0)*z.ln() + lp - current_lp[k];
+            chain.proposed += 1;
+            if lp.is_finite() && rng.gen::<f64>().ln() < log_ratio {
+                current[k] = proposal;
+                current_lp[k] = lp;
+                chain.accepted += 1;
+            }
+        }
+        chain.positions.extend(current.iter().cloned());
+        chain.log_posterior.extend_from_slice(&current_lp);
+    }
+    Ok(chain)
+}
+
+/// Marginal summary of one parameter.
+#[derive(Debug, Clone, Copy)]
+pub struct Marginal {
+    pub mean: f64,
+    pub std: f64,
+    pub median: f64,
+    pub interval_68: [f64; 2],
+    pub interval_95: [f64; 2],
+}
+
+impl Marginal {
+    pub fn of(values: &mut [f64]) -> Self {
+        values.sort_by(f64::total_cmp);
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>()/n;
+        let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>()/(n - 1.0).max(1.0)).sqrt();
+        let quantile = |q: f64| {
+            let x = q*(n - 1.0);
+            let (lo, frac) = (x.floor() as usize, x.fract());
+            values[lo] + frac*(values[(lo + 1).min(values.len() - 1)] - values[lo])
+        };
+        Marginal {
+            mean,
+            std,
+            median: quantile(0.5),
+            interval_68: [quantile(0.158_655), quantile(0.841_345)],
+            interval_95: [quantile(0.022_750), quantile(0.977_250)],
+        }
+    }
+}
+
+/// Posterior of one model and its goodness of fit.
+#[derive(Debug, Clone)]
+pub struct FitResult {
+    pub model: FitModel,
+    pub chain: Chain,
+    pub marginals: Vec<Marginal>,
+    pub best_fit: Vec<f64>,
+    pub chi2_min: f64,
+    pub aic: f64,
+    pub bic: f64,
+}
+
+/// Sampler settings.
+#[derive(Debug, Clone, Copy)]
+pub struct SamplerSettings {
+    pub walkers: usize,
+    pub steps: usize,
+    pub burn: usize,
+    pub seed: u64,
+}
+
+/// Samples the posterior of `model`, walkers started uniformly over the prior box; fails
+/// if `START_DRAWS` draws of the box find no point of finite posterior for a walker.
+pub fn fit(likelihood: &Likelihood, model: FitModel, settings: &SamplerSettings) -> Result<FitResult, FitError> {
+    let mut rng = StdRng::seed_from_u64(settings.seed);
+    let bounds = likelihood.bounds(model);
+    let start = (0..settings.walkers).map(|_| {
+        (0..START_DRAWS)
+            .map(|_| bounds.iter().map(|b| rng.gen_range(b[0]..=b[1])).collect::<Vec<f64>>())
+            .find(|theta| likelihood.log_posterior(model, theta).is_finite())
+            .ok_or_else(|| FitError::Sampler(format!(
+                "{}: no point of finite posterior in {} draws from the prior box {:?}",
+                model.label(), START_DRAWS, bounds)))
+    }).collect::<Result<Vec<Vec<f64>>, FitError>>()?;
+
+    let chain = ensemble_sample(|theta| likelihood.log_posterior(model, theta), start, settings.steps, &mut rng)?;
+    let samples = chain.samples(settings.burn);
+    let marginals = (0..bounds.len())
+        .map(|i| Marginal::of(&mut samples.iter().map(|s| s[i]).collect::<Vec<f64>>()))
+        .collect();
+    let (best, lp) = chain.best();
+    let best_fit = best.to_vec();
+    let chi2_min = -2.0*lp;
+    let k = bounds.len() as f64;
+    let n = likelihood.data_count() as f64;
+    Ok(FitResult {
+        model,
+        marginals,
+        best_fit,
+        chi2_min,
+        aic: chi2_min + 2.0*k,
+        bic: chi2_min + k*n.ln(),
+        chain,
+    })
+}
+
+/// Writes a chain as step,walker,<parameters>,log_posterior.
+pub fn write_chain(path: &Path, result: &FitResult) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "step,walker,{},log_posterior", result.model.parameter_names().join(","))?;
+    let walkers = result.chain.walkers;
+    for (index, (theta, lp)) in result.chain.positions.iter().zip(&result.chain.log_posterior).enumerate() {
+        let values: Vec<String> = theta.iter().map(|v| v.to_string()).collect();
+        writeln!(file, "{},{},{},{}", index/walkers, index%walkers, values.join(","), lp)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,483 @@
+// Integrator layer for the cosmology run.
+//
+// Systems are written as dy/dx = f(x, y); x can be cosmic time, e-folds or log-time.
+// Available schemes:
+//   * classical RK4 with a fixed step,
+//   * Dormand-Prince RK45 with embedded error estimate and adaptive step control,
+//   * Störmer-Verlet (kick-drift-kick), symplectic, for q̈ = a(q), see `leapfrog`,
+//   * for stiff systems (reaction networks), linearised backward Euler with step-doubling
+//     error control, see `integrate_stiff`.
+
+use std::fmt;
+
+/// Why an integration could not reach its end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntegrationError {
+    /// The adaptive step shrank until x + h == x: the tolerances cannot be met past x.
+    StepUnderflow { x: f64 },
+    /// The symplectic scheme was asked to integrate a first-order system.
+    NotFirstOrder,
+}
+
+impl fmt::Display for IntegrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrationError::StepUnderflow { x } => write!(f, "step size underflow at x = {}", x),
+            IntegrationError::NotFirstOrder => {
+                write!(f, "the symplectic scheme integrates a second-order system, see leapfrog()")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IntegrationError {}
+
+/// First-order ODE system dy/dx = f(x, y).
+pub trait OdeSystem {
+    fn dim(&self) -> usize;
+    fn derivatives(&self, x: f64, y: &[f64], dydx: &mut [f64]);
+
+    /// Row-major Jacobian ∂f_i/∂y_j, by forward differences unless the system overrides it;
+    /// returns how many times it evaluated `derivatives` (0 for an analytic Jacobian).
+    fn jacobian(&self, x: f64, y: &[f64], jac: &mut [f64]) -> usize {
+        let n = self.dim();
+        let mut f0 = vec![0.0; n];
+        let mut f1 = vec![0.0; n];
+        let mut shifted = y.to_vec();
+        self.derivatives(x, y, &mut f0);
+        for j in 0..n {
+            let dy = f64::EPSILON.sqrt() * y[j].abs().max(1e-8);
+            shifted[j] = y[j] + dy;
+            self.derivatives(x, &shifted, &mut f1);
+            shifted[j] = y[j];
+            for i in 0..n {
+                jac[i * n + j] = (f1[i] - f0[i]) / dy;
+            }
+        }
+        n + 1
+    }
+}
+
+/// Second-order system q̈ = a(q) with a velocity-independent acceleration,
+/// the form required by the symplectic scheme.
+pub trait SecondOrderSystem {
+    fn acceleration(&self, q: f64) -> f64;
+    /// Natural time scale at q (e.g. the Hubble time), which the step is a fraction of.
+    fn time_scale(&self, q: f64) -> f64;
+}
+
+/// Error tolerances for the adaptive scheme: err_i ≤ atol + rtol |y_i|.
+#[derive(Debug, Clone, Copy)]
+pub struct Tolerances {
+    pub rtol: f64,
+    pub atol: f64,
+}
+
+/// Which scheme drives the run.
+#[derive(Debug, Clone, Copy)]
+pub enum Method {
+    /// Fixed step h in the independent variable.
+    Rk4 { step: f64 },
+    /// Adaptive step with the given tolerances; `initial_step` is only a first guess.
+    DormandPrince45 { tolerances: Tolerances, initial_step: f64 },
+    /// Symplectic Verlet on (q, q̇) stepping `fraction` of the system's time scale, for a
+    /// `SecondOrderSystem` only. It conserves the first integral q̇²/2 − ∫a dq to round-off,
+    /// which bounds the absolute, not the relative, error of q̇ once q̇ decays.
+    Leapfrog { fraction: f64 },
+}
+
+/// Adaptive linearised backward Euler for stiff systems, see `integrate_stiff`.
+#[derive(Debug, Clone, Copy)]
+pub struct StiffMethod {
+    pub tolerances: Tolerances,
+    pub initial_step: f64,
+}
+
+/// Step statistics of an integration.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IntegrationStats {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub evaluations: usize,
+}
+
+impl IntegrationStats {
+    pub fn merge(&mut self, other: &IntegrationStats) {
+        self.accepted += other.accepted;
+        self.rejected += other.rejected;
+        self.evaluations += other.evaluations;
+    }
+}
+
+/// One classical fourth-order Runge-Kutta step.
+pub fn rk4_step<S: OdeSystem>(system: &S, x: f64, y: &[f64], h: f64) -> Vec<f64> {
+    let n = system.dim();
+    let mut k1 = vec![0.0; n];
+    let mut k2 = vec![0.0; n];
+    let mut k3 = vec![0.0; n];
+    let mut k4 = vec![0.0; n];
+    let mut tmp = vec![0.0; n];
+
+    system.derivatives(x, y, &mut k1);
+    for i in 0..n {
+        tmp[i] = y[i] + 0.5 * h * k1[i];
+    }
+    system.derivatives(x + 0.5 * h, &tmp, &mut k2);
+    for i in 0..n {
+        tmp[i] = y[i] + 0.5 * h * k2[i];
+    }
+    system.derivatives(x + 0.5 * h, &tmp, &mut k3);
+    for i in 0..n {
+        tmp[i] = y[i] + h * k3[i];
+    }
+    system.derivatives(x + h, &tmp, &mut k4);
+
+    (0..n)
+        .map(|i| y[i] + h / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+        .collect()
+}
+
+// Dormand-Prince 5(4) Butcher tableau.
+const DP_C: [f64; 7] = [0.0, 1.0 / 5.0, 3.0 / 10.0, 4.0 / 5.0, 8.0 / 9.0, 1.0, 1.0];
+const DP_A: [[f64; 6]; 7] = [
+    [0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [1.0 / 5.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+    [3.0 / 40.0, 9.0 / 40.0, 0.0, 0.0, 0.0, 0.0],
+    [44.0 / 45.0, -56.0 / 15.0, 32.0 / 9.0, 0.0, 0.0, 0.0],
+    [19372.0 / 6561.0, -25360.0 / 2187.0, 64448.0 / 6561.0, -212.0 / 729.0, 0.0, 0.0],
+    [9017.0 / 3168.0, -355.0 / 33.0, 46732.0 / 5247.0, 49.0 / 176.0, -5103.0 / 18656.0, 0.0],
+    [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0],
+];
+// 5th-order weights (same as the last row of A, FSAL) and 4th-order embedded weights.
+const DP_B5: [f64; 7] = [35.0 / 384.0, 0.0, 500.0 / 1113.0, 125.0 / 192.0, -2187.0 / 6784.0, 11.0 / 84.0, 0.0];
+const DP_B4: [f64; 7] = [
+    5179.0 / 57600.0,
+    0.0,
+    7571.0 / 16695.0,
+    393.0 / 640.0,
+    -92097.0 / 339200.0,
+    187.0 / 2100.0,
+    1.0 / 40.0,
+];
+
+/// One Dormand-Prince step: returns the 5th-order solution and the scaled error norm
+/// (≤ 1 means the step meets the tolerances).
+pub fn dormand_prince_step<S: OdeSystem>(
+    system: &S,
+    x: f64,
+    y: &[f64],
+    h: f64,
+    tolerances: &Tolerances,
+) -> (Vec<f64>, f64) {
+    let n = system.dim();
+    let mut k = vec![vec![0.0; n]; 7];
+    let mut tmp = vec![0.0; n];
+
+    for stage in 0..7 {
+        for i in 0..n {
+            let mut acc = 0.0;
+            for (j, kj) in k.iter().enumerate().take(stage) {
+                acc += DP_A[stage][j] * kj[i];
+            }
+            tmp[i] = y[i] + h * acc;
+        }
+        system.derivatives(x + DP_C[stage] * h, &tmp, &mut k[stage]);
+    }
+
+    let mut y5 = vec![0.0; n];
+    let mut err_sq = 0.0;
+    for i in 0..n {
+        let mut high = 0.0;
+        let mut low = 0.0;
+        for (stage, ks) in k.iter().enumerate() {
+            high += DP_B5[stage] * ks[i];
+            low += DP_B4[stage] * ks[i];
+        }
+        y5[i] = y[i] + h * high;
+        let scale = tolerances.atol + tolerances.rtol * y[i].abs().max(y5[i].abs());
+        let e = h * (high - low) / scale;
+        err_sq += e * e;
+    }
+    (y5, (err_sq / n as f64).sqrt())
+}
+
+/// Where an integration stands between two steps: x, y, the step size the next step will
+/// try and the work done so far. Continuing from it reproduces the uninterrupted integration
+/// exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntegratorState {
+    pub x: f64,
+    pub y: Vec<f64>,
+    pub step: f64,
+    pub stats: IntegrationStats,
+}
+
+impl IntegratorState {
+    /// The state at x0 of an integration towards x1, with the first step of `method`.
+    pub fn start(method: &Method, x0: f64, y0: &[f64], x1: f64) -> Self {
+        let step = match *method {
+            Method::Rk4 { step } => step,
+            Method::DormandPrince45 { initial_step, .. } => initial_step,
+            Method::Leapfrog { fraction } => fraction,
+        };
+        IntegratorState {
+            x: x0,
+            y: y0.to_vec(),
+            step: step.abs() * (x1 - x0).signum(),
+            stats: IntegrationStats::default(),
+        }
+    }
+}
+
+/// Integrates from x0 to x1 with RK4 or Dormand-Prince, calling `observe(x, y)`
+/// at the start and after every accepted step; the integration stops early once
+/// `observe` returns false. Returns (x, y) at the end, or an error if the adaptive step
+/// underflows before it or `method` is the symplectic one.
+pub fn integrate<S, F>(
+    system: &S,
+    method: &Method,
+    x0: f64,
+    y0: &[f64],
+    x1: f64,
+    stats: &mut IntegrationStats,
+    mut observe: F,
+) -> Result<(f64, Vec<f64>), IntegrationError>
+where
+    S: OdeSystem,
+    F: FnMut(f64, &[f64]) -> bool,
+{
+    let start = IntegratorState::start(method, x0, y0, x1);
+    let end = integrate_from(system, method, start, x1, |state| observe(state.x, &state.y))?;
+    stats.merge(&end.stats);
+    Ok((end.x, end.y))
+}
+
+/// `integrate` from an intermediate state, e.g. one saved by an earlier run: `observe`
+/// sees the state after every accepted step, including the step size that follows, and
+/// the work is counted on in `stats` of the state.
+pub fn integrate_from<S, F>(
+    system: &S,
+    method: &Method,
+    start: IntegratorState,
+    x1: f64,
+    mut observe: F,
+) -> Result<IntegratorState, IntegrationError>
+where
+    S: OdeSystem,
+    F: FnMut(&IntegratorState) -> bool,
+{
+    if let Method::Leapfrog { .. } = method {
+        return Err(IntegrationError::NotFirstOrder);
+    }
+    let mut state = start;
+    if !observe(&state) {
+        return Ok(state);
+    }
+    let direction = (x1 - state.x).signum();
+
+    match *method {
+        Method::Rk4 { .. } => {
+            let h = state.step;
+            while (x1 - state.x) * direction > 0.0 {
+                let h = if (state.x + h - x1) * direction > 0.0 { x1 - state.x } else { h };
+                state.y = rk4_step(system, state.x, &state.y, h);
+                state.x += h;
+                state.stats.accepted += 1;
+                state.stats.evaluations += 4;
+                if !observe(&state) {
+                    break;
+                }
+            }
+        }
+        Method::DormandPrince45 { tolerances, .. } => {
+            const SAFETY: f64 = 0.9;
+            const MAX_GROWTH: f64 = 5.0;
+            const MIN_SHRINK: f64 = 0.2;
+            while (x1 - state.x) * direction > 0.0 {
+                let mut h = state.step;
+                if (state.x + h - x1) * direction > 0.0 {
+                    h = x1 - state.x;
+                }
+                let (y_new, err) = dormand_prince_step(system, state.x, &state.y, h, &tolerances);
+                state.stats.evaluations += 7;
+                let factor = if err == 0.0 {
+                    MAX_GROWTH
+                } else if !err.is_finite() {
+                    // The trial step left the domain of the system (overflow, NaN): retry shorter
+                    MIN_SHRINK
+                } else {
+                    (SAFETY * err.powf(-0.2)).clamp(MIN_SHRINK, MAX_GROWTH)
+                };
+                state.step = h * factor;
+                if err <= 1.0 {
+                    state.x += h;
+                    state.y = y_new;
+                    state.stats.accepted += 1;
+                    if !observe(&state) {
+                        break;
+                    }
+                } else {
+                    state.stats.rejected += 1;
+                }
+                if state.x + state.step == state.x {
+                    return Err(IntegrationError::StepUnderflow { x: state.x });
+                }
+            }
+        }
+        Method::Leapfrog { .. } => unreachable!("refused above"),
+    }
+    Ok(state)
+}
+
+/// Störmer-Verlet (kick-drift-kick) from `start` = (t, q, q̇) until q reaches q_end or q̇
+/// turns around, each step `fraction` of the system's time scale at its start. Calls
+/// `observe(t, q, q̇)` at the start and after every step, stopping early once it returns
+/// false. Returns (t, q, q̇) at the end, or an error if the step no longer advances t.
+pub fn leapfrog<S, F>(
+    system: &S,
+    fraction: f64,
+    start: (f64, f64, f64),
+    q_end: f64,
+    stats: &mut IntegrationStats,
+    mut observe: F,
+) -> Result<(f64, f64, f64), IntegrationError>
+where
+    S: SecondOrderSystem,
+    F: FnMut(f64, f64, f64) -> bool,
+{
+    let (mut t, mut q, mut v) = start;
+    if !observe(t, q, v) {
+        return Ok((t, q, v));
+    }
+    while q < q_end && v > 0.0 {
+        let dt = fraction * system.time_scale(q);
+        if t + dt == t {
+            return Err(IntegrationError::StepUnderflow { x: t });
+        }
+        v += 0.5 * dt * system.acceleration(q);
+        q += dt * v;
+        v += 0.5 * dt * system.acceleration(q);
+        t += dt;
+        stats.accepted += 1;
+        stats.evaluations += 2;
+        if !observe(t, q, v) {
+            break;
+        }
+    }
+    Ok((t, q, v))
+}
+
+/// Solves a x = b in place (b becomes x) by Gaussian elimination with partial pivoting;
+/// `a` is row-major n × n and is overwritten.
+fn solve_linear(a: &mut [f64], b: &mut [f64]) {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i * n + col].abs().total_cmp(&a[j * n + col].abs())).unwrap();
+        if pivot != col {
+            for k in 0..n {
+                a.swap(col * n + k, pivot * n + k);
+            }
+            b.swap(col, pivot);
+        }
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            if factor != 0.0 {
+                for k in col..n {
+                    a[row * n + k] -= factor * a[col * n + k];
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| a[row * n + k] * b[k]).sum();
+        b[row] = (b[row] - tail) / a[row * n + row];
+    }
+}
+
+/// One linearised backward Euler step, y + (I − h J)⁻¹ h f with f and J taken at x + h;
+/// also returns the number of evaluations of f it took, the Jacobian's included.
+pub fn backward_euler_step<S: OdeSystem>(system: &S, x: f64, y: &[f64], h: f64) -> (Vec<f64>, usize) {
+    let n = system.dim();
+    let mut f = vec![0.0; n];
+    let mut a = vec![0.0; n * n];
+    system.derivatives(x + h, y, &mut f);
+    let evaluations = 1 + system.jacobian(x + h, y, &mut a);
+    for (k, entry) in a.iter_mut().enumerate() {
+        *entry = if k / n == k % n { 1.0 } else { 0.0 } - h * (*entry);
+    }
+    for fi in f.iter_mut() {
+        *fi *= h;
+    }
+    solve_linear(&mut a, &mut f);
+    (y.iter().zip(&f).map(|(yi, dy)| yi + dy).collect(), evaluations)
+}
+
+/// Integrates a stiff system from x0 to x1 with linearised backward Euler, which is
+/// L-stable and so follows equilibria whose relaxation rates far exceed 1/h. Each step is
+/// also taken as two half steps; their difference is the error estimate against the
+/// tolerances, and the Richardson extrapolation 2 y_half − y_full, second order, is kept.
+/// `observe` and the result work as in `integrate`.
+pub fn integrate_stiff<S, F>(
+    system: &S,
+    method: &StiffMethod,
+    x0: f64,
+    y0: &[f64],
+    x1: f64,
+    stats: &mut IntegrationStats,
+    mut observe: F,
+) -> Result<(f64, Vec<f64>), IntegrationError>
+where
+    S: OdeSystem,
+    F: FnMut(f64, &[f64]) -> bool,
+{
+    const SAFETY: f64 = 0.9;
+    const MAX_GROWTH: f64 = 2.0;
+    const MIN_SHRINK: f64 = 0.2;
+    let mut x = x0;
+    let mut y = y0.to_vec();
+    if !observe(x, &y) {
+        return Ok((x, y));
+    }
+    let direction = (x1 - x0).signum();
+    let tolerances = method.tolerances;
+    let mut h = method.initial_step.abs() * direction;
+    while (x1 - x) * direction > 0.0 {
+        if (x + h - x1) * direction > 0.0 {
+            h = x1 - x;
+        }
+        let (full, full_evaluations) = backward_euler_step(system, x, &y, h);
+        let (mid, mid_evaluations) = backward_euler_step(system, x, &y, 0.5 * h);
+        let (half, half_evaluations) = backward_euler_step(system, x + 0.5 * h, &mid, 0.5 * h);
+        stats.evaluations += full_evaluations + mid_evaluations + half_evaluations;
+        let err = (full.iter().zip(&half).zip(&y)
+            .map(|((a, b), y0)| {
+                let e = (a - b) / (tolerances.atol + tolerances.rtol * y0.abs().max(b.abs()));
+                e * e
+            })
+            .sum::<f64>() / y.len() as f64).sqrt();
+        // The estimate is that of the first-order step, scaling as h²
+        let factor = if err == 0.0 {
+            MAX_GROWTH
+        } else if !err.is_finite() {
+            MIN_SHRINK
+        } else {
+            (SAFETY * err.powf(-0.5)).clamp(MIN_SHRINK, MAX_GROWTH)
+        };
+        if err <= 1.0 {
+            x += h;
+            y = half.iter().zip(&full).map(|(b, a)| 2.0 * b - a).collect();
+            stats.accepted += 1;
+            if !observe(x, &y) {
+                break;
+            }
+        } else {
+            stats.rejected += 1;
+        }
+        h *= factor;
+        if x + h == x {
+            return Err(IntegrationError::StepUnderflow { x });
+        }
+    }
+    Ok((x, y))
+}
@@ -0,0 +1,324 @@
+//! Cosmology of the running-vacuum model: the unified background of `model.rs`, its
+//! plasma, integrators, diagnostics and observables, and the analyses built on them.
+//! The `first-product` binary is the command-line front end of this library.
+
+pub mod bbn;
+pub mod checkpoint;
+pub mod diagnostics;
+pub mod dual;
+pub mod fit;
+pub mod integrator;
+pub mod model;
+pub mod numeric;
+pub mod observables;
+pub mod output;
+pub mod params;
+pub mod perturbations;
+pub mod plasma;
+pub mod plot;
+pub mod recombination;
+pub mod reheating;
+pub mod relic;
+pub mod sweep;
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use checkpoint::Checkpoint;
+use diagnostics::Diagnostics;
+use integrator::{IntegrationError, IntegrationStats, IntegratorState};
+use model::{Component, Cosmology, RunningVacuum, State};
+use numeric::Precision;
+use output::{Column, Kind, Metadata, RecordWriter, Value};
+use params::{ParamsError, RunConfig};
+
+// Units: c = ħ = k_B = 1, M_P = (8πG)^(-1/2); the parameters live in `params.rs`.
+
+// The whole history, primeval de Sitter -> radiation -> matter -> final de Sitter, is
+// one integration of the unified model in `model.rs`, in e-folds N = ln a; the era of
+// each step is the dominant component rather than a phase of the loop.
+
+/// A stretch of the run dominated by one component, in cosmic time.
+#[derive(Debug, Clone, Copy)]
+pub struct Epoch {
+    pub component: Component,
+    pub t_start: f64,
+    pub t_end: f64,
+}
+
+/// Numeric CSV columns checked for NaN/Inf on every row.
+const CHECKED_COLUMNS: [&str; 16] = ["t", "phi", "phidot", "H", "Potential", "Kinetic", "Radiation", "Matter",
+    "T", "gstar", "FriedmannResidual", "AccelerationResidual", "w", "q", "epsilon", "eta"];
+
+/// Columns of the trajectory: the step, the checked columns with the era after gstar, and the flag.
+pub fn trajectory_columns() -> Vec<Column> {
+    let column = |name: &str, kind| Column { name: name.to_string(), kind };
+    let mut columns = vec![column("step", Kind::Integer)];
+    columns.extend(CHECKED_COLUMNS[..10].iter().map(|name| column(name, Kind::Float)));
+    columns.push(column("Era", Kind::Text));
+    columns.extend(CHECKED_COLUMNS[10..].iter().map(|name| column(name, Kind::Float)));
+    columns.push(column("Flag", Kind::Integer));
+    columns
+}
+
+/// Opens the trajectory file of `config`, fresh or truncated to `resume_at` bytes.
+pub fn open_trajectory(config: &RunConfig, resume_at: Option<u64>) -> io::Result<Box<dyn RecordWriter>> {
+    let metadata = Metadata { columns: trajectory_columns(), run: config.clone() };
+    output::open(&config.output, config.trajectory.format, &metadata, resume_at)
+}
+
+/// The running totals of a recorder, saved with each checkpoint.
+#[derive(Debug, Clone, Default)]
+pub struct Progress {
+    pub step: usize, // steps recorded so far
+    pub epochs: Vec<Epoch>,
+    pub max_residual: f64,
+    pub max_fluid_fraction: f64,
+    pub flagged: usize,
+    pub first_flagged: Option<usize>,
+    pub crossover: Option<(f64, f64)>,
+    pub last_written: Option<f64>, // N of the last row written
+}
+
+/// Writes the decimated trajectory with its diagnostics and tracks the run summary.
+struct Recorder {
+    writer: Option<Box<dyn RecordWriter>>,
+    threshold: f64,
+    precision: Precision,
+    every: usize,
+    min_dn: f64,
+    progress: Progress,
+    held: Option<(f64, Vec<Value<'static>>)>, // the latest row skipped by decimation, with its N
+    non_finite: Option<(usize, &'static str)>,
+    error: Option<io::Error>,
+}
+
+impl Recorder {
+    /// Records one step; returns false, after writing the row, if any column is NaN or
+    /// infinite or the row cannot be written.
+    fn record(&mut self, model: &RunningVacuum, state: &State) -> bool {
+        let diag = Diagnostics::compute(model, state, self.threshold, self.precision);
+        let h = model.hubble(state.z);
+        let pot = model.potential(h);
+        let kin = state.rho_r() + state.rho_m();
+        let component = model.dominant(state);
+        let radiation = model.radiation(state);
+        let values = [state.t, model.phi(state.n), model.phidot(state), h, pot, kin, state.rho_r(), state.rho_m(),
+                      radiation.temperature, radiation.g_star, diag.friedmann_residual, diag.acceleration_residual, diag.w, diag.q, diag.epsilon, diag.eta];
+        let p = &mut self.progress;
+
+        match p.epochs.last_mut() {
+            Some(epoch) if epoch.component == component => epoch.t_end = state.t,
+            _ => p.epochs.push(Epoch { component, t_start: state.t, t_end: state.t }),
+        }
+        p.max_residual = p.max_residual
+            .max(diag.friedmann_residual.abs())
+            .max(diag.acceleration_residual.abs());
+        p.max_fluid_fraction = p.max_fluid_fraction.max(kin/(kin + pot));
+        if p.crossover.is_none() && radiation.temperature < model.plasma.crossover {
+            p.crossover = Some((state.t, h));
+        }
+        if diag.flagged {
+            p.flagged += 1;
+            p.first_flagged.get_or_insert(p.step);
+        }
+        if let Some(column) = numeric::first_non_finite(&values) {
+            self.non_finite = Some((p.step, CHECKED_COLUMNS[column]));
+        }
+
+        if self.writer.is_some() {
+            let mut row = vec![Value::Integer(p.step as u64)];
+            row.extend(values[..10].iter().map(|&v| Value::Float(v)));
+            row.push(Value::Text(component.label()));
+            row.extend(values[10..].iter().map(|&v| Value::Float(v)));
+            row.push(Value::Integer(diag.flagged as u64));
+            let due = p.step.is_multiple_of(self.every) && p.last_written.is_none_or(|n| state.n - n >= self.min_dn);
+            if due || self.non_finite.is_some() {
+                self.held = None;
+                self.write(state.n, &row);
+            } else {
+                self.held = Some((state.n, row));
+            }
+        }
+        self.progress.step += 1;
+        self.non_finite.is_none() && self.error.is_none()
+    }
+
+    fn write(&mut self, n: f64, row: &[Value]) {
+        if let Some(writer) = self.writer.as_mut() {
+            match writer.write_row(row) {
+                Ok(()) => self.progress.last_written = Some(n),
+                Err(e) => {
+                    self.error.get_or_insert(e);
+                }
+            }
+        }
+    }
+
+    /// Saves the run at `state` to `path` once every `every` recorded steps, before the
+    /// step at `state` is recorded; returns false if that fails.
+    fn checkpoint(&mut self, path: &Path, every: usize, config: &RunConfig, state: &IntegratorState) -> bool {
+        let Some(writer) = self.writer.as_mut() else { return true };
+        if self.progress.step == 0 || !self.progress.step.is_multiple_of(every) {
+            return true;
+        }
+        let saved = writer.sync().and_then(|output_length| Checkpoint {
+            config: config.clone(),
+            integrator: state.clone(),
+            progress: self.progress.clone(),
+            output_length,
+        }.save(path));
+        if let Err(e) = saved {
+            self.error.get_or_insert(e);
+        }
+        self.error.is_none()
+    }
+
+    /// Writes the last row if decimation held it back and flushes the trajectory.
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some((n, row)) = self.held.take() {
+            self.write(n, &row);
+        }
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+        if let Some(writer) = self.writer.as_mut() {
+            writer.sync()?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a run stopped short of a summary.
+#[derive(Debug)]
+pub enum RunError {
+    /// The trajectory or a checkpoint could not be written.
+    Io(io::Error),
+    /// The integrator could not carry the run on.
+    Integration(IntegrationError),
+    /// The parameters do not make a model.
+    Params(ParamsError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Io(e) => write!(f, "{}", e),
+            RunError::Integration(e) => write!(f, "integration failed: {}", e),
+            RunError::Params(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<io::Error> for RunError {
+    fn from(e: io::Error) -> Self {
+        RunError::Io(e)
+    }
+}
+
+impl From<IntegrationError> for RunError {
+    fn from(e: IntegrationError) -> Self {
+        RunError::Integration(e)
+    }
+}
+
+impl From<ParamsError> for RunError {
+    fn from(e: ParamsError) -> Self {
+        RunError::Params(e)
+    }
+}
+
+/// Outcome of one run, the per-run metrics of a sweep.
+#[derive(Debug, Clone)]
+pub struct RunSummary {
+    pub stats: IntegrationStats,
+    pub epochs: Vec<Epoch>, // consecutive eras by dominant component
+    pub final_h_over_hf: f64,
+    pub max_fluid_fraction: f64, // max (ρ_r + ρ_m)/ρ along the run
+    pub max_residual: f64,
+    pub flagged: usize,
+    pub first_flagged: Option<usize>,
+    pub crossover: Option<(f64, f64)>, // (t, H) where T first falls below the QCD crossover
+    pub non_finite: Option<(usize, &'static str)>, // (step, column) where the run was stopped
+}
+
+impl RunSummary {
+    /// Cosmic time spent with `component` dominant, GeV^-1.
+    pub fn duration(&self, component: Component) -> f64 {
+        self.epochs.iter()
+            .filter(|e| e.component == component)
+            .map(|e| e.t_end - e.t_start)
+            .sum()
+    }
+
+    /// Duration of the primeval de Sitter phase (the first vacuum-dominated epoch).
+    pub fn primeval_duration(&self) -> f64 {
+        self.epochs.first()
+            .filter(|e| e.component == Component::Vacuum)
+            .map_or(0.0, |e| e.t_end - e.t_start)
+    }
+}
+
+/// Runs the model from the primeval de Sitter phase until H/HF − 1 falls below the
+/// tolerance, writing the trajectory to `output` if given, with checkpoints if the
+/// configuration asks for them. A NaN or infinite value stops the run at that step.
+pub fn run(config: &RunConfig, output: Option<Box<dyn RecordWriter>>) -> Result<RunSummary, RunError> {
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+    let start = model.initial_state();
+    let method = config.integration.method();
+    let start = IntegratorState::start(&method, start.n, &start.to_vec(), model::MAX_EFOLDS);
+    drive(config, model, output, start, Progress::default())
+}
+
+/// Carries on the run saved in `checkpoint`, appending to `output`, which `open_trajectory`
+/// has truncated to the checkpoint's length; the result is that of the uninterrupted run.
+pub fn resume(checkpoint: Checkpoint, output: Box<dyn RecordWriter>) -> Result<RunSummary, RunError> {
+    let config = &checkpoint.config;
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma)?;
+    drive(config, model, Some(output), checkpoint.integrator, checkpoint.progress)
+}
+
+fn drive(config: &RunConfig, model: RunningVacuum, output: Option<Box<dyn RecordWriter>>,
+         start: IntegratorState, progress: Progress) -> Result<RunSummary, RunError> {
+    let numerics = &config.integration;
+    let mut recorder = Recorder {
+        writer: output,
+        threshold: numerics.residual_threshold,
+        precision: numerics.precision,
+        every: config.trajectory.every,
+        min_dn: config.trajectory.min_dn,
+        progress,
+        held: None,
+        non_finite: None,
+        error: None,
+    };
+
+    let checkpoint = config.trajectory.checkpoint.as_deref();
+    let end = model.evolve_from(&numerics.method(), start, |state, at| {
+        let saved = checkpoint.is_none_or(|path| {
+            recorder.checkpoint(path, config.trajectory.checkpoint_every, config, at)
+        });
+        saved && recorder.record(&model, state)
+            && numeric::above_final(&model, state.z, numerics.h_final_tolerance, numerics.precision)
+    });
+    // Rows written before the integrator gave up are kept
+    let finished = recorder.finish();
+    let end = end?;
+    finished?;
+
+    let progress = recorder.progress;
+    Ok(RunSummary {
+        stats: end.stats,
+        epochs: progress.epochs,
+        final_h_over_hf: model.hubble(State::from_vec(end.x, &end.y).z)/config.cosmology.hf(),
+        max_fluid_fraction: progress.max_fluid_fraction,
+        max_residual: progress.max_residual,
+        flagged: progress.flagged,
+        first_flagged: progress.first_flagged,
+        crossover: progress.crossover,
+        non_finite: recorder.non_finite,
+    })
+}
@@ -1,183 +1,143 @@
-use ndarray::prelude::*;
-use rand::SeedableRng;
-use rand::{rngs::StdRng, Rng};
-use std::f64::consts::PI;
 use std::fs::File;
 use std::io::Write;
-use lazy_static::lazy_static;
-
-// Fundamental constants matching 1:1 with the Standard Model and relativistic units
-// Units: c = ħ = k_B = 1, M_P = (8πG)^(-1/2)
-const MP: f64 = 2.435e18; // Reduced Planck mass in GeV
-const M: f64 = 1.0e9;     // Example scale, adjustable to tested scenario
-const HI: f64 = MP;       // Primeval scale at or below reduced Planck mass
-const H0: f64 = 1.0e-33;  // Present-day Hubble scale (~ in GeV)
-const NU: f64 = 0.001;    // Example small deviation parameter from rigid vacuum
-const OMEGA_M0: f64 = 0.3;
-const OMEGA_R0: f64 = 1e-5;
-
-lazy_static! {
-    static ref HF: f64 = H0*((OMEGA_M0/(1.0 - NU)).sqrt()); // Final de Sitter scale derived
-}
-
-// Equation of state indices for radiation and matter
-// Radiation: ω = 1/3, Matter: ω = 0
-// The code runs through radiation-to-matter and matter-to-final de Sitter phases.
-
-// Derived parameters from the theoretical appendices:
-fn alpha_param() -> f64 {
-    // According to the final expression from the appendices:
-    // α = ((1−ν)^3(1−2ν))^{1/4} * (HI/(2^{1/4} * MP * M))
-    let one_minus_nu = 1.0 - NU;
-    let one_minus_2nu = 1.0 - 2.0*NU;
-    let val = ((one_minus_nu.powf(3.0)) * one_minus_2nu).powf(0.25);
-    val * (HI/(M*(2.0_f64.powf(0.25)*MP)))
-}
-
-fn sigma_param() -> f64 {
-    // For matter era (ω=0):
-    // σ = (1−ν)*HF/(M^2)
-    (1.0 - NU)*(*HF)/(M*M)
-}
-
-// Potential and kinetic energy densities in the radiation era:
-fn potential_radiation(phi: f64, alpha: f64) -> f64 {
-    // V(φ)=VI [1+(α φ)^4]^-2 [1+ν(α φ)^4]
-    let vi = 3.0*MP.powi(2)*HI.powi(2);
-    let x = (alpha*phi).powi(4);
-    vi * (1.0 + NU*x)/((1.0 + x).powi(2))
-}
-
-fn kinetic_radiation(phi: f64, alpha: f64) -> f64 {
-    // ρ_k(φ) = (1−ν)*VI*(αφ)^4 / [ (1+(αφ)^4)^2 ]
-    let vi = 3.0*MP.powi(2)*HI.powi(2);
-    let x = (alpha*phi).powi(4);
-    (1.0 - NU)*vi*x/((1.0 + x).powi(2))
-}
-
-// Potential and kinetic energy densities in the matter era:
-fn potential_matter(phi: f64, sigma: f64) -> f64 {
-    // V(φ)=VF [1+ ν(σ φ)^{-3}]
-    // VF = 3 MP^2 HF^2
-    let vf = 3.0*MP.powi(2)*(*HF).powi(2);
-    let y = (sigma*phi).powf(-3.0);
-    vf*(1.0 + NU*y)
-}
-
-fn kinetic_matter(phi: f64, sigma: f64) -> f64 {
-    // ρ_k(φ) = (1−ν)*VF (σ φ)^{-3}/[1+ν(σ φ)^{-3}]
-    let vf = 3.0*MP.powi(2)*(*HF).powi(2);
-    let y = (sigma*phi).powf(-3.0);
-    (1.0 - NU)*vf*y/(1.0 + NU*y)
-}
-
-// Time evolution parameters:
-const TIME_STEPS: usize = 1000;
-const DT: f64 = 5.59 * 1e-44; // Very small timestep to resolve early universe dynamics
-
-struct FieldPoint {
-    phi: f64,
-    phidot: f64,
-}
-
-// Evolve during radiation era:
-fn evolve_radiation(phi: f64, phidot: f64, alpha: f64) -> (f64, f64) {
-    // H(φ)=HI/[1+(αφ)^4]^{1/2}
-    let x = (alpha*phi).powi(4);
-    let h_val = HI/(1.0 + x).sqrt();
-
-    // From eq.(56): φ̇ = (1−ν)*φ*H(φ)
-    let phidot_new = (1.0 - NU)*phi*h_val;
-
-    // To find φ̈, we differentiate φ̇ w.r.t φ:
-    // φ̇(φ) = (1−ν)*H(φ)*φ
-    // dφ̇/dφ = (1−ν)(H(φ) + φ dH/dφ)
-    // dH/dφ = HI*(-2)*α^4 φ^3/(1+x)^{3/2}
-    let alpha4 = alpha.powi(4);
-    let dH_dphi = HI*(-2.0)*alpha4*phi.powi(3)/((1.0+x).powf(1.5));
-    let dphidot_dphi = (1.0 - NU)*(h_val + phi*dH_dphi);
-
-    // φ̈ = dφ̇/dt = dφ̇/dφ * φ̇
-    let phiddot = dphidot_dphi * phidot_new;
-
-    let phi_next = phi + phidot*DT;
-    let phidot_next = phidot + phiddot*DT;
-
-    (phi_next, phidot_next)
+use std::path::Path;
+
+use first_product::model::{self, RunningVacuum};
+use first_product::observables::{self, History, LambdaCdm};
+use first_product::checkpoint::Checkpoint;
+use first_product::params::{ParamsError, RunConfig};
+use first_product::plasma;
+use first_product::{open_trajectory, resume, run, RunSummary};
+
+mod cli;
+
+/// A subcommand, given the arguments after its name; its usage follows malformed flags.
+type Command = fn(Vec<String>) -> Result<(), Box<dyn std::error::Error>>;
+
+const SUBCOMMANDS: &[(&str, Command, &str)] = &[
+    ("sweep", cli::sweep::main, cli::sweep::USAGE),
+    ("fit", cli::fit::main, cli::fit::USAGE),
+    ("spectrum", cli::spectrum::main, cli::spectrum::USAGE),
+    ("plasma", cli::plasma::main, cli::plasma::USAGE),
+    ("plot", cli::plot::main, cli::plot::USAGE),
+    ("bbn", cli::bbn::main, cli::bbn::USAGE),
+    ("recombination", cli::recombination::main, cli::recombination::USAGE),
+    ("relic", cli::relic::main, cli::relic::USAGE),
+    ("reheat", cli::reheating::main, cli::reheating::USAGE),
+];
+
+const USAGE: &str = "\
+usage: first-product [--config FILE.toml] [--output FILE.csv] [--method rk45|rk4]
+                     [--mp X] [--m X] [--hi X] [--h0 X] [--nu X] [--omega-m0 X] [--omega-r0 X]
+                     [--rtol X] [--atol X] [--rk4-step X]
+                     [--h-final-tolerance X] [--residual-threshold X] [--precision double|exact]
+                     [--plasma qgp|conformal] [--bag-root X] [--crossover-width X]
+                     [--z-max X] [--z-points N]
+                     [--format csv|jsonl|binary] [--every N] [--min-dn X]
+                     [--checkpoint FILE] [--checkpoint-every N]
+       first-product --resume FILE
+       first-product sweep --help
+       first-product fit --help
+       first-product spectrum --help
+       first-product plasma --help
+       first-product reheat --help
+       first-product bbn --help
+       first-product recombination --help
+       first-product relic --help
+       first-product plot FILE.csv [--out-dir DIR] [--format png|svg|both]
+
+Flags override values read from --config; everything else keeps its default.";
+
+/// Observables of the running-vacuum model and its ΛCDM reference, also written as a table.
+fn observables_of(config: &RunConfig) -> Result<Vec<History>, Box<dyn std::error::Error>> {
+    let method = config.integration.method();
+    let redshifts = config.observables.redshifts();
+    let histories = vec![
+        observables::running_vacuum(&RunningVacuum::new(&config.cosmology, &config.plasma)?, &method, &redshifts)?,
+        observables::lambda_cdm(&LambdaCdm::new(&config.cosmology), &method, &redshifts)?,
+    ];
+    let mut table = File::create(config.observables_path())?;
+    writeln!(table, "{}", History::CSV_HEADER)?;
+    for history in &histories {
+        for row in history.csv_rows() {
+            writeln!(table, "{}", row)?;
+        }
+    }
+    Ok(histories)
 }
 
-// Evolve during matter era:
-fn evolve_matter(phi: f64, phidot: f64, sigma: f64) -> (f64, f64) {
-    // H(φ)=HF(1+(σφ)^{-3})^{1/2}
-    let y = (sigma*phi).powf(-3.0);
-    let h_val = (*HF)*(1.0+y).sqrt();
-
-    let phidot_new = (1.0 - NU)*phi*h_val;
-
-    // dH/dφ for matter era:
-    // y=(σ φ)^{-3}, dy/dφ = -3σ^{-3}φ^{-4}
-    let dy_dphi = -3.0*(sigma.powf(-3.0))*phi.powf(-4.0);
-    let dH_dphi = (*HF)*0.5*(1.0+y).powf(-0.5)*dy_dphi;
-
-    let dphidot_dphi = (1.0 - NU)*(h_val + phi*dH_dphi);
-    let phiddot = dphidot_dphi * phidot_new;
-
-    let phi_next = phi + phidot*DT;
-    let phidot_next = phidot + phiddot*DT;
-
-    (phi_next, phidot_next)
+/// The run of the command line, fresh or continued from `--resume FILE`.
+fn trajectory(args: Vec<String>) -> Result<(RunConfig, RunSummary), Box<dyn std::error::Error>> {
+    if args.first().map(String::as_str) == Some("--resume") {
+        let [_, path] = args.as_slice() else {
+            return Err(ParamsError::Usage("--resume takes the checkpoint file and no other flags".to_string()).into());
+        };
+        let checkpoint = Checkpoint::load(Path::new(path)).map_err(|e| format!("{}: {}", path, e))?;
+        let config = checkpoint.config.clone();
+        let output = open_trajectory(&config, Some(checkpoint.output_length))?;
+        let summary = resume(checkpoint, output)?;
+        return Ok((config, summary));
+    }
+    let config = RunConfig::from_args(args)?;
+    let summary = run(&config, Some(open_trajectory(&config, None)?))?;
+    Ok((config, summary))
 }
 
 fn main() {
-    let alpha = alpha_param();
-    let sigma = sigma_param();
-
-    // Initial conditions:
-    let mut phi = 77.3147 * (1.0/137.0);
-    let x_init = (alpha*1e-30).powi(4);
-    let h_initial = HI/(1.0+x_init).sqrt();
-    let mut phidot = (1.0 - NU)*1e-30*h_initial;
-
-    let phi_end_radiation = 1.0/alpha;
-
-    let mut file = File::create("qgp_data.csv").unwrap();
-    writeln!(file, "step,phi,phidot,H,Potential,Kinetic,Era").unwrap();
-
-    // Radiation era
-    for step in 0..TIME_STEPS {
-        let pot = potential_radiation(phi, alpha);
-        let kin = kinetic_radiation(phi, alpha);
-        let x = (alpha*phi).powi(4);
-        let h_val = HI/(1.0+x).sqrt();
-        writeln!(file, "{},{},{},{},{},{},{}",
-                 step, phi, phidot, h_val, pot, kin, "radiation").unwrap();
-
-        if phi > phi_end_radiation {
-            break;
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let subcommand = SUBCOMMANDS.iter()
+        .find(|(name, _, _)| Some(*name) == args.first().map(String::as_str))
+        .map(|&(_, command, usage)| (command, usage));
+    if let Some((subcommand, usage)) = subcommand {
+        args.remove(0);
+        if let Err(e) = subcommand(args) {
+            cli::report(&*e, usage);
+            std::process::exit(2);
         }
-        let (phi_new, phidot_new) = evolve_radiation(phi, phidot, alpha);
-        phi = phi_new;
-        phidot = phidot_new;
+        return;
     }
 
-    // Matter era
-    for step in TIME_STEPS..(2*TIME_STEPS) {
-        let pot = potential_matter(phi, sigma);
-        let kin = kinetic_matter(phi, sigma);
-        let y = (sigma*phi).powf(-3.0);
-        let h_val = (*HF)*(1.0+y).sqrt();
-
-        writeln!(file, "{},{},{},{},{},{},{}",
-                 step, phi, phidot, h_val, pot, kin, "matter").unwrap();
-
-        let (phi_new, phidot_new) = evolve_matter(phi, phidot, sigma);
-        phi = phi_new;
-        phidot = phidot_new;
-
-        if (h_val - *HF).abs() < 1e-35 {
-            break;
-        }
+    let (config, summary) = trajectory(args).unwrap_or_else(|e| {
+        cli::report(&*e, USAGE);
+        std::process::exit(2);
+    });
+    let sidecar = config.write_sidecar(model::alpha_param(&config.cosmology)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    let histories = observables_of(&config).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
+    for epoch in &summary.epochs {
+        println!("{:>9}: t = {:.3e} .. {:.3e} GeV^-1",
+                 epoch.component.label(), epoch.t_start, epoch.t_end);
     }
-
-    println!("Simulation complete. Data in quantum_gravity_plasma_data.csv");
+    let stats = summary.stats;
+    println!("{} accepted, {} rejected steps, {} RHS evaluations; final H/HF = {}",
+             stats.accepted, stats.rejected, stats.evaluations, summary.final_h_over_hf);
+    println!("Max Friedmann/acceleration residual {:.3e} (threshold {:.0e})",
+             summary.max_residual, config.integration.residual_threshold);
+    if let Some((t, h)) = summary.crossover {
+        println!("QCD crossover (Tc = {:.4} GeV, {} plasma) at t = {:.4e} GeV^-1, H t = {:.4}",
+                 plasma::crossover_temperature(config.plasma.bag_root).unwrap_or(f64::NAN),
+                 config.plasma.model, t, h*t);
+    }
+    if let Some(step) = summary.first_flagged {
+        println!("WARNING: {} steps exceed the residual threshold, first at step {}",
+                 summary.flagged, step);
+    }
+    for history in &histories {
+        println!("{:>14}: age {:.4e} GeV^-1 ({:.4e} Gyr), conformal age {:.4e} GeV^-1 ({:.4e} Mpc)",
+                 history.label, history.age, observables::to_gyr(history.age),
+                 history.conformal_age, observables::to_mpc(history.conformal_age));
+    }
+    if let Some((step, column)) = summary.non_finite {
+        eprintln!("ERROR: {} is not finite at step {}; the run stopped there. Data in {}",
+                  column, step, config.output.display());
+        std::process::exit(1);
+    }
+    println!("Simulation complete. Data in {}, observables in {}, parameters in {}",
+             config.output.display(), config.observables_path().display(), sidecar.display());
 }
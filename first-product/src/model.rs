@@ -0,0 +1,523 @@
+// Unified running-vacuum model: one vacuum potential for the whole history, with
+// radiation and matter fluids fed by the decaying vacuum.
+//
+// The two era solutions of the appendices,
+//   radiation: H = HI/[1+(αφ)^4]^{1/2},  V = VI [1+(αφ)^4]^-2 [1+ν(αφ)^4]
+//   matter:    H = HF [1+(σφ)^-3]^{1/2}, V = VF [1+ν(σφ)^-3]
+// are both limits of the single running-vacuum potential
+//   V(H) = 3 MP² [ (1−ν) HF² + ν H² + (1−ν) H⁴/HI² ],
+// so the model evolves H itself and the era is whichever of V, ρ_r, ρ_m dominates.
+//
+// Field and fluids, with N = ln a (e-folds) and φ ∝ a^{1−ν} (φ̇ = (1−ν) φ H):
+//   Friedmann:     3 MP² H² = ρ_r + ρ_m + V(H)
+//   Raychaudhuri:  Ḣ = −(4/3 ρ_r + ρ_m)/(2 MP²)
+//   continuity:    ρ̇_i + 3(1+w_i) H ρ_i = Q_i,  Q = −V̇ = −V'(H) Ḣ ≥ 0
+// with the vacuum decay Q shared in proportion to (1+w_i) ρ_i. Per e-fold this gives
+//   d ln ρ_i/dN = −3 (1+w_i)(1−ν)(1 − 2 H²/HI²)
+// which reproduce ρ_k ∝ (αφ)^4 in the primeval de Sitter phase, ρ_r ∝ a^{−4(1−ν)} and
+// ρ_m ∝ a^{−3(1−ν)} afterwards. The radiation is the plasma of `plasma.rs`, with
+// w_r(T) = 1/3 except where species become non-relativistic (the QCD crossover, e±
+// annihilation, ...); the Raychaudhuri equation above is then Ḣ = −[(1+w_r) ρ_r + ρ_m]/(2 MP²).
+//
+// H is carried as z = ln(HI²/H² − 1) (z = ln (αφ)^4 in the radiation era), which stays
+// well-conditioned from H ≈ HI down to H ≈ HF. Integrating Ḣ from the fluid densities
+// alone carries a constraint-violating mode that grows like 1/H², so dz/dN is driven by
+// the Friedmann total ρ_f(H) split by the fluids' share, and the Friedmann residual of
+// the integrated densities measures accumulated truncation error.
+//
+// The densities are carried as logarithms and the initial state is built from
+// logarithms too, so that the 100+ orders of magnitude between HI and H0 never pass
+// through a product that can underflow or overflow.
+
+use crate::dual::Dual;
+use crate::integrator::{self, IntegrationError, IntegrationStats, IntegratorState, Method, OdeSystem,
+                        SecondOrderSystem};
+use crate::numeric::softplus;
+use crate::observables::Expansion;
+use crate::params::{CosmologyParams, ParamsError, PlasmaParams};
+use crate::plasma::{Plasma, RadiationState};
+
+/// Initial field value of the run.
+pub const PHI_START: f64 = 77.3147 * (1.0/137.0);
+
+/// Upper bound on the e-folds of a run; runs normally stop on H first.
+pub const MAX_EFOLDS: f64 = 1.0e3;
+
+// Derived parameters from the theoretical appendices:
+pub fn alpha_param(p: &CosmologyParams) -> f64 {
+    ln_alpha(p).exp()
+}
+
+pub fn ln_alpha(p: &CosmologyParams) -> f64 {
+    // According to the final expression from the appendices:
+    // α = ((1−ν)^3(1−2ν))^{1/4} * (HI/(2^{1/4} * MP * M))
+    let one_minus_nu = 1.0 - p.nu;
+    let one_minus_2nu = 1.0 - 2.0*p.nu;
+    0.25*(3.0*one_minus_nu.ln() + one_minus_2nu.ln() - 2.0_f64.ln()) + p.hi.ln() - p.m.ln() - p.mp.ln()
+}
+
+// For matter era (ω=0):
+// σ = (1−ν)*HF/(M^2)
+pub fn sigma_param(p: &CosmologyParams) -> f64 {
+    (1.0 - p.nu)*p.hf()/(p.m*p.m)
+}
+
+/// z at the start of the run, ln (αφ_start)^4.
+pub fn initial_z(p: &CosmologyParams) -> f64 {
+    4.0*(ln_alpha(p) + PHI_START.ln())
+}
+
+// Kinetic energy density in the radiation era, the initial state:
+pub fn ln_kinetic_radiation(p: &CosmologyParams, z: f64) -> f64 {
+    // ρ_k(φ) = (1−ν)*VI*(αφ)^4 / [ (1+(αφ)^4)^2 ], with (αφ)^4 = e^z
+    let ln_vi = 3.0_f64.ln() + 2.0*p.mp.ln() + 2.0*p.hi.ln();
+    (1.0 - p.nu).ln() + ln_vi + z - 2.0*softplus(z)
+}
+
+/// The closed-form era solutions of the appendices, in which H is a function of φ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Era {
+    /// H = HI/[1+(αφ)^4]^{1/2}
+    Radiation,
+    /// H = HF [1+(σφ)^-3]^{1/2}
+    Matter,
+}
+
+/// The field of an era solution at cosmic time t.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldPoint {
+    pub t: f64,
+    pub phi: f64,
+    pub phidot: f64,
+}
+
+/// φ̈ = a(φ) along an era solution, for the symplectic scheme; steps are fractions of the
+/// Hubble time 1/H(φ).
+struct FieldEquation<'a, C> {
+    model: &'a C,
+    era: Era,
+}
+
+impl<C: Cosmology> SecondOrderSystem for FieldEquation<'_, C> {
+    fn acceleration(&self, phi: f64) -> f64 {
+        self.model.phiddot_of_phi(self.era, phi)
+    }
+
+    fn time_scale(&self, phi: f64) -> f64 {
+        1.0/self.model.hubble_of_phi(self.era, phi)
+    }
+}
+
+/// The same field equation in e-folds of the field s = ln φ, state y = [ln φ̇, t]:
+/// d ln φ̇/ds = 1 + φ H'/H, dt/ds = φ/φ̇.
+///
+/// The second-order form φ̈ = a(φ) integrates to φ̇² = φ̇(φ)² + 2C and the era solution has
+/// C = 0. Once φ̇ decays any C left by round-off grows relative to φ̇ like e^{Δs}, so here
+/// φ̇ is carried along the solution and only truncation error moves it off.
+struct FieldEFolds<'a, C> {
+    model: &'a C,
+    era: Era,
+}
+
+impl<C: Cosmology> OdeSystem for FieldEFolds<'_, C> {
+    fn dim(&self) -> usize {
+        2
+    }
+
+    fn derivatives(&self, s: f64, y: &[f64], dyds: &mut [f64]) {
+        let phi = s.exp();
+        dyds[0] = 1.0 + phi*self.model.dhubble_dphi(self.era, phi)/self.model.hubble_of_phi(self.era, phi);
+        dyds[1] = phi/y[0].exp();
+    }
+}
+
+/// A background driven by a running vacuum: the vacuum energy V(H), the era solutions
+/// H(φ) with the densities and field derivatives along them, and the integrated history.
+pub trait Cosmology: OdeSystem + Sized {
+    fn params(&self) -> &CosmologyParams;
+
+    /// Vacuum energy density V(H).
+    fn potential(&self, h: f64) -> f64;
+
+    /// Fluid density 3 MP² H² − V(H) that the Friedmann equation leaves to radiation and matter.
+    fn fluid_density(&self, h: f64) -> f64;
+
+    /// V(H) on dual numbers; `potential` must agree with its value.
+    fn potential_dual(&self, h: Dual) -> Dual;
+
+    /// H(φ) of the era solution on dual numbers, from which `hubble_of_phi` and the `_ad`
+    /// derivatives follow.
+    fn hubble_of_phi_dual(&self, era: Era, phi: Dual) -> Dual;
+
+    /// dH/dφ of the era solution, derived by hand.
+    fn dhubble_dphi(&self, era: Era, phi: f64) -> f64;
+
+    /// State at the start of the run.
+    fn initial_state(&self) -> State;
+
+    /// H(φ) of the era solution.
+    fn hubble_of_phi(&self, era: Era, phi: f64) -> f64 {
+        self.hubble_of_phi_dual(era, Dual::constant(phi)).value
+    }
+
+    /// V(φ) = V(H(φ))
+    fn potential_of_phi(&self, era: Era, phi: f64) -> f64 {
+        self.potential(self.hubble_of_phi(era, phi))
+    }
+
+    /// ρ_k(φ), the fluid density along the era solution.
+    fn kinetic_of_phi(&self, era: Era, phi: f64) -> f64 {
+        self.fluid_density(self.hubble_of_phi(era, phi))
+    }
+
+    /// φ̇ = (1−ν) φ H(φ), eq.(56)
+    fn phidot_of_phi(&self, era: Era, phi: f64) -> f64 {
+        (1.0 - self.params().nu)*phi*self.hubble_of_phi(era, phi)
+    }
+
+    /// dφ̇/dφ = (1−ν) (H + φ dH/dφ)
+    fn dphidot_dphi(&self, era: Era, phi: f64) -> f64 {
+        (1.0 - self.params().nu)*(self.hubble_of_phi(era, phi) + phi*self.dhubble_dphi(era, phi))
+    }
+
+    /// φ̈ = dφ̇/dφ φ̇
+    fn phiddot_of_phi(&self, era: Era, phi: f64) -> f64 {
+        self.dphidot_dphi(era, phi)*self.phidot_of_phi(era, phi)
+    }
+
+    /// dH/dφ by forward-mode differentiation of H(φ); it checks `dhubble_dphi` and is not
+    /// used by the run.
+    fn dhubble_dphi_ad(&self, era: Era, phi: f64) -> f64 {
+        self.hubble_of_phi_dual(era, Dual::variable(phi)).derivative
+    }
+
+    /// dφ̇/dφ by forward-mode differentiation of φ̇ = (1−ν) φ H(φ).
+    fn dphidot_dphi_ad(&self, era: Era, phi: f64) -> f64 {
+        let phi = Dual::variable(phi);
+        ((1.0 - self.params().nu)*phi*self.hubble_of_phi_dual(era, phi)).derivative
+    }
+
+    /// dV/dφ by forward-mode differentiation of V(H(φ)).
+    fn dpotential_dphi_ad(&self, era: Era, phi: f64) -> f64 {
+        self.potential_dual(self.hubble_of_phi_dual(era, Dual::variable(phi))).derivative
+    }
+
+    /// Integrates the history in e-folds from `initial_state`, calling `observe` at the start
+    /// and after every accepted step until it returns false; returns the last state.
+    fn evolve<F>(&self, method: &Method, stats: &mut IntegrationStats, mut observe: F) -> Result<State, IntegrationError>
+    where
+        F: FnMut(&State) -> bool,
+    {
+        let start = self.initial_state();
+        let start = IntegratorState::start(method, start.n, &start.to_vec(), MAX_EFOLDS);
+        let end = self.evolve_from(method, start, |state, _| observe(state))?;
+        stats.merge(&end.stats);
+        Ok(State::from_vec(end.x, &end.y))
+    }
+
+    /// Evolves the field along the `era` solution from `start` until φ reaches `phi_end`:
+    /// Störmer-Verlet on φ̈ = a(φ) for `Method::Leapfrog`, the other methods on the e-fold
+    /// form. `observe` sees the start and every step until it returns false; returns the
+    /// last point.
+    fn evolve_field<F>(&self, era: Era, method: &Method, start: FieldPoint, phi_end: f64,
+                       stats: &mut IntegrationStats, mut observe: F) -> Result<FieldPoint, IntegrationError>
+    where
+        F: FnMut(&FieldPoint) -> bool,
+    {
+        if let Method::Leapfrog { fraction } = *method {
+            let system = FieldEquation { model: self, era };
+            let (t, phi, phidot) = integrator::leapfrog(&system, fraction, (start.t, start.phi, start.phidot),
+                                                        phi_end, stats,
+                |t, phi, phidot| observe(&FieldPoint { t, phi, phidot }))?;
+            return Ok(FieldPoint { t, phi, phidot });
+        }
+        let point = |s: f64, y: &[f64]| FieldPoint { t: y[1], phi: s.exp(), phidot: y[0].exp() };
+        let system = FieldEFolds { model: self, era };
+        let (s, y) = integrator::integrate(&system, method, start.phi.ln(), &[start.phidot.ln(), start.t],
+                                           phi_end.ln(), stats, |s, y| observe(&point(s, y)))?;
+        Ok(point(s, &y))
+    }
+
+    /// `evolve` from a point of an earlier integration, e.g. a checkpoint; `observe` also
+    /// sees the integrator state, step size and statistics included, after each step.
+    fn evolve_from<F>(&self, method: &Method, start: IntegratorState, mut observe: F)
+        -> Result<IntegratorState, IntegrationError>
+    where
+        F: FnMut(&State, &IntegratorState) -> bool,
+    {
+        integrator::integrate_from(self, method, start, MAX_EFOLDS, |s| observe(&State::from_vec(s.x, &s.y), s))
+    }
+}
+
+/// The component dominating the energy budget, which names the era.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Component {
+    Vacuum,
+    Radiation,
+    Matter,
+}
+
+impl Component {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Component::Vacuum => "vacuum",
+            Component::Radiation => "radiation",
+            Component::Matter => "matter",
+        }
+    }
+}
+
+/// Integrated state at e-fold N; `to_vec`/`from_vec` give the ODE layout [z, ln ρ_r, ln ρ_m, t].
+#[derive(Debug, Clone, Copy)]
+pub struct State {
+    pub n: f64,
+    pub z: f64,
+    pub ln_rho_r: f64,
+    pub ln_rho_m: f64,
+    pub t: f64,
+}
+
+impl State {
+    pub fn from_vec(n: f64, y: &[f64]) -> Self {
+        State { n, z: y[0], ln_rho_r: y[1], ln_rho_m: y[2], t: y[3] }
+    }
+
+    pub fn to_vec(self) -> Vec<f64> {
+        vec![self.z, self.ln_rho_r, self.ln_rho_m, self.t]
+    }
+
+    pub fn rho_r(&self) -> f64 {
+        self.ln_rho_r.exp()
+    }
+
+    pub fn rho_m(&self) -> f64 {
+        self.ln_rho_m.exp()
+    }
+}
+
+/// The unified running-vacuum model under one parameter set.
+#[derive(Debug, Clone)]
+pub struct RunningVacuum {
+    pub params: CosmologyParams,
+    pub plasma: Plasma,
+}
+
+impl RunningVacuum {
+    /// The model of `params` in the plasma of `plasma`; fails if the plasma table cannot be built.
+    pub fn new(params: &CosmologyParams, plasma: &PlasmaParams) -> Result<Self, ParamsError> {
+        Ok(RunningVacuum { params: *params, plasma: Plasma::new(plasma)? })
+    }
+
+    /// Temperature and equation of state of the radiation fluid.
+    pub fn radiation(&self, state: &State) -> RadiationState {
+        self.plasma.radiation(state.ln_rho_r)
+    }
+
+    /// H² = HI²/(1 + e^z)
+    pub fn hubble(&self, z: f64) -> f64 {
+        self.params.hi/(1.0 + z.exp()).sqrt()
+    }
+
+    /// V(H) = 3 MP² [ (1−ν) HF² + ν H² + (1−ν) H⁴/HI² ]
+    pub fn potential(&self, h: f64) -> f64 {
+        let p = &self.params;
+        3.0*p.mp.powi(2)*((1.0 - p.nu)*p.hf().powi(2) + p.nu*h*h + (1.0 - p.nu)*h.powi(4)/p.hi.powi(2))
+    }
+
+    /// ln(3 MP² H²) at z, with ln H² = ln HI² − ln(1 + e^z).
+    pub fn ln_critical_density(&self, z: f64) -> f64 {
+        let p = &self.params;
+        3.0_f64.ln() + 2.0*p.mp.ln() + 2.0*p.hi.ln() - softplus(z)
+    }
+
+    /// Ω_i = ρ_i/(3 MP² H²) of the radiation and matter fluids.
+    pub fn fluid_fractions(&self, state: &State) -> (f64, f64) {
+        let ln_critical = self.ln_critical_density(state.z);
+        ((state.ln_rho_r - ln_critical).exp(), (state.ln_rho_m - ln_critical).exp())
+    }
+
+    /// Ω_V = V/(3 MP² H²) = (1−ν) HF²/H² + ν + (1−ν) H²/HI²
+    pub fn vacuum_fraction(&self, z: f64) -> f64 {
+        let p = &self.params;
+        let r = (-softplus(z)).exp();
+        let f = (2.0*(p.hf()/p.hi).ln() + softplus(z)).exp();
+        (1.0 - p.nu)*f + p.nu + (1.0 - p.nu)*r
+    }
+
+    /// Fluid density required by the Friedmann equation at z, relative to 3 MP² (1−ν) H²:
+    /// ρ_f/(3 MP² (1−ν) H²) = 1 − H²/HI² − HF²/H², times (1 + e^{-z}) this is
+    /// 1 − (HF/HI)² (1 + e^z)(1 + e^{-z}), formed in logarithms.
+    pub fn fluid_share(&self, z: f64) -> f64 {
+        let p = &self.params;
+        1.0 - (2.0*(p.hf()/p.hi).ln() + softplus(z) + softplus(-z)).exp()
+    }
+
+    /// φ along the run, φ = φ_start e^{(1−ν) N}
+    pub fn phi(&self, n: f64) -> f64 {
+        PHI_START*((1.0 - self.params.nu)*n).exp()
+    }
+
+    /// φ̇ = (1−ν) φ H, eq.(56)
+    pub fn phidot(&self, state: &State) -> f64 {
+        (1.0 - self.params.nu)*self.phi(state.n)*self.hubble(state.z)
+    }
+
+    /// Primeval de Sitter start at φ_start: the radiation-era solution, with the matter
+    /// seed chosen so that ρ_m/ρ_r = (Ω_m0/Ω_r0) R a once the radiation era is established,
+    /// a⁴ = Ω_r0 H0² x/((1−ν) HI² R) being the radiation-era scale factor normalised today
+    /// and R = `Plasma::entropy_release` the growth of ρ_r a⁴ from there to today.
+    /// With x = (αφ)^4 the ratio evolves as ρ_m/ρ_r ∝ (1+x)^{1/2} x^{-1/4}, i.e. like
+    /// (x x_0)^{1/4} once x ≫ 1, which fixes the seed at x_0.
+    pub fn initial_state(&self) -> State {
+        let p = &self.params;
+        let z0 = initial_z(p);
+        let ln_rho_f = ln_kinetic_radiation(p, z0);
+        let ln_release = self.plasma.entropy_release().ln();
+        let ln_a_over_x = 0.25*(p.omega_r0.ln() + 2.0*p.h0.ln() - (1.0 - p.nu).ln() - 2.0*p.hi.ln() - ln_release);
+        let ln_matter_ratio = if p.omega_r0 > 0.0 {
+            (p.omega_m0/p.omega_r0).ln() + ln_release + ln_a_over_x + 0.5*softplus(z0) - 0.25*z0
+        } else {
+            f64::NEG_INFINITY
+        };
+        State {
+            n: 0.0,
+            z: z0,
+            ln_rho_r: ln_rho_f - softplus(ln_matter_ratio),
+            ln_rho_m: ln_rho_f + ln_matter_ratio - softplus(ln_matter_ratio),
+            t: 0.0,
+        }
+    }
+
+    /// Share of the fluids in radiation, ρ_r/(ρ_r + ρ_m) = 1/(1 + e^{ln ρ_m − ln ρ_r}).
+    pub fn radiation_share(&self, state: &State) -> f64 {
+        1.0/(1.0 + (state.ln_rho_m - state.ln_rho_r).exp())
+    }
+
+    /// dz/dN = −2 (1 + e^{-z}) d ln H/dN, with d ln H/dN = −[(1+w_r) ρ_r + ρ_m]/(2 MP² H²)
+    /// evaluated on the Friedmann fluid density:
+    ///   dz/dN = 3 (1−ν)(1 + w_r s_r) (1 + e^{-z}) ρ_f/(3 MP² (1−ν) H²)
+    pub fn dz_dn(&self, state: &State) -> f64 {
+        let s_r = self.radiation_share(state);
+        let w_r = self.radiation(state).w;
+        3.0*(1.0 - self.params.nu)*(1.0 + w_r*s_r)*self.fluid_share(state.z).max(0.0)
+    }
+
+    /// d ln ρ_r/dN and d ln ρ_m/dN from the continuity equations with vacuum decay.
+    pub fn density_slopes(&self, state: &State) -> (f64, f64) {
+        let nu = self.params.nu;
+        let ratio = 1.0/(1.0 + state.z.exp()); // H²/HI²
+        let dilution = -3.0*(1.0 - nu)*(1.0 - 2.0*ratio);
+        ((1.0 + self.radiation(state).w)*dilution, dilution)
+    }
+
+    /// ε = −Ḣ/H² = −d ln H/dN along the integrated trajectory, from dz/dN.
+    pub fn epsilon(&self, state: &State) -> f64 {
+        self.dz_dn(state)/(2.0*(1.0 + (-state.z).exp()))
+    }
+
+    /// η = d ln ε/dN along the trajectory. With ε = (3/2)(1−ν) g (1 + w_r s_r),
+    /// g = 1 − r − f, r = H²/HI², f = HF²/H² and s_r the radiation share:
+    ///   η = 2ε (r − f)/g + (s_r w_r' + w_r s_r')/(1 + w_r s_r)
+    /// with s_r' = 3 w_r (1−ν)(2r − 1) s_r (1 − s_r) and w_r' = dw_r/d ln ρ_r · d ln ρ_r/dN.
+    pub fn slow_roll_eta(&self, state: &State) -> f64 {
+        let p = &self.params;
+        let r = (-softplus(state.z)).exp();
+        let f = (2.0*(p.hf()/p.hi).ln() + softplus(state.z)).exp();
+        let g = 1.0/(1.0 + (-state.z).exp()) - f; // 1 − r without cancellation
+        let s_r = self.radiation_share(state);
+        let radiation = self.radiation(state);
+        let w_r = radiation.w;
+        let epsilon = 1.5*(1.0 - p.nu)*g*(1.0 + w_r*s_r);
+        let ds_r = 3.0*w_r*(1.0 - p.nu)*(2.0*r - 1.0)*s_r*(1.0 - s_r);
+        let dw_r = radiation.dw_dln_rho*self.density_slopes(state).0;
+        2.0*epsilon*(r - f)/g + (s_r*dw_r + w_r*ds_r)/(1.0 + w_r*s_r)
+    }
+
+    /// Which component dominates.
+    pub fn dominant(&self, state: &State) -> Component {
+        let ln_v = self.ln_critical_density(state.z) + self.vacuum_fraction(state.z).ln();
+        if ln_v >= state.ln_rho_r && ln_v >= state.ln_rho_m {
+            Component::Vacuum
+        } else if state.ln_rho_r >= state.ln_rho_m {
+            Component::Radiation
+        } else {
+            Component::Matter
+        }
+    }
+}
+
+impl Cosmology for RunningVacuum {
+    fn params(&self) -> &CosmologyParams {
+        &self.params
+    }
+
+    fn potential(&self, h: f64) -> f64 {
+        RunningVacuum::potential(self, h)
+    }
+
+    /// 3 MP² (1−ν) (H² − HF² − H⁴/HI²)
+    fn fluid_density(&self, h: f64) -> f64 {
+        let p = &self.params;
+        3.0*p.mp.powi(2)*(1.0 - p.nu)*(h*h - p.hf().powi(2) - h.powi(4)/p.hi.powi(2))
+    }
+
+    fn potential_dual(&self, h: Dual) -> Dual {
+        let p = &self.params;
+        3.0*p.mp.powi(2)*((1.0 - p.nu)*p.hf().powi(2) + p.nu*h*h + (1.0 - p.nu)*h.powi(4)/p.hi.powi(2))
+    }
+
+    /// Radiation: H = HI/(1 + e^z)^{1/2}, z = ln (αφ)^4, as `hubble`; matter: H = HF [1+(σφ)^-3]^{1/2}.
+    fn hubble_of_phi_dual(&self, era: Era, phi: Dual) -> Dual {
+        match era {
+            Era::Radiation => {
+                let z = 4.0*(ln_alpha(&self.params) + phi.ln());
+                self.params.hi/(1.0 + z.exp()).sqrt()
+            }
+            Era::Matter => self.params.hf()*(1.0 + (sigma_param(&self.params)*phi).powi(-3)).sqrt(),
+        }
+    }
+
+    /// Radiation: dH/dφ = −2 H x/(φ (1+x)), x = (αφ)^4; matter: dH/dφ = −(3/2) HF y/(φ (1+y)^{1/2}),
+    /// y = (σφ)^-3.
+    fn dhubble_dphi(&self, era: Era, phi: f64) -> f64 {
+        match era {
+            Era::Radiation => {
+                let z = 4.0*(ln_alpha(&self.params) + phi.ln());
+                -2.0*self.hubble(z)/(phi*(1.0 + (-z).exp()))
+            }
+            Era::Matter => {
+                let y = (sigma_param(&self.params)*phi).powi(-3);
+                -1.5*self.params.hf()*y/(phi*(1.0 + y).sqrt())
+            }
+        }
+    }
+
+    fn initial_state(&self) -> State {
+        RunningVacuum::initial_state(self)
+    }
+}
+
+impl OdeSystem for RunningVacuum {
+    fn dim(&self) -> usize {
+        4
+    }
+
+    fn derivatives(&self, n: f64, y: &[f64], dydn: &mut [f64]) {
+        let state = State::from_vec(n, y);
+        let (slope_r, slope_m) = self.density_slopes(&state);
+        dydn[0] = self.dz_dn(&state);
+        dydn[1] = slope_r;
+        dydn[2] = slope_m;
+        dydn[3] = 1.0/self.hubble(state.z);
+    }
+}
+
+impl Expansion for RunningVacuum {
+    fn expansion_rate(&self, _n: f64, y: &[f64]) -> f64 {
+        self.hubble(y[0])
+    }
+
+    fn cosmic_time(&self, y: &[f64]) -> f64 {
+        y[3]
+    }
+}
@@ -0,0 +1,120 @@
+// Numeric layer for the extreme scales of the run.
+//
+// A default run spans HI/HF ~ 1e51 with V ~ 1e74 GeV⁴ next to H0 ~ 1e-33 GeV. The model
+// already integrates rescaled variables (z = ln(HI²/H² − 1), ln ρ_r, ln ρ_m); what
+// remains dimensional is where the trajectory is compared with the model:
+//   closure       Ω_r + Ω_m + Ω_V − 1,  Ω_i = ρ_i/(3 MP² H²)
+//                 Ω_V = (1−ν) HF²/H² + ν + (1−ν) H²/HI²
+//   acceleration  Ḣ/H² + (3/2)((1+w_r) Ω_r + Ω_m)   (V cancels from ρ + p analytically)
+//   stop          ln(H/HF) = ln(HI/HF) − ½ ln(1 + e^z) ≤ ln(1 + tol)
+// The Ω_i are formed from ln ρ_i − ln(3 MP² H²), so nothing dimensional is ever
+// exponentiated. The sums are evaluated in f64 or, with the opt-in exact backend, as
+// exact rationals of the f64 terms, so that no rounding enters beyond that of the
+// integrated state itself.
+
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::model::{RunningVacuum, State};
+
+/// Arithmetic of the closure checks and the stopping rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Precision {
+    /// f64 on dimensionless ratios and logarithms.
+    Double,
+    /// Exact rationals (num-rational over num-bigint); slower, for verification.
+    Exact,
+}
+
+impl fmt::Display for Precision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Precision::Double => "double",
+            Precision::Exact => "exact",
+        })
+    }
+}
+
+/// ln(1 + e^x) without overflow.
+pub fn softplus(x: f64) -> f64 {
+    x.max(0.0) + (-x.abs()).exp().ln_1p()
+}
+
+/// Index of the first NaN or infinite value.
+pub fn first_non_finite(values: &[f64]) -> Option<usize> {
+    values.iter().position(|v| !v.is_finite())
+}
+
+/// An f64 as an exact rational, None if it is not finite.
+fn exact(x: f64) -> Option<BigRational> {
+    BigRational::from_float(x)
+}
+
+/// Exact H²/HI² = 1/(1 + e^z) and HF²/H² = (HF/HI)² (1 + e^z) at z.
+fn exact_ratios(model: &RunningVacuum, z: f64) -> Option<(BigRational, BigRational)> {
+    let p = &model.params;
+    let one_plus_ez = BigRational::one() + exact(z.exp())?;
+    let hf_over_hi2 = exact((2.0*(p.hf()/p.hi).ln()).exp())?;
+    Some((one_plus_ez.recip(), hf_over_hi2*one_plus_ez))
+}
+
+fn exact_friedmann(model: &RunningVacuum, state: &State) -> Option<f64> {
+    let (omega_r, omega_m) = model.fluid_fractions(state);
+    let (r, f) = exact_ratios(model, state.z)?;
+    let nu = exact(model.params.nu)?;
+    let one_minus_nu = BigRational::one() - &nu;
+    let omega_v = &one_minus_nu*f + nu + one_minus_nu*r;
+    (exact(omega_r)? + exact(omega_m)? + omega_v - BigRational::one()).to_f64()
+}
+
+fn exact_acceleration(model: &RunningVacuum, state: &State, epsilon: f64) -> Option<f64> {
+    let (omega_r, omega_m) = model.fluid_fractions(state);
+    let one_plus_w = BigRational::one() + exact(model.radiation(state).w)?;
+    let three_halves = BigRational::new(BigInt::from(3), BigInt::from(2));
+    (three_halves*(one_plus_w*exact(omega_r)? + exact(omega_m)?) - exact(epsilon)?).to_f64()
+}
+
+/// Friedmann closure of the integrated state, ρ/(3 MP² H²) − 1; NaN if the state is not finite.
+pub fn friedmann_residual(model: &RunningVacuum, state: &State, precision: Precision) -> f64 {
+    match precision {
+        Precision::Double => {
+            let (omega_r, omega_m) = model.fluid_fractions(state);
+            (omega_r + omega_m - 1.0) + model.vacuum_fraction(state.z)
+        }
+        Precision::Exact => exact_friedmann(model, state).unwrap_or(f64::NAN),
+    }
+}
+
+/// Acceleration residual (Ḣ + (ρ + p)/(2 MP²))/H² = −ε + (3/2)((1+w_r) Ω_r + Ω_m) for the
+/// integrated ε; NaN if not finite.
+pub fn acceleration_residual(model: &RunningVacuum, state: &State, epsilon: f64, precision: Precision) -> f64 {
+    match precision {
+        Precision::Double => {
+            let (omega_r, omega_m) = model.fluid_fractions(state);
+            1.5*((1.0 + model.radiation(state).w)*omega_r + omega_m) - epsilon
+        }
+        Precision::Exact => exact_acceleration(model, state, epsilon).unwrap_or(f64::NAN),
+    }
+}
+
+/// Whether H at z is still above HF (1 + tol), the end of the run; false once z is not finite.
+pub fn above_final(model: &RunningVacuum, z: f64, tol: f64, precision: Precision) -> bool {
+    let p = &model.params;
+    match precision {
+        Precision::Double => (p.hi/p.hf()).ln() - 0.5*softplus(z) > tol.ln_1p(),
+        Precision::Exact => {
+            // 1 > HF²/H² (1 + tol)²
+            let exact_above = || -> Option<bool> {
+                let (_, f) = exact_ratios(model, z)?;
+                let one_plus_tol = BigRational::one() + exact(tol)?;
+                Some(f*&one_plus_tol*&one_plus_tol < BigRational::one())
+            };
+            exact_above().unwrap_or(false)
+        }
+    }
+}
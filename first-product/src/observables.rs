@@ -0,0 +1,261 @@
+// Observable cosmology: scale factor, redshift, times and distances.
+//
+// Backgrounds are integrated in e-folds N = ln a with a = 1 today, "today" being where
+// H = H0. With c = 1 and spatially flat sections:
+//   redshift            1 + z = 1/a
+//   conformal time      η = ∫ dt/a,  dη/dN = 1/(a H)
+//   comoving distance   χ(z) = η0 − η(z)
+//   luminosity          d_L = (1+z) χ,  angular diameter  d_A = χ/(1+z)
+//   age                 t0, the cosmic time today
+// for the running-vacuum model (times counted from the start of the run, in the
+// primeval de Sitter phase) and a ΛCDM reference with the same H0, Ω_m0 and Ω_r0.
+
+use crate::integrator::{self, IntegrationStats, Method, OdeSystem};
+use crate::model::{RunningVacuum, MAX_EFOLDS};
+use crate::params::{CosmologyParams, ParamsError};
+
+// Natural units to astronomical ones: ħc in GeV m, ħ in GeV s.
+const HBAR_C: f64 = 1.973269804e-16;
+const HBAR: f64 = 6.582119569e-25;
+const METERS_PER_MPC: f64 = 3.085_677_581_491_367e22;
+const SECONDS_PER_GYR: f64 = 3.15576e16;
+const KELVIN_PER_GEV: f64 = 1.160_452e13;
+
+/// Bisection steps locating H = H0 in the running-vacuum history.
+const BISECTIONS: usize = 60;
+
+/// A length in GeV^-1, in Mpc.
+pub fn to_mpc(length: f64) -> f64 {
+    length*HBAR_C/METERS_PER_MPC
+}
+
+/// A time in GeV^-1, in Gyr.
+pub fn to_gyr(time: f64) -> f64 {
+    time*HBAR/SECONDS_PER_GYR
+}
+
+/// A temperature in GeV, in K.
+pub fn to_kelvin(temperature: f64) -> f64 {
+    temperature*KELVIN_PER_GEV
+}
+
+/// A background integrated in e-folds whose state carries the cosmic time.
+pub trait Expansion: OdeSystem {
+    fn expansion_rate(&self, n: f64, y: &[f64]) -> f64;
+    fn cosmic_time(&self, y: &[f64]) -> f64;
+}
+
+/// The background extended by the conformal time, y = [background.., η],
+/// with dη/dN = e^{N_today − N}/H.
+struct WithConformalTime<'a, B> {
+    background: &'a B,
+    n_today: f64,
+}
+
+impl<B: Expansion> OdeSystem for WithConformalTime<'_, B> {
+    fn dim(&self) -> usize {
+        self.background.dim() + 1
+    }
+
+    fn derivatives(&self, n: f64, y: &[f64], dydn: &mut [f64]) {
+        let inner = self.background.dim();
+        self.background.derivatives(n, &y[..inner], &mut dydn[..inner]);
+        dydn[inner] = (self.n_today - n).exp()/self.background.expansion_rate(n, &y[..inner]);
+    }
+}
+
+/// Times and distances at one redshift, in GeV^-1.
+#[derive(Debug, Clone, Copy)]
+pub struct ObservablePoint {
+    pub z: f64,
+    pub a: f64,
+    pub t: f64,
+    pub conformal_time: f64,
+    pub hubble: f64,
+    pub comoving_distance: f64,
+    pub luminosity_distance: f64,
+    pub angular_diameter_distance: f64,
+}
+
+impl ObservablePoint {
+    /// μ = 5 log10(d_L/10 pc)
+    pub fn distance_modulus(&self) -> f64 {
+        5.0*to_mpc(self.luminosity_distance).log10() + 25.0
+    }
+}
+
+/// Observables of one model on a redshift grid.
+#[derive(Debug, Clone)]
+pub struct History {
+    pub label: &'static str,
+    pub age: f64,
+    pub conformal_age: f64,
+    pub points: Vec<ObservablePoint>,
+    pub stats: IntegrationStats,
+}
+
+impl History {
+    pub const CSV_HEADER: &'static str = "model,z,a,t,conformal_time,H,comoving_distance,\
+luminosity_distance,angular_diameter_distance,distance_modulus";
+
+    pub fn csv_rows(&self) -> Vec<String> {
+        self.points.iter().map(|p| format!("{},{},{},{},{},{},{},{},{},{}",
+            self.label, p.z, p.a, p.t, p.conformal_time, p.hubble, p.comoving_distance,
+            p.luminosity_distance, p.angular_diameter_distance, p.distance_modulus())).collect()
+    }
+}
+
+/// Evenly spaced redshifts from 0 to z_max.
+pub fn redshift_grid(z_max: f64, points: usize) -> Vec<f64> {
+    if points < 2 {
+        return vec![0.0];
+    }
+    (0..points).map(|i| z_max*i as f64/(points - 1) as f64).collect()
+}
+
+/// Integrates `background` from (n0, y0, η0) through N_i = N_today − ln(1+z_i) and on to
+/// today, then measures the distances back from today.
+fn sample<B: Expansion>(background: &B, method: &Method, n0: f64, y0: &[f64], eta0: f64,
+                        n_today: f64, redshifts: &[f64]) -> Result<History, ParamsError> {
+    let system = WithConformalTime { background, n_today };
+    let inner = background.dim();
+    let mut stats = IntegrationStats::default();
+
+    // Visit the nodes in increasing N, i.e. decreasing z, with today last.
+    let mut order: Vec<usize> = (0..redshifts.len()).collect();
+    order.sort_by(|&i, &j| redshifts[j].total_cmp(&redshifts[i]));
+    let mut y: Vec<f64> = y0.iter().copied().chain([eta0]).collect();
+    let mut n = n0;
+    let mut at_nodes = vec![(0.0, 0.0, 0.0); redshifts.len()];
+    for &i in &order {
+        let n_node = n_today - redshifts[i].ln_1p();
+        if n_node < n0 {
+            return Err(ParamsError::Invalid(format!(
+                "z = {} lies before the start of the integration", redshifts[i])));
+        }
+        y = integrator::integrate(&system, method, n, &y, n_node, &mut stats, |_, _| true)?.1;
+        n = n_node;
+        at_nodes[i] = (background.cosmic_time(&y[..inner]), y[inner], background.expansion_rate(n, &y[..inner]));
+    }
+    y = integrator::integrate(&system, method, n, &y, n_today, &mut stats, |_, _| true)?.1;
+    let (age, conformal_age) = (background.cosmic_time(&y[..inner]), y[inner]);
+
+    let points = redshifts.iter().zip(at_nodes).map(|(&z, (t, eta, h))| {
+        let chi = conformal_age - eta;
+        ObservablePoint {
+            z,
+            a: 1.0/(1.0 + z),
+            t,
+            conformal_time: eta,
+            hubble: h,
+            comoving_distance: chi,
+            luminosity_distance: (1.0 + z)*chi,
+            angular_diameter_distance: chi/(1.0 + z),
+        }
+    }).collect();
+    Ok(History { label: "", age, conformal_age, points, stats })
+}
+
+/// Observables of the running-vacuum model. Today is located by integrating until H
+/// drops below H0 and bisecting the last step.
+pub fn running_vacuum(model: &RunningVacuum, method: &Method, redshifts: &[f64]) -> Result<History, ParamsError> {
+    let h0 = model.params.h0;
+    let start = model.initial_state();
+    let mut stats = IntegrationStats::default();
+
+    let mut above = (start.n, start.to_vec());
+    let (mut n_hi, y_hi) = integrator::integrate(model, method, start.n, &start.to_vec(), MAX_EFOLDS, &mut stats,
+        |n, y| {
+            let before = model.hubble(y[0]) > h0;
+            if before {
+                above = (n, y.to_vec());
+            }
+            before
+        })?;
+    if model.hubble(y_hi[0]) > h0 {
+        return Err(ParamsError::Invalid(format!("H never reaches H0 = {:e} within {} e-folds", h0, MAX_EFOLDS)));
+    }
+
+    let (mut n_lo, mut y_lo) = above;
+    for _ in 0..BISECTIONS {
+        let mid = 0.5*(n_lo + n_hi);
+        let (_, y) = integrator::integrate(model, method, n_lo, &y_lo, mid, &mut stats, |_, _| true)?;
+        if model.hubble(y[0]) > h0 {
+            (n_lo, y_lo) = (mid, y);
+        } else {
+            n_hi = mid;
+        }
+    }
+
+    let mut history = sample(model, method, start.n, &start.to_vec(), 0.0, 0.5*(n_lo + n_hi), redshifts)?;
+    history.label = "running_vacuum";
+    history.stats.merge(&stats);
+    Ok(history)
+}
+
+/// Flat ΛCDM reference: H(a) = H0 [Ω_m a^-3 + Ω_r a^-4 + Ω_Λ]^{1/2}, Ω_Λ = 1 − Ω_m − Ω_r.
+#[derive(Debug, Clone, Copy)]
+pub struct LambdaCdm {
+    pub h0: f64,
+    pub omega_m0: f64,
+    pub omega_r0: f64,
+}
+
+impl LambdaCdm {
+    pub fn new(params: &CosmologyParams) -> Self {
+        LambdaCdm { h0: params.h0, omega_m0: params.omega_m0, omega_r0: params.omega_r0 }
+    }
+
+    pub fn omega_lambda(&self) -> f64 {
+        1.0 - self.omega_m0 - self.omega_r0
+    }
+
+    pub fn hubble(&self, a: f64) -> f64 {
+        self.h0*(self.omega_m0/a.powi(3) + self.omega_r0/a.powi(4) + self.omega_lambda()).sqrt()
+    }
+
+    /// (t, η) at a ≪ 1 from the exact radiation + matter solution, a_eq = Ω_r/Ω_m, x = a/a_eq:
+    ///   t = a² (3 − x)/(3 H0 Ω_r^{1/2} [1 + (1 − x/2)(1+x)^{1/2}])   (t = 2 a^{3/2}/(3 H0 Ω_m^{1/2}) if Ω_r = 0)
+    ///   η = 2 a/(H0 Ω_m^{1/2} [(a + a_eq)^{1/2} + a_eq^{1/2}])
+    fn early(&self, a: f64) -> (f64, f64) {
+        let a_eq = self.omega_r0/self.omega_m0;
+        let t = if self.omega_r0 > 0.0 {
+            let x = a/a_eq;
+            a*a*(3.0 - x)/(3.0*self.h0*self.omega_r0.sqrt()*(1.0 + (1.0 - 0.5*x)*(1.0 + x).sqrt()))
+        } else {
+            2.0*a.powf(1.5)/(3.0*self.h0*self.omega_m0.sqrt())
+        };
+        let eta = 2.0*a/(self.h0*self.omega_m0.sqrt()*((a + a_eq).sqrt() + a_eq.sqrt()));
+        (t, eta)
+    }
+}
+
+impl OdeSystem for LambdaCdm {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, n: f64, _y: &[f64], dydn: &mut [f64]) {
+        dydn[0] = 1.0/self.hubble(n.exp());
+    }
+}
+
+impl Expansion for LambdaCdm {
+    fn expansion_rate(&self, n: f64, _y: &[f64]) -> f64 {
+        self.hubble(n.exp())
+    }
+
+    fn cosmic_time(&self, y: &[f64]) -> f64 {
+        y[0]
+    }
+}
+
+/// Observables of the ΛCDM reference, started deep in the radiation/matter era where
+/// Λ is negligible.
+pub fn lambda_cdm(reference: &LambdaCdm, method: &Method, redshifts: &[f64]) -> Result<History, ParamsError> {
+    let z_max = redshifts.iter().copied().fold(0.0, f64::max);
+    let a_start = 1e-8_f64.min(0.1/(1.0 + z_max));
+    let (t, eta) = reference.early(a_start);
+    let history = sample(reference, method, a_start.ln(), &[t], eta, 0.0, redshifts)?;
+    Ok(History { label: "lcdm", ..history })
+}
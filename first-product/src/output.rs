@@ -0,0 +1,387 @@
+// Trajectory writers for the run, chosen at runtime.
+//
+//   csv     one header line and one comma-separated line per row, as before
+//   jsonl   one JSON object per row, keyed by column name; NaN and ±Inf become null
+//   binary  columnar blocks after a metadata header:
+//             b"FPCOLS1\n", u32 length, TOML metadata (columns with their kinds, the run
+//             parameters), then blocks of up to BLOCK_ROWS rows, each
+//             u32 row count, and column by column: f64 or u64 values, or for text
+//             columns a u8 dictionary size, the entries as u8 length + UTF-8, and one
+//             u8 code per row
+// All integers and floats are little-endian. Every writer can report the length of what
+// it has written so far after flushing it (`sync`), the offset a restarted run truncates
+// the file to before it appends; the binary writer closes its current block there.
+//
+// Decimation is the recorder's business: a writer writes every row it is given.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::params::RunConfig;
+
+const MAGIC: &[u8; 8] = b"FPCOLS1\n";
+
+/// Rows per block of the binary format.
+const BLOCK_ROWS: usize = 4096;
+
+/// File format of the trajectory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    Csv,
+    Jsonl,
+    Binary,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Jsonl => "jsonl",
+            OutputFormat::Binary => "binary",
+        })
+    }
+}
+
+/// Type of a column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Float,
+    Integer,
+    Text,
+}
+
+/// A named, typed column.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Column {
+    pub name: String,
+    pub kind: Kind,
+}
+
+/// One cell of a row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value<'a> {
+    Float(f64),
+    Integer(u64),
+    Text(&'a str),
+}
+
+impl fmt::Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Integer(v) => write!(f, "{}", v),
+            Value::Text(v) => f.write_str(v),
+        }
+    }
+}
+
+/// Header of the binary format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    pub columns: Vec<Column>,
+    pub run: RunConfig,
+}
+
+/// A streaming sink for the rows of a run.
+pub trait RecordWriter {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()>;
+
+    /// Flushes everything written so far and returns its length in bytes.
+    fn sync(&mut self) -> io::Result<u64>;
+}
+
+/// Opens the writer of `format` at `path`. A fresh file starts with the header; with
+/// `resume_at` the file is truncated to that length and the rows are appended.
+pub fn open(path: &Path, format: OutputFormat, metadata: &Metadata, resume_at: Option<u64>)
+            -> io::Result<Box<dyn RecordWriter>> {
+    let (file, fresh) = match resume_at {
+        Some(length) => {
+            let mut file = OpenOptions::new().write(true).open(path)?;
+            if file.metadata()?.len() < length {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, format!(
+                    "{} is shorter than the {} bytes recorded in the checkpoint", path.display(), length)));
+            }
+            file.set_len(length)?;
+            file.seek(SeekFrom::End(0))?;
+            (file, false)
+        }
+        None => (File::create(path)?, true),
+    };
+    let mut out = BufWriter::new(file);
+    let columns = metadata.columns.clone();
+    Ok(match format {
+        OutputFormat::Csv => {
+            if fresh {
+                let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+                writeln!(out, "{}", names.join(","))?;
+            }
+            Box::new(CsvWriter { out })
+        }
+        OutputFormat::Jsonl => Box::new(JsonLinesWriter { out, columns }),
+        OutputFormat::Binary => {
+            if fresh {
+                let header = toml::to_string(metadata).expect("metadata always serializes");
+                out.write_all(MAGIC)?;
+                out.write_all(&(header.len() as u32).to_le_bytes())?;
+                out.write_all(header.as_bytes())?;
+            }
+            Box::new(BinaryWriter { out, columns, block: Vec::new() })
+        }
+    })
+}
+
+struct CsvWriter {
+    out: BufWriter<File>,
+}
+
+impl RecordWriter for CsvWriter {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let cells: Vec<String> = row.iter().map(|v| v.to_string()).collect();
+        writeln!(self.out, "{}", cells.join(","))
+    }
+
+    fn sync(&mut self) -> io::Result<u64> {
+        self.out.flush()?;
+        self.out.get_mut().stream_position()
+    }
+}
+
+struct JsonLinesWriter {
+    out: BufWriter<File>,
+    columns: Vec<Column>,
+}
+
+/// A JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl RecordWriter for JsonLinesWriter {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let fields: Vec<String> = self.columns.iter().zip(row).map(|(column, value)| {
+            let value = match value {
+                Value::Float(v) if !v.is_finite() => "null".to_string(),
+                Value::Float(v) => format!("{:e}", v),
+                Value::Integer(v) => v.to_string(),
+                Value::Text(v) => json_string(v),
+            };
+            format!("{}:{}", json_string(&column.name), value)
+        }).collect();
+        writeln!(self.out, "{{{}}}", fields.join(","))
+    }
+
+    fn sync(&mut self) -> io::Result<u64> {
+        self.out.flush()?;
+        self.out.get_mut().stream_position()
+    }
+}
+
+/// A row held back until its block is written.
+#[derive(Debug, Clone, PartialEq)]
+enum Owned {
+    Float(f64),
+    Integer(u64),
+    Text(String),
+}
+
+struct BinaryWriter {
+    out: BufWriter<File>,
+    columns: Vec<Column>,
+    block: Vec<Vec<Owned>>,
+}
+
+impl BinaryWriter {
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.block.is_empty() {
+            return Ok(());
+        }
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.block.len() as u32).to_le_bytes());
+        for (k, column) in self.columns.iter().enumerate() {
+            match column.kind {
+                Kind::Float | Kind::Integer => {
+                    for row in &self.block {
+                        match &row[k] {
+                            Owned::Float(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+                            Owned::Integer(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+                            Owned::Text(_) => unreachable!("text in a numeric column"),
+                        }
+                    }
+                }
+                Kind::Text => {
+                    let mut dictionary: Vec<&str> = Vec::new();
+                    let mut codes = Vec::with_capacity(self.block.len());
+                    for row in &self.block {
+                        let Owned::Text(text) = &row[k] else { unreachable!("number in a text column") };
+                        let code = match dictionary.iter().position(|d| d == text) {
+                            Some(code) => code,
+                            None => {
+                                dictionary.push(text);
+                                dictionary.len() - 1
+                            }
+                        };
+                        codes.push(u8::try_from(code).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput,
+                            format!("more than 256 distinct values in column {}", column.name)))?);
+                    }
+                    bytes.push(dictionary.len() as u8);
+                    for entry in dictionary {
+                        bytes.push(u8::try_from(entry.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput,
+                            format!("'{}' is longer than 255 bytes", entry)))?);
+                        bytes.extend_from_slice(entry.as_bytes());
+                    }
+                    bytes.extend_from_slice(&codes);
+                }
+            }
+        }
+        self.block.clear();
+        self.out.write_all(&bytes)
+    }
+}
+
+impl RecordWriter for BinaryWriter {
+    fn write_row(&mut self, row: &[Value]) -> io::Result<()> {
+        let owned = self.columns.iter().zip(row).map(|(column, value)| match (column.kind, value) {
+            (Kind::Float, Value::Float(v)) => Ok(Owned::Float(*v)),
+            (Kind::Integer, Value::Integer(v)) => Ok(Owned::Integer(*v)),
+            (Kind::Text, Value::Text(v)) => Ok(Owned::Text(v.to_string())),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                    format!("value {} does not fit column {}", value, column.name))),
+        }).collect::<io::Result<Vec<Owned>>>()?;
+        self.block.push(owned);
+        if self.block.len() == BLOCK_ROWS {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<u64> {
+        self.write_block()?;
+        self.out.flush()?;
+        self.out.get_mut().stream_position()
+    }
+}
+
+impl Drop for BinaryWriter {
+    fn drop(&mut self) {
+        let _ = self.write_block();
+    }
+}
+
+/// A column read back from a binary file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnData {
+    Float(Vec<f64>),
+    Integer(Vec<u64>),
+    Text(Vec<String>),
+}
+
+pub(crate) fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Little-endian reader over the bytes of a file.
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    at: usize,
+    path: &'a Path,
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(bytes: &'a [u8], path: &'a Path) -> Self {
+        ByteReader { bytes, at: 0, path }
+    }
+
+    pub(crate) fn at_end(&self) -> bool {
+        self.at == self.bytes.len()
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        let slice = self.bytes.get(self.at..self.at + n)
+            .ok_or_else(|| invalid(format!("{} is truncated", self.path.display())))?;
+        self.at += n;
+        Ok(slice)
+    }
+
+    pub(crate) fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn f64(&mut self) -> io::Result<f64> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// A u32 length followed by that many bytes of UTF-8.
+    pub(crate) fn text(&mut self) -> io::Result<&'a str> {
+        let length = self.u32()? as usize;
+        std::str::from_utf8(self.take(length)?).map_err(|e| invalid(e.to_string()))
+    }
+}
+
+/// Reads a binary trajectory: its metadata and the columns in header order.
+pub fn read_binary(path: &Path) -> io::Result<(Metadata, Vec<ColumnData>)> {
+    let mut bytes = Vec::new();
+    File::open(path)?.read_to_end(&mut bytes)?;
+    let mut reader = ByteReader::new(&bytes, path);
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid(format!("{} is not a first-product binary trajectory", path.display())));
+    }
+    let metadata: Metadata = toml::from_str(reader.text()?).map_err(|e| invalid(e.to_string()))?;
+    let mut columns: Vec<ColumnData> = metadata.columns.iter().map(|c| match c.kind {
+        Kind::Float => ColumnData::Float(Vec::new()),
+        Kind::Integer => ColumnData::Integer(Vec::new()),
+        Kind::Text => ColumnData::Text(Vec::new()),
+    }).collect();
+
+    while !reader.at_end() {
+        let rows = reader.u32()? as usize;
+        for column in columns.iter_mut() {
+            match column {
+                ColumnData::Float(values) => for _ in 0..rows {
+                    values.push(reader.f64()?);
+                },
+                ColumnData::Integer(values) => for _ in 0..rows {
+                    values.push(reader.u64()?);
+                },
+                ColumnData::Text(values) => {
+                    let entries = reader.u8()? as usize;
+                    let mut dictionary = Vec::with_capacity(entries);
+                    for _ in 0..entries {
+                        let length = reader.u8()? as usize;
+                        let entry = std::str::from_utf8(reader.take(length)?).map_err(|e| invalid(e.to_string()))?;
+                        dictionary.push(entry.to_string());
+                    }
+                    for &code in reader.take(rows)? {
+                        values.push(dictionary.get(code as usize).cloned()
+                            .ok_or_else(|| invalid(format!("code {} outside the dictionary", code)))?);
+                    }
+                }
+            }
+        }
+    }
+    Ok((metadata, columns))
+}
@@ -0,0 +1,453 @@
+// Run parameters: loaded from a TOML file and/or command-line flags, validated,
+// and written next to the output as a sidecar so every CSV records how it was made.
+//
+// Units: c = ħ = k_B = 1, M_P = (8πG)^(-1/2), scales in GeV.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::integrator::{IntegrationError, Method, Tolerances};
+use crate::model;
+use crate::numeric::{self, Precision};
+use crate::observables;
+use crate::output::OutputFormat;
+use crate::plasma::Plasma;
+
+/// Physical parameters of the running-vacuum model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CosmologyParams {
+    pub mp: f64,       // Reduced Planck mass in GeV
+    pub m: f64,        // Example scale, adjustable to tested scenario
+    pub hi: f64,       // Primeval scale at or below reduced Planck mass
+    pub h0: f64,       // Present-day Hubble scale (~ in GeV)
+    pub nu: f64,       // Example small deviation parameter from rigid vacuum
+    pub omega_m0: f64,
+    pub omega_r0: f64,
+}
+
+impl Default for CosmologyParams {
+    fn default() -> Self {
+        CosmologyParams {
+            mp: 2.435e18,
+            m: 1.0e9,
+            hi: 2.435e18,
+            h0: 1.0e-33,
+            nu: 0.001,
+            omega_m0: 0.3,
+            omega_r0: 1e-5,
+        }
+    }
+}
+
+impl CosmologyParams {
+    /// Final de Sitter scale HF = H0 ((Ω_Λ0 − ν)/(1−ν))^{1/2}, Ω_Λ0 = 1 − Ω_m0 − Ω_r0,
+    /// so that the vacuum today is ρ_Λ(H0) = 3 MP² [(1−ν) HF² + ν H0²] = 3 MP² H0² Ω_Λ0.
+    pub fn hf(&self) -> f64 {
+        let omega_lambda0 = 1.0 - self.omega_m0 - self.omega_r0;
+        self.h0*(((omega_lambda0 - self.nu)/(1.0 - self.nu)).sqrt())
+    }
+
+    /// Checks the ranges the model formulas rely on.
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        for (name, value) in [("mp", self.mp), ("m", self.m), ("hi", self.hi), ("h0", self.h0)] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(ParamsError::Invalid(format!("{} = {} must be positive and finite", name, value)));
+            }
+        }
+        if self.hi > self.mp {
+            return Err(ParamsError::Invalid(format!(
+                "hi = {} must not exceed the reduced Planck mass mp = {}", self.hi, self.mp)));
+        }
+        // (1−2ν)^{1/4} in α must be real and 1−ν in HF non-zero
+        if !(0.0..0.5).contains(&self.nu) {
+            return Err(ParamsError::Invalid(format!("nu = {} must satisfy 0 <= nu < 1/2", self.nu)));
+        }
+        if !(self.omega_m0 > 0.0 && self.omega_m0 <= 1.0) {
+            return Err(ParamsError::Invalid(format!("omega_m0 = {} must lie in (0, 1]", self.omega_m0)));
+        }
+        if !(self.omega_r0 >= 0.0 && self.omega_m0 + self.omega_r0 <= 1.0) {
+            return Err(ParamsError::Invalid(format!(
+                "omega_r0 = {} must be >= 0 with omega_m0 + omega_r0 <= 1", self.omega_r0)));
+        }
+        // HF² > 0 needs Ω_Λ0 = 1 − Ω_m0 − Ω_r0 > ν
+        if self.omega_m0 + self.omega_r0 + self.nu >= 1.0 {
+            return Err(ParamsError::Invalid(format!(
+                "omega_m0 + omega_r0 + nu = {} must be below 1 for a positive HF",
+                self.omega_m0 + self.omega_r0 + self.nu)));
+        }
+        // The start needs fluid above the final de Sitter floor, ρ_f(z0) > 0:
+        // 2 + e^z0 + e^-z0 < HI²/HF², compared in logarithms
+        let z0 = model::initial_z(self);
+        if 2.0*numeric::softplus(z0) - z0 >= 2.0*(self.hi/self.hf()).ln() {
+            return Err(ParamsError::Invalid(format!(
+                "the start (alpha phi)^4 = e^{:.1} leaves no fluid above the final de Sitter scale; \
+                 adjust m, hi or h0", z0)));
+        }
+        Ok(())
+    }
+}
+
+/// Integrator selection, see `integrator::Method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodName {
+    Rk45,
+    Rk4,
+}
+
+/// Numerical settings of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IntegrationParams {
+    pub method: MethodName,
+    pub rtol: f64,
+    pub atol: f64,
+    pub initial_step: f64,         // First RK45 step, in e-folds
+    pub rk4_step: f64,             // Fixed RK4 step, in e-folds
+    pub h_final_tolerance: f64,    // Stop once H/HF - 1 falls below this
+    pub residual_threshold: f64,   // Diagnostics residuals above this are flagged
+    pub precision: Precision,      // Arithmetic of the closure checks and the stop
+}
+
+impl Default for IntegrationParams {
+    fn default() -> Self {
+        IntegrationParams {
+            method: MethodName::Rk45,
+            rtol: 1e-10,
+            atol: 0.0,
+            initial_step: 1e-3,
+            rk4_step: 1e-2,
+            h_final_tolerance: 1e-6,
+            residual_threshold: 1e-6,
+            precision: Precision::Double,
+        }
+    }
+}
+
+impl IntegrationParams {
+    pub fn method(&self) -> Method {
+        match self.method {
+            MethodName::Rk45 => Method::DormandPrince45 {
+                tolerances: Tolerances { rtol: self.rtol, atol: self.atol },
+                initial_step: self.initial_step,
+            },
+            MethodName::Rk4 => Method::Rk4 { step: self.rk4_step },
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        let positive = [
+            ("rtol", self.rtol),
+            ("initial_step", self.initial_step),
+            ("rk4_step", self.rk4_step),
+            ("h_final_tolerance", self.h_final_tolerance),
+            ("residual_threshold", self.residual_threshold),
+        ];
+        for (name, value) in positive {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(ParamsError::Invalid(format!("{} = {} must be positive and finite", name, value)));
+            }
+        }
+        if self.atol.is_nan() || self.atol < 0.0 {
+            return Err(ParamsError::Invalid(format!("atol = {} must be >= 0", self.atol)));
+        }
+        Ok(())
+    }
+}
+
+/// Equation of state of the radiation fluid, see `plasma.rs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlasmaModel {
+    /// w = 1/3 throughout; T and g* are still reported from the plasma table.
+    Conformal,
+    /// Standard Model plasma with a bag-model QGP and its crossover to a hadron gas.
+    Qgp,
+}
+
+impl fmt::Display for PlasmaModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PlasmaModel::Conformal => "conformal",
+            PlasmaModel::Qgp => "qgp",
+        })
+    }
+}
+
+/// The primordial plasma feeding the radiation fluid.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlasmaParams {
+    pub model: PlasmaModel,
+    pub bag_root: f64,        // Bag constant B^{1/4} in GeV, fixes the crossover temperature
+    pub crossover_width: f64, // Half-width of the QGP-hadron crossover in GeV
+}
+
+impl Default for PlasmaParams {
+    fn default() -> Self {
+        PlasmaParams { model: PlasmaModel::Qgp, bag_root: 0.22, crossover_width: 0.03 }
+    }
+}
+
+impl PlasmaParams {
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        for (name, value) in [("bag_root", self.bag_root), ("crossover_width", self.crossover_width)] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(ParamsError::Invalid(format!("{} = {} must be positive and finite", name, value)));
+            }
+        }
+        Plasma::new(self).map(|_| ())
+    }
+}
+
+/// Redshift grid of the observables table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObservableParams {
+    pub z_max: f64,
+    pub z_points: usize,
+}
+
+impl Default for ObservableParams {
+    fn default() -> Self {
+        ObservableParams { z_max: 3.0, z_points: 31 }
+    }
+}
+
+impl ObservableParams {
+    pub fn redshifts(&self) -> Vec<f64> {
+        observables::redshift_grid(self.z_max, self.z_points)
+    }
+
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        if !(self.z_max >= 0.0 && self.z_max.is_finite()) {
+            return Err(ParamsError::Invalid(format!("z_max = {} must be >= 0 and finite", self.z_max)));
+        }
+        if self.z_points == 0 {
+            return Err(ParamsError::Invalid("z_points must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Trajectory format, decimation and checkpoints of a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputParams {
+    pub format: OutputFormat,
+    pub every: usize,                 // Write every n-th step
+    pub min_dn: f64,                  // ... and only once N has advanced this far since the last row
+    pub checkpoint: Option<PathBuf>,  // Restart file, rewritten every checkpoint_every steps
+    pub checkpoint_every: usize,
+}
+
+impl Default for OutputParams {
+    fn default() -> Self {
+        OutputParams { format: OutputFormat::Csv, every: 1, min_dn: 0.0, checkpoint: None, checkpoint_every: 1000 }
+    }
+}
+
+impl OutputParams {
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        if self.every == 0 || self.checkpoint_every == 0 {
+            return Err(ParamsError::Invalid("every and checkpoint_every must be at least 1".to_string()));
+        }
+        if !(self.min_dn >= 0.0 && self.min_dn.is_finite()) {
+            return Err(ParamsError::Invalid(format!("min_dn = {} must be >= 0 and finite", self.min_dn)));
+        }
+        Ok(())
+    }
+}
+
+/// Everything a run needs: model, numerics and where to write.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    pub output: PathBuf,
+    pub cosmology: CosmologyParams,
+    pub integration: IntegrationParams,
+    pub plasma: PlasmaParams,
+    pub observables: ObservableParams,
+    pub trajectory: OutputParams,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        RunConfig {
+            output: PathBuf::from("qgp_data.csv"),
+            cosmology: CosmologyParams::default(),
+            integration: IntegrationParams::default(),
+            plasma: PlasmaParams::default(),
+            observables: ObservableParams::default(),
+            trajectory: OutputParams::default(),
+        }
+    }
+}
+
+/// Error raised while reading or validating parameters.
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(PathBuf, std::io::Error),
+    Toml(toml::de::Error),
+    Usage(String),
+    Invalid(String),
+    Integration(IntegrationError),
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            ParamsError::Toml(e) => write!(f, "invalid parameter file: {}", e),
+            ParamsError::Usage(msg) => write!(f, "{}", msg),
+            ParamsError::Invalid(msg) => write!(f, "invalid parameters: {}", msg),
+            ParamsError::Integration(e) => write!(f, "integration failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {}
+
+impl From<IntegrationError> for ParamsError {
+    fn from(e: IntegrationError) -> Self {
+        ParamsError::Integration(e)
+    }
+}
+
+impl RunConfig {
+    /// Reads a TOML parameter file (`output`, `[cosmology]`, `[integration]`, `[plasma]`,
+    /// `[observables]`, `[trajectory]`).
+    pub fn from_file(path: &Path) -> Result<Self, ParamsError> {
+        let text = fs::read_to_string(path).map_err(|e| ParamsError::Io(path.to_path_buf(), e))?;
+        toml::from_str(&text).map_err(ParamsError::Toml)
+    }
+
+    /// Builds the configuration from command-line arguments (without the program name).
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Self, ParamsError> {
+        let args: Vec<String> = args.into_iter().collect();
+        if args.iter().any(|a| a == "--help" || a == "-h") {
+            return Err(ParamsError::Usage("first-product: running-vacuum cosmology run".to_string()));
+        }
+
+        // The config file is read first so that the other flags override it.
+        let mut config = match args.iter().position(|a| a == "--config") {
+            Some(i) => {
+                let path = args.get(i + 1)
+                    .ok_or_else(|| ParamsError::Usage("--config needs a file".to_string()))?;
+                RunConfig::from_file(Path::new(path))?
+            }
+            None => RunConfig::default(),
+        };
+
+        let mut iter = args.iter();
+        while let Some(flag) = iter.next() {
+            let value = iter.next()
+                .ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+            config.set(flag, value)?;
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn set(&mut self, flag: &str, value: &str) -> Result<(), ParamsError> {
+        let number = || value.parse::<f64>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a number, got '{}'", flag, value)));
+        let count = || value.parse::<usize>()
+            .map_err(|_| ParamsError::Usage(format!("{} expects a count, got '{}'", flag, value)));
+        let c = &mut self.cosmology;
+        let n = &mut self.integration;
+        match flag {
+            "--config" => {}
+            "--output" => self.output = PathBuf::from(value),
+            "--method" => {
+                n.method = match value {
+                    "rk45" => MethodName::Rk45,
+                    "rk4" => MethodName::Rk4,
+                    other => return Err(ParamsError::Usage(format!(
+                        "unknown integrator '{}', expected rk45 or rk4", other))),
+                }
+            }
+            "--mp" => c.mp = number()?,
+            "--m" => c.m = number()?,
+            "--hi" => c.hi = number()?,
+            "--h0" => c.h0 = number()?,
+            "--nu" => c.nu = number()?,
+            "--omega-m0" => c.omega_m0 = number()?,
+            "--omega-r0" => c.omega_r0 = number()?,
+            "--rtol" => n.rtol = number()?,
+            "--atol" => n.atol = number()?,
+            "--rk4-step" => n.rk4_step = number()?,
+            "--h-final-tolerance" => n.h_final_tolerance = number()?,
+            "--residual-threshold" => n.residual_threshold = number()?,
+            "--precision" => {
+                n.precision = match value {
+                    "double" => Precision::Double,
+                    "exact" => Precision::Exact,
+                    other => return Err(ParamsError::Usage(format!(
+                        "unknown precision '{}', expected double or exact", other))),
+                }
+            }
+            "--plasma" => {
+                self.plasma.model = match value {
+                    "qgp" => PlasmaModel::Qgp,
+                    "conformal" => PlasmaModel::Conformal,
+                    other => return Err(ParamsError::Usage(format!(
+                        "unknown plasma '{}', expected qgp or conformal", other))),
+                }
+            }
+            "--bag-root" => self.plasma.bag_root = number()?,
+            "--crossover-width" => self.plasma.crossover_width = number()?,
+            "--z-max" => self.observables.z_max = number()?,
+            "--z-points" => self.observables.z_points = count()?,
+            "--format" => {
+                self.trajectory.format = match value {
+                    "csv" => OutputFormat::Csv,
+                    "jsonl" => OutputFormat::Jsonl,
+                    "binary" => OutputFormat::Binary,
+                    other => return Err(ParamsError::Usage(format!(
+                        "unknown format '{}', expected csv, jsonl or binary", other))),
+                }
+            }
+            "--every" => self.trajectory.every = count()?,
+            "--min-dn" => self.trajectory.min_dn = number()?,
+            "--checkpoint" => self.trajectory.checkpoint = Some(PathBuf::from(value)),
+            "--checkpoint-every" => self.trajectory.checkpoint_every = count()?,
+            other => return Err(ParamsError::Usage(format!("unknown flag '{}'", other))),
+        }
+        Ok(())
+    }
+
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        self.cosmology.validate()?;
+        self.integration.validate()?;
+        self.plasma.validate()?;
+        self.observables.validate()?;
+        self.trajectory.validate()
+    }
+
+    /// Sidecar path next to the output: qgp_data.csv -> qgp_data.params.toml
+    pub fn sidecar_path(&self) -> PathBuf {
+        self.output.with_extension("params.toml")
+    }
+
+    /// Observables table next to the output: qgp_data.csv -> qgp_data.observables.csv
+    pub fn observables_path(&self) -> PathBuf {
+        self.output.with_extension("observables.csv")
+    }
+
+    /// Writes the parameters used (and the derived scales, as comments) to the sidecar.
+    pub fn write_sidecar(&self, alpha: f64) -> std::io::Result<PathBuf> {
+        let path = self.sidecar_path();
+        let body = toml::to_string(self).expect("parameters always serialize");
+        let text = format!(
+            "# Parameters of the run that produced {}\n# Derived: HF = {:e}, alpha = {:e}\n\n{}",
+            self.output.display(), self.cosmology.hf(), alpha, body);
+        fs::write(&path, text)?;
+        Ok(path)
+    }
+}
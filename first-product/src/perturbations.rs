@@ -0,0 +1,372 @@
+// Linear perturbations: Mukhanov-Sasaki modes and the primordial power spectra.
+//
+// On an inflating background with Hubble-flow parameters ε = −Ḣ/H² and η = d ln ε/dN,
+// the curvature perturbation R = v/z (z = a (2ε)^{1/2} MP) and the tensor amplitude h obey,
+// in e-folds N = ln a,
+//   R'' + (3 − ε + η) R' + (k/aH)² R = 0
+//   h'' + (3 − ε) h' + (k/aH)² h = 0
+// Each mode starts in the Bunch-Davies vacuum v = e^{−ikτ}/(2k)^{1/2} deep inside the
+// horizon (k/aH = 100), so R' = −R (1 + η/2 + i k/aH) and h = 2 v/(a MP), and is
+// integrated with its background until it is frozen outside (k/aH = 10^-3):
+//   P_R = k³ |R|²/(2π²),  P_T = 2 k³ |h|²/(2π²)   (two polarisations)
+// A quadratic fit of ln P around the pivot k* = aH at N* = N_end − efolds gives
+//   n_s − 1 = d ln P_R/d ln k,  α_s = d n_s/d ln k,  r = P_T/P_R at k*.
+//
+// Slow-roll references: to first order in the Hubble flow, at horizon crossing k = aH,
+//   P_R = H²/(8π² ε MP²),  P_T = 2 H²/(π² MP²);
+// and, for a potential, with ε_V = (MP²/2)(V'/V)², η_V = MP² V''/V, ξ² = MP⁴ V'V'''/V²,
+//   n_s = 1 − 6ε_V + 2η_V,  r = 16 ε_V,  α_s = 16 ε_V η_V − 24 ε_V² − 2ξ²
+// at the field value φ* of the pivot crossing.
+// Away from slow roll these references fail: the primeval running-vacuum phase has
+// η ≈ 4(1 − ν), which fixes the tilt n_s − 1 = −2ε − η but not the amplitudes.
+
+use std::f64::consts::PI;
+
+use crate::integrator::{self, IntegrationError, IntegrationStats, Method, OdeSystem};
+use crate::model::{RunningVacuum, State, MAX_EFOLDS};
+use crate::params::ParamsError;
+
+/// k/aH at which a mode starts in the Bunch-Davies vacuum.
+const SUBHORIZON: f64 = 100.0;
+/// k/aH below which a mode is taken as frozen.
+const SUPERHORIZON: f64 = 1e-3;
+
+/// Hubble rate and flow parameters of a background state.
+#[derive(Debug, Clone, Copy)]
+pub struct Flow {
+    pub hubble: f64,
+    pub epsilon: f64, // ε = −Ḣ/H²
+    pub eta: f64,     // η = d ln ε/dN
+}
+
+/// A background integrated in e-folds N = ln a on which perturbations evolve.
+pub trait InflatingBackground: OdeSystem {
+    fn flow(&self, n: f64, y: &[f64]) -> Flow;
+    fn planck_mass(&self) -> f64;
+    /// Initial e-fold and state.
+    fn initial(&self) -> (f64, Vec<f64>);
+}
+
+impl InflatingBackground for RunningVacuum {
+    fn flow(&self, n: f64, y: &[f64]) -> Flow {
+        let state = State::from_vec(n, y);
+        Flow {
+            hubble: self.hubble(state.z),
+            epsilon: self.epsilon(&state),
+            eta: self.slow_roll_eta(&state),
+        }
+    }
+
+    fn planck_mass(&self) -> f64 {
+        self.params.mp
+    }
+
+    fn initial(&self) -> (f64, Vec<f64>) {
+        let start = self.initial_state();
+        (start.n, start.to_vec())
+    }
+}
+
+/// Test inflaton with V = v0 φ^p, MP = 1, state y = [φ, φ'] in e-folds:
+///   H² = V/(3 − ε), ε = φ'²/2,  φ'' = −(3 − ε)(φ' + V'/V)
+#[derive(Debug, Clone, Copy)]
+pub struct Inflaton {
+    pub v0: f64,
+    pub power: f64,
+    pub phi0: f64,
+}
+
+/// m²φ²/2 at the observed amplitude, starting some 70 e-folds before the end of inflation.
+impl Default for Inflaton {
+    fn default() -> Self {
+        Inflaton { v0: 1.8e-11, power: 2.0, phi0: 17.0 }
+    }
+}
+
+impl Inflaton {
+    /// V, V', V'', V''' at φ.
+    pub fn potential(&self, phi: f64) -> [f64; 4] {
+        let p = self.power;
+        [
+            self.v0*phi.powf(p),
+            self.v0*p*phi.powf(p - 1.0),
+            self.v0*p*(p - 1.0)*phi.powf(p - 2.0),
+            self.v0*p*(p - 1.0)*(p - 2.0)*phi.powf(p - 3.0),
+        ]
+    }
+
+    fn phi_ddot(&self, y: &[f64]) -> f64 {
+        let [v, dv, ..] = self.potential(y[0]);
+        -(3.0 - 0.5*y[1]*y[1])*(y[1] + dv/v)
+    }
+
+    /// Field value at e-fold n.
+    pub fn field_at(&self, method: &Method, n: f64) -> Result<f64, IntegrationError> {
+        let (n0, y0) = self.initial();
+        let mut stats = IntegrationStats::default();
+        Ok(integrator::integrate(self, method, n0, &y0, n, &mut stats, |_, _| true)?.1[0])
+    }
+
+    /// Potential slow-roll predictions at φ.
+    pub fn slow_roll(&self, phi: f64) -> SpectralFit {
+        let [v, dv, d2v, d3v] = self.potential(phi);
+        let epsilon_v = 0.5*(dv/v).powi(2);
+        let eta_v = d2v/v;
+        let xi2 = dv*d3v/(v*v);
+        SpectralFit {
+            amplitude: v/(24.0*PI*PI*epsilon_v),
+            n_s: 1.0 - 6.0*epsilon_v + 2.0*eta_v,
+            running: 16.0*epsilon_v*eta_v - 24.0*epsilon_v*epsilon_v - 2.0*xi2,
+            r: 16.0*epsilon_v,
+        }
+    }
+}
+
+impl OdeSystem for Inflaton {
+    fn dim(&self) -> usize {
+        2
+    }
+
+    fn derivatives(&self, _n: f64, y: &[f64], dydn: &mut [f64]) {
+        dydn[0] = y[1];
+        dydn[1] = self.phi_ddot(y);
+    }
+}
+
+impl InflatingBackground for Inflaton {
+    fn flow(&self, _n: f64, y: &[f64]) -> Flow {
+        let epsilon = 0.5*y[1]*y[1];
+        Flow {
+            hubble: (self.potential(y[0])[0]/(3.0 - epsilon)).sqrt(),
+            epsilon,
+            eta: 2.0*self.phi_ddot(y)/y[1],
+        }
+    }
+
+    fn planck_mass(&self) -> f64 {
+        1.0
+    }
+
+    fn initial(&self) -> (f64, Vec<f64>) {
+        let [v, dv, ..] = self.potential(self.phi0);
+        (0.0, vec![self.phi0, -dv/v])
+    }
+}
+
+/// Background plus one mode: y = [background.., R, R', h, h'] with the complex
+/// amplitudes stored as (re, im).
+struct ModeEquations<'a, B> {
+    background: &'a B,
+    k: f64,
+}
+
+impl<B: InflatingBackground> OdeSystem for ModeEquations<'_, B> {
+    fn dim(&self) -> usize {
+        self.background.dim() + 8
+    }
+
+    fn derivatives(&self, n: f64, y: &[f64], dydn: &mut [f64]) {
+        let m = self.background.dim();
+        self.background.derivatives(n, &y[..m], &mut dydn[..m]);
+        let flow = self.background.flow(n, &y[..m]);
+        let q2 = (self.k*(-n).exp()/flow.hubble).powi(2);
+        let frictions = [3.0 - flow.epsilon + flow.eta, 3.0 - flow.epsilon];
+        for (field, friction) in frictions.into_iter().enumerate() {
+            let i = m + 4*field;
+            for c in 0..2 {
+                dydn[i + c] = y[i + 2 + c];
+                dydn[i + 2 + c] = -friction*y[i + 2 + c] - q2*y[i + c];
+            }
+        }
+    }
+}
+
+/// Power spectra of one mode, with the slow-roll values at its horizon crossing.
+#[derive(Debug, Clone, Copy)]
+pub struct Mode {
+    pub k: f64,
+    pub crossing: f64, // e-fold of k = aH
+    pub scalar: f64,
+    pub tensor: f64,
+    pub scalar_slow_roll: f64,
+    pub tensor_slow_roll: f64,
+}
+
+/// Amplitude, tilt, running and tensor ratio at the pivot.
+#[derive(Debug, Clone, Copy)]
+pub struct SpectralFit {
+    pub amplitude: f64,
+    pub n_s: f64,
+    pub running: f64,
+    pub r: f64,
+}
+
+/// Least-squares ln P = c0 + c1 x + c2 x²/2 in x = ln(k/k*).
+fn quadratic_fit(xs: &[f64], ys: &[f64]) -> [f64; 3] {
+    let mut a = [[0.0; 3]; 3];
+    let mut b = [0.0; 3];
+    for (&x, &y) in xs.iter().zip(ys) {
+        let basis = [1.0, x, 0.5*x*x];
+        for i in 0..3 {
+            b[i] += basis[i]*y;
+            for j in 0..3 {
+                a[i][j] += basis[i]*basis[j];
+            }
+        }
+    }
+    // Cramer's rule on the 3x3 normal equations
+    let det = |m: &[[f64; 3]; 3]| {
+        m[0][0]*(m[1][1]*m[2][2] - m[1][2]*m[2][1]) - m[0][1]*(m[1][0]*m[2][2] - m[1][2]*m[2][0])
+            + m[0][2]*(m[1][0]*m[2][1] - m[1][1]*m[2][0])
+    };
+    let d = det(&a);
+    let mut c = [0.0; 3];
+    for (col, ci) in c.iter_mut().enumerate() {
+        let mut m = a;
+        for row in 0..3 {
+            m[row][col] = b[row];
+        }
+        *ci = det(&m)/d;
+    }
+    c
+}
+
+impl SpectralFit {
+    fn of(xs: &[f64], scalar: &[f64], tensor: &[f64]) -> Self {
+        let s = quadratic_fit(xs, &scalar.iter().map(|p| p.ln()).collect::<Vec<f64>>());
+        let t = quadratic_fit(xs, &tensor.iter().map(|p| p.ln()).collect::<Vec<f64>>());
+        SpectralFit { amplitude: s[0].exp(), n_s: 1.0 + s[1], running: s[2], r: (t[0] - s[0]).exp() }
+    }
+
+    /// [A_s, n_s, α_s, r]
+    pub fn values(&self) -> [f64; 4] {
+        [self.amplitude, self.n_s, self.running, self.r]
+    }
+}
+
+/// The spectra of a set of modes around the pivot.
+#[derive(Debug, Clone)]
+pub struct Spectrum {
+    pub pivot_k: f64,
+    pub pivot_n: f64,
+    pub end_n: f64,
+    pub modes: Vec<Mode>,
+    pub numerical: SpectralFit,
+    pub slow_roll: SpectralFit,
+    pub stats: IntegrationStats,
+}
+
+impl Spectrum {
+    pub const CSV_HEADER: &'static str = "k,ln_k_over_pivot,crossing_efold,P_scalar,P_tensor,\
+P_scalar_slow_roll,P_tensor_slow_roll";
+
+    pub fn csv_rows(&self) -> Vec<String> {
+        self.modes.iter().map(|m| format!("{},{},{},{},{},{},{}",
+            m.k, (m.k/self.pivot_k).ln(), m.crossing, m.scalar, m.tensor,
+            m.scalar_slow_roll, m.tensor_slow_roll)).collect()
+    }
+}
+
+/// Integrates the background alone from its start until `keep_going` fails.
+fn background_until<B, F>(background: &B, method: &Method, stats: &mut IntegrationStats,
+                          mut keep_going: F) -> Result<(f64, Vec<f64>), IntegrationError>
+where
+    B: InflatingBackground,
+    F: FnMut(f64, &Flow) -> bool,
+{
+    let (n0, y0) = background.initial();
+    integrator::integrate(background, method, n0, &y0, MAX_EFOLDS, stats,
+                          |n, y| keep_going(n, &background.flow(n, y)))
+}
+
+/// Evolves the mode k from k/aH = SUBHORIZON until it is frozen.
+fn evolve_mode<B: InflatingBackground>(background: &B, method: &Method, k: f64,
+                                       stats: &mut IntegrationStats) -> Result<Mode, ParamsError> {
+    let mp = background.planck_mass();
+    let horizon_ratio = |n: f64, flow: &Flow| k*(-n).exp()/flow.hubble;
+    let (n0, y0) = background.initial();
+    if horizon_ratio(n0, &background.flow(n0, &y0)) < SUBHORIZON {
+        return Err(ParamsError::Invalid(format!(
+            "mode k = {:e} is not inside the horizon at the start of the background", k)));
+    }
+    let (n_start, bg) = background_until(background, method, stats,
+                                         |n, flow| horizon_ratio(n, flow) > SUBHORIZON && flow.epsilon < 1.0)?;
+    let flow = background.flow(n_start, &bg);
+    let q = horizon_ratio(n_start, &flow);
+    if q > SUBHORIZON {
+        return Err(ParamsError::Invalid(format!("mode k = {:e} never enters inflation", k)));
+    }
+
+    // Bunch-Davies: R = v/z, h = 2v/(a MP), v = 1/(2k)^{1/2} up to a phase
+    let a = n_start.exp();
+    let v = 1.0/(2.0*k).sqrt();
+    let r = v/(a*(2.0*flow.epsilon).sqrt()*mp);
+    let h = 2.0*v/(a*mp);
+    let mut y = bg.clone();
+    y.extend([r, 0.0, -r*(1.0 + 0.5*flow.eta), -r*q, h, 0.0, -h, -h*q]);
+
+    // Follow the mode, recording the flow where it crosses k = aH
+    let system = ModeEquations { background, k };
+    let m = background.dim();
+    let mut previous: Option<(f64, Flow)> = None;
+    let mut crossing = None;
+    let (n_end, y) = integrator::integrate(&system, method, n_start, &y, MAX_EFOLDS, stats, |n, y| {
+        let flow = background.flow(n, &y[..m]);
+        let q = horizon_ratio(n, &flow);
+        if let Some((n_prev, prev)) = previous.filter(|_| crossing.is_none() && q <= 1.0) {
+            // Interpolate ln H, ln ε and N linearly in ln(k/aH)
+            let q_prev = horizon_ratio(n_prev, &prev);
+            let s = q_prev.ln()/(q_prev.ln() - q.ln());
+            let mix = |a: f64, b: f64| (a.ln() + s*(b.ln() - a.ln())).exp();
+            crossing = Some((n_prev + s*(n - n_prev), mix(prev.hubble, flow.hubble), mix(prev.epsilon, flow.epsilon)));
+        }
+        previous = Some((n, flow));
+        q > SUPERHORIZON && flow.epsilon < 1.0
+    })?;
+    let q_end = horizon_ratio(n_end, &background.flow(n_end, &y[..m]));
+    let (crossing, hubble, epsilon) = match crossing {
+        Some(c) if q_end <= SUPERHORIZON => c,
+        _ => return Err(ParamsError::Invalid(format!("mode k = {:e} does not freeze before inflation ends", k))),
+    };
+
+    let norm = k.powi(3)/(2.0*PI*PI);
+    let modulus2 = |i: usize| y[i]*y[i] + y[i + 1]*y[i + 1];
+    Ok(Mode {
+        k,
+        crossing,
+        scalar: norm*modulus2(m),
+        tensor: 2.0*norm*modulus2(m + 4),
+        scalar_slow_roll: hubble*hubble/(8.0*PI*PI*epsilon*mp*mp),
+        tensor_slow_roll: 2.0*hubble*hubble/(PI*PI*mp*mp),
+    })
+}
+
+/// Spectra of `modes` wavenumbers spread over ln(k/k*) in [−span, span], with the pivot
+/// leaving the horizon `efolds` before the end of inflation (ε = 1).
+pub fn spectrum<B: InflatingBackground>(background: &B, method: &Method, efolds: f64, modes: usize,
+                                        span: f64) -> Result<Spectrum, ParamsError> {
+    let mut stats = IntegrationStats::default();
+    let (end_n, y) = background_until(background, method, &mut stats, |_, flow| flow.epsilon < 1.0)?;
+    if background.flow(end_n, &y).epsilon < 1.0 {
+        return Err(ParamsError::Invalid("the background never ends inflation (ε < 1 throughout)".to_string()));
+    }
+    let pivot_n = end_n - efolds;
+    if pivot_n <= background.initial().0 {
+        return Err(ParamsError::Invalid(format!("the background inflates only {:.2} e-folds", end_n)));
+    }
+    let (n0, y0) = background.initial();
+    let (_, y) = integrator::integrate(background, method, n0, &y0, pivot_n, &mut stats, |_, _| true)?;
+    let pivot_k = pivot_n.exp()*background.flow(pivot_n, &y).hubble;
+
+    let xs: Vec<f64> = (0..modes)
+        .map(|i| if modes > 1 { span*(2.0*i as f64/(modes - 1) as f64 - 1.0) } else { 0.0 })
+        .collect();
+    let modes = xs.iter()
+        .map(|x| evolve_mode(background, method, pivot_k*x.exp(), &mut stats))
+        .collect::<Result<Vec<Mode>, ParamsError>>()?;
+
+    let column = |f: fn(&Mode) -> f64| modes.iter().map(f).collect::<Vec<f64>>();
+    let numerical = SpectralFit::of(&xs, &column(|m| m.scalar), &column(|m| m.tensor));
+    let slow_roll = SpectralFit::of(&xs, &column(|m| m.scalar_slow_roll), &column(|m| m.tensor_slow_roll));
+    Ok(Spectrum { pivot_k, pivot_n, end_n, modes, numerical, slow_roll, stats })
+}
@@ -0,0 +1,404 @@
+// Equation of state of the primordial plasma and its effective degrees of freedom.
+//
+// The radiation fluid of the run is the Standard Model plasma at temperature T:
+//   ρ = (π²/30) g*(T) T⁴,  s = (2π²/45) g*s(T) T³,  w = p/ρ
+// Each species of mass m, g internal states and Bose (+) or Fermi (−) statistics
+// contributes, with x = m/T and σ_n = 1 (bosons) or (−1)^{n+1} (fermions),
+//   p/T⁴ = g x²/(2π²) Σ σ_n K2(n x)/n²
+//   ρ/T⁴ = g/(2π²) Σ σ_n [3 x² K2(n x)/n² + x³ K1(n x)/n]
+// which tends to g π²/90 (× 7/8 for fermions) and ρ = 3p as m/T → 0.
+//
+// The strongly interacting sector is a bag-model quark-gluon plasma (gluons, u, d, s)
+//   p_Q = p_ideal − B,  ρ_Q = ρ_ideal + B
+// above a crossover to a hadron resonance gas of the light mesons and baryons. The
+// crossover temperature Tc is where the two pressures meet (the Gibbs construction of
+// the bag model), and the first-order jump is smoothed over Tc ± ΔT by interpolating the
+// entropy density, which keeps s > 0 and the thermodynamics consistent:
+//   s = S s_Q + (1 − S) s_H,  S = 1/2 + (15u − 10u³ + 3u⁵)/16,  u = (T − Tc)/ΔT ∈ [−1, 1]
+//   p = p_H(Tc − ΔT) + ∫ s dT,  ρ = T s − p
+// S is 0 below the window and 1 above, where p = p_Q + const continues the integral. The heavy
+// quarks are counted with the plasma fraction S. Neutrinos decouple at T_dec and keep
+// their entropy, so below it T_ν/T = (g*s,γe(T)/g*s,γe(T_dec))^{1/3} → (4/11)^{1/3}.
+//
+// The run carries ln ρ, so the equation of state is tabulated once on a grid in ln T
+// and looked up by ln ρ; outside the grid every species is either ultra-relativistic
+// or gone, w = 1/3 and g* is constant.
+
+use std::f64::consts::PI;
+
+use crate::params::{ParamsError, PlasmaModel, PlasmaParams};
+
+/// Neutrino decoupling temperature, GeV.
+const NEUTRINO_DECOUPLING: f64 = 2.0e-3;
+
+/// Temperature range of the table, GeV, and its resolution in ln T.
+const T_MIN: f64 = 1.0e-5;
+const T_MAX: f64 = 1.0e5;
+const POINTS_PER_EFOLD: f64 = 64.0;
+
+/// Simpson panels of the entropy integral inside the crossover window.
+const WINDOW_PANELS: usize = 16;
+
+/// Terms of the Bessel series; the series stops early once K(n x) ~ e^{-n x} is negligible.
+const SERIES_TERMS: usize = 40;
+const SERIES_CUTOFF: f64 = 60.0;
+
+/// Below this m/T a species is counted as massless (relative error ~ (m/T)²).
+const RELATIVISTIC: f64 = 1.0e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Statistics {
+    Bose,
+    Fermi,
+}
+
+use Statistics::{Bose, Fermi};
+
+/// One particle species: mass in GeV, internal states (spin, colour, antiparticles).
+#[derive(Debug, Clone, Copy)]
+struct Species {
+    mass: f64,
+    states: f64,
+    statistics: Statistics,
+}
+
+const fn species(mass: f64, states: f64, statistics: Statistics) -> Species {
+    Species { mass, states, statistics }
+}
+
+const PHOTON: Species = species(0.0, 2.0, Bose);
+const ELECTRONS: Species = species(0.000_511, 4.0, Fermi);
+const NEUTRINOS: Species = species(0.0, 6.0, Fermi);
+
+/// Leptons and electroweak bosons beyond the photon and the electrons.
+const ELECTROWEAK: [Species; 5] = [
+    species(0.105_66, 4.0, Fermi), // μ±
+    species(1.776_9, 4.0, Fermi),  // τ±
+    species(80.38, 6.0, Bose),     // W±
+    species(91.19, 3.0, Bose),     // Z
+    species(125.25, 1.0, Bose),    // H
+];
+
+/// Deconfined light quarks and gluons of the bag.
+const QUARK_GLUON: [Species; 4] = [
+    species(0.0, 16.0, Bose),    // g
+    species(0.0, 12.0, Fermi),   // u
+    species(0.0, 12.0, Fermi),   // d
+    species(0.093, 12.0, Fermi), // s
+];
+
+const HEAVY_QUARKS: [Species; 3] = [
+    species(1.27, 12.0, Fermi),  // c
+    species(4.18, 12.0, Fermi),  // b
+    species(172.7, 12.0, Fermi), // t
+];
+
+/// Light hadrons and resonances below 1.6 GeV, antiparticles included in the states.
+const HADRONS: [Species; 31] = [
+    species(0.138, 3.0, Bose),   // π
+    species(0.4957, 4.0, Bose),  // K
+    species(0.5479, 1.0, Bose),  // η
+    species(0.7753, 9.0, Bose),  // ρ
+    species(0.7827, 3.0, Bose),  // ω
+    species(0.8917, 12.0, Bose), // K*(892)
+    species(0.9389, 8.0, Fermi), // N
+    species(0.9578, 1.0, Bose),  // η'
+    species(0.980, 3.0, Bose),   // a0(980)
+    species(0.990, 1.0, Bose),   // f0(980)
+    species(1.0195, 3.0, Bose),  // φ
+    species(1.1157, 4.0, Fermi), // Λ
+    species(1.166, 3.0, Bose),   // h1(1170)
+    species(1.1932, 12.0, Fermi),// Σ
+    species(1.2295, 9.0, Bose),  // b1(1235)
+    species(1.230, 9.0, Bose),   // a1(1260)
+    species(1.232, 32.0, Fermi), // Δ(1232)
+    species(1.272, 12.0, Bose),  // K1(1270)
+    species(1.2755, 5.0, Bose),  // f2(1270)
+    species(1.2819, 3.0, Bose),  // f1(1285)
+    species(1.3183, 8.0, Fermi), // Ξ
+    species(1.3837, 24.0, Fermi),// Σ(1385)
+    species(1.403, 12.0, Bose),  // K1(1400)
+    species(1.405, 4.0, Fermi),  // Λ(1405)
+    species(1.4273, 20.0, Bose), // K2*(1430)
+    species(1.440, 8.0, Fermi),  // N(1440)
+    species(1.515, 16.0, Fermi), // N(1520)
+    species(1.519, 8.0, Fermi),  // Λ(1520)
+    species(1.5318, 16.0, Fermi),// Ξ(1530)
+    species(1.535, 8.0, Fermi),  // N(1535)
+    species(1.6725, 8.0, Fermi), // Ω
+];
+
+/// Modified Bessel functions K0(x) and K1(x), Abramowitz & Stegun 9.8.1-9.8.8 (|ε| < 1e-7).
+fn bessel_k01(x: f64) -> (f64, f64) {
+    let poly = |c: &[f64], t: f64| c.iter().rev().fold(0.0, |acc, &a| acc*t + a);
+    if x <= 2.0 {
+        let u = (x/3.75).powi(2);
+        let i0 = poly(&[1.0, 3.515_622_9, 3.089_942_4, 1.206_749_2, 0.265_973_2, 0.036_076_8, 0.004_581_3], u);
+        let i1 = x*poly(&[0.5, 0.878_905_94, 0.514_988_69, 0.150_849_34, 0.026_587_33, 0.003_015_32,
+                          0.000_324_11], u);
+        let t = x*x/4.0;
+        let k0 = -(x/2.0).ln()*i0 + poly(&[-0.577_215_66, 0.422_784_20, 0.230_697_56, 0.034_885_90,
+                                           0.002_626_98, 0.000_107_50, 0.000_007_40], t);
+        let k1 = (x/2.0).ln()*i1 + poly(&[1.0, 0.154_431_44, -0.672_785_79, -0.181_568_97, -0.019_194_02,
+                                          -0.001_104_04, -0.000_046_86], t)/x;
+        (k0, k1)
+    } else {
+        let v = 2.0/x;
+        let scale = (-x).exp()/x.sqrt();
+        let k0 = scale*poly(&[1.253_314_14, -0.078_323_58, 0.021_895_68, -0.010_624_46, 0.005_878_72,
+                              -0.002_515_40, 0.000_532_08], v);
+        let k1 = scale*poly(&[1.253_314_14, 0.234_986_19, -0.036_556_20, 0.015_042_68, -0.007_803_53,
+                              0.003_256_14, -0.000_682_45], v);
+        (k0, k1)
+    }
+}
+
+/// Pressure, energy density and entropy density in GeV⁴ and GeV³.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Thermo {
+    pub p: f64,
+    pub rho: f64,
+    pub s: f64,
+}
+
+impl std::ops::Add for Thermo {
+    type Output = Thermo;
+    fn add(self, o: Thermo) -> Thermo {
+        Thermo { p: self.p + o.p, rho: self.rho + o.rho, s: self.s + o.s }
+    }
+}
+
+/// Ideal-gas thermodynamics of one species at temperature t.
+fn ideal(sp: &Species, t: f64) -> Thermo {
+    let t4 = t.powi(4);
+    let (p, rho) = if sp.mass < RELATIVISTIC*t {
+        let factor = if sp.statistics == Bose { 1.0 } else { 7.0/8.0 };
+        let p = factor*sp.states*PI*PI/90.0*t4;
+        (p, 3.0*p)
+    } else {
+        let x = sp.mass/t;
+        let (mut p, mut rho) = (0.0, 0.0);
+        for n in 1..=SERIES_TERMS {
+            let nx = n as f64*x;
+            if nx > SERIES_CUTOFF {
+                break;
+            }
+            let sign = if sp.statistics == Fermi && n % 2 == 0 { -1.0 } else { 1.0 };
+            let (k0, k1) = bessel_k01(nx);
+            let k2 = k0 + 2.0*k1/nx;
+            let n = n as f64;
+            p += sign*x*x*k2/(n*n);
+            rho += sign*(3.0*x*x*k2/(n*n) + x*x*x*k1/n);
+        }
+        let c = sp.states/(2.0*PI*PI)*t4;
+        (c*p, c*rho)
+    };
+    Thermo { p, rho, s: (p + rho)/t }
+}
+
+fn sum(list: &[Species], t: f64) -> Thermo {
+    list.iter().map(|sp| ideal(sp, t)).fold(Thermo::default(), |a, b| a + b)
+}
+
+/// Bag-model quark-gluon plasma with bag constant `bag` = B in GeV⁴.
+fn quark_gluon(t: f64, bag: f64) -> Thermo {
+    let ideal = sum(&QUARK_GLUON, t);
+    Thermo { p: ideal.p - bag, rho: ideal.rho + bag, s: ideal.s }
+}
+
+/// Crossover temperature where the plasma pressure first overtakes the hadron gas, None if
+/// it does not between 10 MeV and 1 GeV. (With all its resonances massless the hadron gas
+/// would win again at high T, so the first crossing is the physical one.)
+pub fn crossover_temperature(bag_root: f64) -> Option<f64> {
+    let bag = bag_root.powi(4);
+    let gap = |t: f64| quark_gluon(t, bag).p - sum(&HADRONS, t).p;
+    let mut lo = 0.01;
+    if gap(lo) >= 0.0 {
+        return None;
+    }
+    let mut hi = lo;
+    while gap(hi) < 0.0 {
+        lo = hi;
+        hi *= 1.02;
+        if hi > 1.0 {
+            return None;
+        }
+    }
+    for _ in 0..100 {
+        let mid = 0.5*(lo + hi);
+        if gap(mid) < 0.0 { lo = mid } else { hi = mid }
+    }
+    Some(0.5*(lo + hi))
+}
+
+/// Plasma fraction S of the strongly interacting sector, 0 below Tc − ΔT and 1 above Tc + ΔT.
+fn plasma_share(t: f64, tc: f64, width: f64) -> f64 {
+    let u = ((t - tc)/width).clamp(-1.0, 1.0);
+    0.5 + (15.0*u - 10.0*u.powi(3) + 3.0*u.powi(5))/16.0
+}
+
+/// Entropy density of the strongly interacting sector inside the crossover window.
+fn crossover_entropy(t: f64, bag: f64, tc: f64, width: f64) -> f64 {
+    let share = plasma_share(t, tc, width);
+    share*(quark_gluon(t, bag) + sum(&HEAVY_QUARKS, t)).s + (1.0 - share)*sum(&HADRONS, t).s
+}
+
+/// ∫ f over [a, b] by Simpson's rule on `panels` (even) panels.
+fn simpson(f: impl Fn(f64) -> f64, a: f64, b: f64, panels: usize) -> f64 {
+    let h = (b - a)/panels as f64;
+    let inner: f64 = (1..panels).map(|i| f(a + i as f64*h)*if i % 2 == 1 { 4.0 } else { 2.0 }).sum();
+    h/3.0*(f(a) + inner + f(b))
+}
+
+/// Strongly interacting sector: the hadron gas below the window, the bag plasma above it
+/// with its pressure shifted by `offset` to stay continuous, p = ∫ s dT inside.
+fn strong(t: f64, bag: f64, tc: f64, width: f64, offset: f64) -> Thermo {
+    let (below, above) = (tc - width, tc + width);
+    if t <= below {
+        sum(&HADRONS, t)
+    } else if t >= above {
+        let q = quark_gluon(t, bag) + sum(&HEAVY_QUARKS, t);
+        Thermo { p: q.p + offset, rho: q.rho - offset, s: q.s }
+    } else {
+        let entropy = |t: f64| crossover_entropy(t, bag, tc, width);
+        let p = sum(&HADRONS, below).p + simpson(entropy, below, t, WINDOW_PANELS);
+        let s = entropy(t);
+        Thermo { p, rho: t*s - p, s }
+    }
+}
+
+/// One row of the table.
+#[derive(Debug, Clone, Copy)]
+pub struct PlasmaPoint {
+    pub temperature: f64,
+    pub thermo: Thermo,
+}
+
+impl PlasmaPoint {
+    pub fn g_star(&self) -> f64 {
+        self.thermo.rho/(PI*PI/30.0*self.temperature.powi(4))
+    }
+
+    pub fn g_star_s(&self) -> f64 {
+        self.thermo.s/(2.0*PI*PI/45.0*self.temperature.powi(3))
+    }
+
+    pub fn w(&self) -> f64 {
+        self.thermo.p/self.thermo.rho
+    }
+}
+
+/// Neutrino temperature at photon temperature T, GeV.
+pub fn neutrino_temperature(t: f64) -> f64 {
+    if t >= NEUTRINO_DECOUPLING {
+        return t;
+    }
+    // Comoving entropy of photons and e± is conserved after decoupling: s a³ ∝ s/T_ν³.
+    let s_dec = (ideal(&PHOTON, NEUTRINO_DECOUPLING) + ideal(&ELECTRONS, NEUTRINO_DECOUPLING)).s;
+    let photons_electrons = ideal(&PHOTON, t) + ideal(&ELECTRONS, t);
+    (photons_electrons.s/s_dec).cbrt()*NEUTRINO_DECOUPLING
+}
+
+fn point(t: f64, bag: f64, tc: f64, width: f64, offset: f64) -> PlasmaPoint {
+    let photons_electrons = ideal(&PHOTON, t) + ideal(&ELECTRONS, t);
+    let neutrinos = ideal(&NEUTRINOS, neutrino_temperature(t));
+    let thermo = photons_electrons + neutrinos + sum(&ELECTROWEAK, t) + strong(t, bag, tc, width, offset);
+    PlasmaPoint { temperature: t, thermo }
+}
+
+/// Radiation equation of state at one ln ρ of the run.
+#[derive(Debug, Clone, Copy)]
+pub struct RadiationState {
+    pub temperature: f64,
+    pub g_star: f64,
+    pub w: f64,
+    pub dw_dln_rho: f64, // d w/d ln ρ along the table
+}
+
+/// Equation of state of the radiation fluid, tabulated in ln T and looked up by ln ρ.
+#[derive(Debug, Clone)]
+pub struct Plasma {
+    pub model: PlasmaModel,
+    pub crossover: f64, // Tc, GeV
+    pub points: Vec<PlasmaPoint>,
+    ln_t: Vec<f64>,
+    ln_rho: Vec<f64>,
+}
+
+impl Plasma {
+    /// Builds the table; fails if the pressures do not cross or ρ(T) is not increasing.
+    pub fn new(params: &PlasmaParams) -> Result<Self, ParamsError> {
+        let tc = crossover_temperature(params.bag_root).ok_or_else(|| ParamsError::Invalid(format!(
+            "bag_root = {} GeV gives no crossover to the hadron gas between 10 MeV and 1 GeV",
+            params.bag_root)))?;
+        let (bag, width) = (params.bag_root.powi(4), params.crossover_width);
+        if width >= tc {
+            return Err(ParamsError::Invalid(format!(
+                "crossover_width = {} GeV must be below the crossover temperature {:.4} GeV", width, tc)));
+        }
+        // Pressure carried across the window, less that of the bag plasma at its top
+        let (below, above) = (tc - width, tc + width);
+        let offset = sum(&HADRONS, below).p
+            + simpson(|t| crossover_entropy(t, bag, tc, width), below, above, 4*WINDOW_PANELS)
+            - (quark_gluon(above, bag) + sum(&HEAVY_QUARKS, above)).p;
+
+        let count = ((T_MAX/T_MIN).ln()*POINTS_PER_EFOLD).ceil() as usize + 1;
+        let step = (T_MAX/T_MIN).ln()/(count - 1) as f64;
+        let ln_t: Vec<f64> = (0..count).map(|i| T_MIN.ln() + i as f64*step).collect();
+        let points: Vec<PlasmaPoint> = ln_t.iter()
+            .map(|l| point(l.exp(), bag, tc, width, offset))
+            .collect();
+        let ln_rho: Vec<f64> = points.iter().map(|p| p.thermo.rho.ln()).collect();
+        if let Some(i) = ln_rho.windows(2).position(|w| w[1].is_nan() || w[1] <= w[0]) {
+            return Err(ParamsError::Invalid(format!(
+                "the energy density is not increasing at T = {:.4e} GeV; widen crossover_width = {}",
+                points[i].temperature, params.crossover_width)));
+        }
+        Ok(Plasma { model: params.model, crossover: tc, points, ln_t, ln_rho })
+    }
+
+    /// g*s at temperature T, interpolated in ln T and constant beyond the table.
+    pub fn entropy_dof(&self, temperature: f64) -> f64 {
+        let last = self.ln_t.len() - 1;
+        let ln_t = temperature.ln().clamp(self.ln_t[0], self.ln_t[last]);
+        let i = self.ln_t.partition_point(|&l| l <= ln_t).clamp(1, last) - 1;
+        let u = (ln_t - self.ln_t[i])/(self.ln_t[i + 1] - self.ln_t[i]);
+        let (a, b) = (self.points[i].g_star_s(), self.points[i + 1].g_star_s());
+        a + u*(b - a)
+    }
+
+    /// Factor by which ρ a⁴ grows from the top of the table to its bottom, as the species
+    /// that annihilate hand their entropy to the rest: with g*s (aT)³ conserved,
+    /// ρ a⁴ ∝ g*/g*s^{4/3}. 1 for the conformal model, whose radiation scales as a⁻⁴.
+    pub fn entropy_release(&self) -> f64 {
+        let scaling = |p: &PlasmaPoint| p.g_star()/p.g_star_s().powf(4.0/3.0);
+        match self.model {
+            PlasmaModel::Qgp => scaling(&self.points[0])/scaling(&self.points[self.points.len() - 1]),
+            PlasmaModel::Conformal => 1.0,
+        }
+    }
+
+    /// T, g*, w and dw/d ln ρ at the radiation density e^{ln ρ}; w = 1/3 for the conformal model.
+    pub fn radiation(&self, ln_rho: f64) -> RadiationState {
+        let last = self.ln_rho.len() - 1;
+        let (ln_t, g_star, w, dw) = if ln_rho.is_nan() || ln_rho <= self.ln_rho[0] || ln_rho >= self.ln_rho[last] {
+            // Constant g* beyond the table: ρ ∝ T⁴
+            let edge = if ln_rho >= self.ln_rho[last] { last } else { 0 };
+            let ln_t = self.ln_t[edge] + 0.25*(ln_rho - self.ln_rho[edge]);
+            let ln_t = if ln_t.is_nan() { self.ln_t[edge] } else { ln_t };
+            (ln_t, self.points[edge].g_star(), 1.0/3.0, 0.0)
+        } else {
+            let i = self.ln_rho.partition_point(|&l| l <= ln_rho).clamp(1, last) - 1;
+            let (a, b) = (&self.points[i], &self.points[i + 1]);
+            let u = (ln_rho - self.ln_rho[i])/(self.ln_rho[i + 1] - self.ln_rho[i]);
+            let lerp = |x: f64, y: f64| x + u*(y - x);
+            let dw = (b.w() - a.w())/(self.ln_rho[i + 1] - self.ln_rho[i]);
+            (lerp(self.ln_t[i], self.ln_t[i + 1]), lerp(a.g_star(), b.g_star()), lerp(a.w(), b.w()), dw)
+        };
+        let (w, dw_dln_rho) = match self.model {
+            PlasmaModel::Qgp => (w, dw),
+            PlasmaModel::Conformal => (1.0/3.0, 0.0),
+        };
+        RadiationState { temperature: ln_t.exp(), g_star, w, dw_dln_rho }
+    }
+}
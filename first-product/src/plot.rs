@@ -0,0 +1,380 @@
+// Figures of a run, drawn natively with plotters.
+//
+// `first-product plot FILE.csv` reads a table by its header and writes every figure the
+// columns allow, each as PNG and SVG next to the table (FILE.<figure>.png/.svg):
+//   phase       log10|φ|, log10|V|, log10|ρ_k| as a 3D scatter coloured by era
+//   hubble      H(t) on log-log axes
+//   w           equation of state w(t)
+//   fractions   energy fractions ρ_V/ρ, ρ_r/ρ, ρ_m/ρ against t
+//   pv          P-V diagram of a carnot_data.csv cycle
+// Trajectories start at t = 0; the time axes are logarithmic, so that row is skipped.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use plotters::coord::Shift;
+use plotters::prelude::*;
+
+const SIZE: (u32, u32) = (1024, 768);
+
+/// Offset keeping log10 finite at zero.
+const LOG_FLOOR: f64 = 1e-60;
+
+/// Error raised while reading the table or the plot flags.
+#[derive(Debug)]
+pub enum PlotError {
+    Io(PathBuf, std::io::Error),
+    Data { path: PathBuf, line: usize, message: String },
+    Usage(String),
+}
+
+impl fmt::Display for PlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlotError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            PlotError::Data { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+            PlotError::Usage(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for PlotError {}
+
+/// A CSV table held as text, parsed column by column on demand.
+pub struct Table {
+    path: PathBuf,
+    names: Vec<String>,
+    rows: Vec<(usize, Vec<String>)>, // (line number, fields)
+}
+
+impl Table {
+    /// Reads a CSV table with a header line; lines starting with '#' are comments.
+    pub fn read(path: &Path) -> Result<Table, PlotError> {
+        let text = fs::read_to_string(path).map_err(|e| PlotError::Io(path.to_path_buf(), e))?;
+        let mut lines = text.lines().enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'));
+        let (_, header) = lines.next().ok_or_else(|| PlotError::Data {
+            path: path.to_path_buf(), line: 1, message: "empty table".to_string(),
+        })?;
+        let names = header.split(',').map(|n| n.trim().to_string()).collect();
+        let rows = lines
+            .map(|(line, l)| (line, l.split(',').map(|f| f.trim().to_string()).collect()))
+            .collect();
+        Ok(Table { path: path.to_path_buf(), names, rows })
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.names.iter().any(|n| n == name)
+    }
+
+    fn column(&self, name: &str) -> Result<usize, PlotError> {
+        self.names.iter().position(|n| n == name).ok_or_else(|| PlotError::Data {
+            path: self.path.clone(), line: 1, message: format!("missing column '{}'", name),
+        })
+    }
+
+    /// The text of one column.
+    pub fn labels(&self, name: &str) -> Result<Vec<&str>, PlotError> {
+        let c = self.column(name)?;
+        self.rows.iter()
+            .map(|(line, fields)| fields.get(c).map(String::as_str).ok_or_else(|| PlotError::Data {
+                path: self.path.clone(), line: *line, message: format!("expected {} columns", self.names.len()),
+            }))
+            .collect()
+    }
+
+    /// One column as numbers; NaN and inf are kept and left to the figures to skip.
+    pub fn numbers(&self, name: &str) -> Result<Vec<f64>, PlotError> {
+        let c = self.column(name)?;
+        self.rows.iter()
+            .map(|(line, fields)| fields.get(c).and_then(|f| f.parse().ok()).ok_or_else(|| PlotError::Data {
+                path: self.path.clone(), line: *line, message: format!("column '{}' is not numeric", name),
+            }))
+            .collect()
+    }
+}
+
+/// Image formats written for each figure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Svg,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Png => "png",
+            Format::Svg => "svg",
+        }
+    }
+}
+
+/// The figures `plot` knows how to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Figure {
+    Phase,
+    Hubble,
+    EquationOfState,
+    Fractions,
+    PressureVolume,
+}
+
+impl Figure {
+    pub const ALL: [Figure; 5] =
+        [Figure::Phase, Figure::Hubble, Figure::EquationOfState, Figure::Fractions, Figure::PressureVolume];
+
+    /// Suffix of the output files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Figure::Phase => "phase",
+            Figure::Hubble => "hubble",
+            Figure::EquationOfState => "w",
+            Figure::Fractions => "fractions",
+            Figure::PressureVolume => "pv",
+        }
+    }
+
+    /// Columns the figure is drawn from.
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            Figure::Phase => &["phi", "Potential", "Kinetic", "Era"],
+            Figure::Hubble => &["t", "H"],
+            Figure::EquationOfState => &["t", "w"],
+            Figure::Fractions => &["t", "Potential", "Radiation", "Matter"],
+            Figure::PressureVolume => &["V", "P"],
+        }
+    }
+
+    pub fn available(self, table: &Table) -> bool {
+        self.columns().iter().all(|c| table.has(c))
+    }
+
+    pub fn draw<DB>(self, table: &Table, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+    where
+        DB: DrawingBackend,
+        DB::ErrorType: 'static,
+    {
+        root.fill(&WHITE)?;
+        match self {
+            Figure::Phase => draw_phase(table, root),
+            Figure::Hubble => {
+                let h = table.numbers("H")?;
+                draw_against_time(table, root, "Hubble rate", "H [GeV]", &[("H", &h, BLUE)], true)
+            }
+            Figure::EquationOfState => {
+                let w = table.numbers("w")?;
+                draw_against_time(table, root, "Equation of state", "w", &[("w", &w, BLUE)], false)
+            }
+            Figure::Fractions => {
+                let [pot, rad, mat] = [table.numbers("Potential")?, table.numbers("Radiation")?,
+                                       table.numbers("Matter")?];
+                let fraction = |part: &[f64]| -> Vec<f64> {
+                    part.iter().zip(&pot).zip(rad.iter().zip(&mat))
+                        .map(|((x, v), (r, m))| x/(v + r + m))
+                        .collect()
+                };
+                let series = [("vacuum", &fraction(&pot), era_color("vacuum")),
+                              ("radiation", &fraction(&rad), era_color("radiation")),
+                              ("matter", &fraction(&mat), era_color("matter"))];
+                draw_against_time(table, root, "Energy fractions", "rho_i / rho",
+                                  &series.map(|(l, v, c)| (l, v.as_slice(), c)), false)
+            }
+            Figure::PressureVolume => draw_pressure_volume(table, root),
+        }
+    }
+}
+
+fn era_color(era: &str) -> RGBColor {
+    match era {
+        "radiation" => BLUE,
+        "matter" => RED,
+        _ => RGBColor(0, 150, 0),
+    }
+}
+
+/// Range of the finite values with a margin, never empty.
+fn bounds(values: impl Iterator<Item = f64>) -> Range<f64> {
+    let (lo, hi) = values.filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if lo > hi {
+        return 0.0..1.0;
+    }
+    let margin = if hi > lo { 0.05*(hi - lo) } else { 0.5*lo.abs().max(1.0) };
+    lo - margin..hi + margin
+}
+
+/// Range of the positive finite values, padded by a factor of 2 for log axes.
+fn log_bounds(values: impl Iterator<Item = f64>) -> Range<f64> {
+    let (lo, hi) = values.filter(|v| v.is_finite() && *v > 0.0)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(v), hi.max(v)));
+    if lo > hi { 1.0..10.0 } else { lo/2.0..hi*2.0 }
+}
+
+fn draw_phase<DB>(table: &Table, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let log = |name: &str| -> Result<Vec<f64>, PlotError> {
+        Ok(table.numbers(name)?.iter().map(|v| (v.abs() + LOG_FLOOR).log10()).collect())
+    };
+    let (phi, pot, kin) = (log("phi")?, log("Potential")?, log("Kinetic")?);
+    let eras = table.labels("Era")?;
+
+    let mut chart = ChartBuilder::on(root)
+        .caption("Quantum Gravity Plasma Field Evolution", ("sans-serif", 24))
+        .margin(20)
+        .build_cartesian_3d(bounds(phi.iter().copied()), bounds(pot.iter().copied()),
+                            bounds(kin.iter().copied()))?;
+    chart.with_projection(|mut projection| {
+        projection.yaw = 0.6;
+        projection.pitch = 0.3;
+        projection.scale = 0.8;
+        projection.into_matrix()
+    });
+    chart.configure_axes().draw()?;
+
+    for era in ["vacuum", "radiation", "matter"] {
+        let color = era_color(era);
+        let points: Vec<(f64, f64, f64)> = eras.iter().enumerate()
+            .filter(|(i, e)| **e == era && phi[*i].is_finite() && pot[*i].is_finite() && kin[*i].is_finite())
+            .map(|(i, _)| (phi[i], pot[i], kin[i]))
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        chart.draw_series(points.into_iter().map(|p| Circle::new(p, 2, color.mix(0.7).filled())))?
+            .label(format!("{} era", era))
+            .legend(move |(x, y)| Circle::new((x, y), 5, color.filled()));
+    }
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    root.draw(&Text::new("x: log10|phi|   y: log10|Potential|   z: log10|Kinetic|",
+                         (20, SIZE.1 as i32 - 30), ("sans-serif", 16)))?;
+    Ok(())
+}
+
+/// Line plot of several columns against cosmic time on a logarithmic time axis.
+fn draw_against_time<DB>(table: &Table, root: &DrawingArea<DB, Shift>, caption: &str, y_desc: &str,
+                         series: &[(&str, &[f64], RGBColor)], log_y: bool) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let t = table.numbers("t")?;
+    let points: Vec<Vec<(f64, f64)>> = series.iter()
+        .map(|(_, values, _)| t.iter().zip(values.iter())
+            .map(|(&t, &v)| (t, v))
+            .filter(|(t, v)| *t > 0.0 && t.is_finite() && v.is_finite() && (!log_y || *v > 0.0))
+            .collect())
+        .collect();
+    let x_range = log_bounds(points.iter().flatten().map(|p| p.0));
+    let y_values = || points.iter().flatten().map(|p| p.1);
+
+    let mut builder = ChartBuilder::on(root);
+    builder.caption(caption, ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(90);
+
+    // The two y scales give different chart types, hence the duplicated body.
+    macro_rules! draw {
+        ($chart:expr) => {{
+            let mut chart = $chart;
+            chart.configure_mesh()
+                .x_desc("t [GeV^-1]")
+                .y_desc(y_desc)
+                .x_label_formatter(&|x| format!("{:.0e}", x))
+                .draw()?;
+            for ((label, _, color), points) in series.iter().zip(points) {
+                let color = *color;
+                chart.draw_series(LineSeries::new(points, color.stroke_width(2)))?
+                    .label(*label)
+                    .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2)));
+            }
+            if series.len() > 1 {
+                chart.configure_series_labels()
+                    .background_style(WHITE.mix(0.8))
+                    .border_style(BLACK)
+                    .draw()?;
+            }
+        }};
+    }
+    if log_y {
+        draw!(builder.build_cartesian_2d(x_range.log_scale(), log_bounds(y_values()).log_scale())?);
+    } else {
+        draw!(builder.build_cartesian_2d(x_range.log_scale(), bounds(y_values()))?);
+    }
+    Ok(())
+}
+
+fn draw_pressure_volume<DB>(table: &Table, root: &DrawingArea<DB, Shift>) -> Result<(), Box<dyn Error>>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static,
+{
+    let points: Vec<(f64, f64)> = table.numbers("V")?.into_iter().zip(table.numbers("P")?)
+        .filter(|(v, p)| v.is_finite() && p.is_finite())
+        .collect();
+    let mut chart = ChartBuilder::on(root)
+        .caption("P-V Diagram of Carnot Cycle", ("sans-serif", 24))
+        .margin(20)
+        .x_label_area_size(50)
+        .y_label_area_size(70)
+        .build_cartesian_2d(bounds(points.iter().map(|p| p.0)), bounds(points.iter().map(|p| p.1)))?;
+    chart.configure_mesh().x_desc("Volume").y_desc("Pressure").draw()?;
+    chart.draw_series(LineSeries::new(points.iter().copied(), BLUE.stroke_width(2)))?
+        .label("Carnot cycle")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE.stroke_width(2)));
+    chart.draw_series(points.iter().map(|&p| Circle::new(p, 3, BLUE.filled())))?;
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+/// Writes one figure in one format.
+fn render(figure: Figure, table: &Table, format: Format, path: &Path) -> Result<(), Box<dyn Error>> {
+    match format {
+        Format::Png => {
+            let root = BitMapBackend::new(path, SIZE).into_drawing_area();
+            figure.draw(table, &root)?;
+            root.present()?;
+        }
+        Format::Svg => {
+            let root = SVGBackend::new(path, SIZE).into_drawing_area();
+            figure.draw(table, &root)?;
+            root.present()?;
+        }
+    }
+    Ok(())
+}
+
+/// Draws every available figure of `csv` into `out_dir`; returns the files written.
+pub fn plot(csv: &Path, out_dir: &Path, formats: &[Format]) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let table = Table::read(csv)?;
+    let figures: Vec<Figure> = Figure::ALL.into_iter().filter(|f| f.available(&table)).collect();
+    if figures.is_empty() {
+        return Err(PlotError::Data {
+            path: csv.to_path_buf(), line: 1, message: "no figure can be drawn from these columns".to_string(),
+        }.into());
+    }
+    let stem = csv.file_stem().map_or("plot".into(), |s| s.to_string_lossy());
+    let mut written = Vec::new();
+    for figure in figures {
+        for &format in formats {
+            let path = out_dir.join(format!("{}.{}.{}", stem, figure.name(), format.extension()));
+            render(figure, &table, format, &path)?;
+            written.push(path);
+        }
+    }
+    Ok(written)
+}
@@ -0,0 +1,362 @@
+// Recombination history and the acoustic scales of the photon-baryon fluid.
+//
+// The late run, from T = 1 keV until H = H0, is tabulated in e-folds; a = e^{N − N_today}
+// and 1 + z = 1/a. Photons have T from the plasma, ρ_γ = (π²/15) T⁴; baryons are the
+// fraction Ω_b0/Ω_m0 of the matter fluid, with hydrogen n_H = (1 − Y_p) ρ_b/m_H and helium
+// n_He = f_He n_H, f_He = Y_p/(4 (1 − Y_p)). Ionisation follows x_e = n_e/n_H:
+//   * Saha equilibrium of H, He I and He II while hydrogen is more than 99% ionised,
+//   * then Peebles' three-level atom for hydrogen, helium staying in Saha equilibrium:
+//       dx_H/dt = C [β (1 − x_H) e^{−E_α/T} − α_B n_H x_e x_H]
+//       C = (Λ_2s1s + Λ_α)/(Λ_2s1s + Λ_α + β),  Λ_α = 8π H/(λ_α³ n_1s) = H E_α³/(π² n_1s)
+//     with α_B the case-B coefficient (Péquignot et al. fit, RECFAST fudge 1.14) and
+//     β = α_B (m_e T/2π)^{3/2} e^{−E_2/T} the photoionisation rate from n = 2.
+// Thomson scattering gives the optical depth and visibility
+//   dτ/dN = n_e σ_T/H,  g(z) = e^{−τ} dτ/dz,
+// whose peak is the last-scattering redshift z_*. The drag epoch z_d is where the baryons'
+// own optical depth ∫ (dτ/dN)/R dN reaches 1, R = 3 ρ_b/(4 ρ_γ), and the comoving sound horizon
+//   r_s(z) = ∫_z^∞ c_s dz/H,  c_s = [3 (1 + R)]^{−1/2}
+// sets the BAO scale r_s(z_d) and the acoustic angle θ_* = r_s(z_*)/D_M(z_*). Reionisation
+// is not modelled.
+
+use std::f64::consts::PI;
+
+use crate::integrator::{self, IntegrationError, IntegrationStats, OdeSystem, StiffMethod, Tolerances};
+use crate::model::{RunningVacuum, State, MAX_EFOLDS};
+use crate::params::{ParamsError, RunConfig};
+
+/// Photon temperature where the table starts, GeV: fully ionised, deep in radiation.
+const T_TABLE: f64 = 1.0e-6;
+
+/// Resolution of the history in e-folds.
+const GRID_STEP: f64 = 1.0e-3;
+
+/// Hydrogen ionisation below which Peebles' equation takes over from Saha.
+const SAHA_LIMIT: f64 = 0.99;
+
+/// Energies in GeV: hydrogen ionisation, Lyman α, helium I and II ionisation.
+const E_ION: f64 = 13.605_693e-9;
+const E_ALPHA: f64 = 0.75*E_ION;
+const E_HE1: f64 = 24.587_4e-9;
+const E_HE2: f64 = 54.417_8e-9;
+
+/// Electron and hydrogen-atom masses, GeV.
+const ELECTRON_MASS: f64 = 0.510_998_95e-3;
+const HYDROGEN_MASS: f64 = 0.938_783;
+
+/// Two-photon decay rate of 2s in s⁻¹, ħ in GeV s and ħc in GeV cm.
+const LAMBDA_2S1S: f64 = 8.224_58;
+const HBAR: f64 = 6.582_119_569e-25;
+const HBAR_C: f64 = 1.973_269_804e-14;
+
+/// Thomson cross-section in cm², Kelvin per GeV.
+const SIGMA_THOMSON: f64 = 6.652_458_7e-25;
+const KELVIN_PER_GEV: f64 = 1.160_452e13;
+
+/// Fudge factor of the case-B coefficient matching the multi-level atom.
+const FUDGE: f64 = 1.14;
+
+/// Bisection steps of the Saha electron density.
+const BISECTIONS: usize = 100;
+
+/// Case-B recombination coefficient at T, GeV⁻².
+fn case_b(temperature: f64) -> f64 {
+    let t4 = temperature*KELVIN_PER_GEV/1.0e4;
+    let cm3_per_s = FUDGE*1.0e-13*4.309*t4.powf(-0.6166)/(1.0 + 0.6703*t4.powf(0.5300));
+    cm3_per_s*HBAR/HBAR_C.powi(3)
+}
+
+/// (m_e T/2π)^{3/2} e^{−E/T}, the Saha factor of a level bound by E.
+fn saha(temperature: f64, energy: f64) -> f64 {
+    (ELECTRON_MASS*temperature/(2.0*PI)).powf(1.5)*(-energy/temperature).exp()
+}
+
+/// Baryons and photons of the run at one e-fold.
+#[derive(Debug, Clone, Copy)]
+pub struct Plasma {
+    pub temperature: f64,
+    pub hubble: f64,
+    pub hydrogen: f64, // n_H, GeV³
+    pub baryon_to_photon: f64, // R = 3 ρ_b/(4 ρ_γ)
+}
+
+/// The late run, tabulated in e-folds.
+#[derive(Debug, Clone)]
+pub struct LateHistory {
+    n: Vec<f64>,
+    ln_hubble: Vec<f64>,
+    ln_temperature: Vec<f64>,
+    ln_rho_m: Vec<f64>,
+    pub n_today: f64,
+    pub nu: f64,
+    baryon_share: f64, // Ω_b0/Ω_m0
+    helium: f64,       // Y_p
+}
+
+impl LateHistory {
+    /// Integrates the run until H = H0, keeping the rows below `T_TABLE`; hydrogen must have
+    /// recombined by then.
+    pub fn of_run(model: &RunningVacuum, config: &RunConfig, omega_b0: f64, helium: f64) -> Result<Self, ParamsError> {
+        let params = &config.cosmology;
+        let mut history = LateHistory {
+            n: Vec::new(),
+            ln_hubble: Vec::new(),
+            ln_temperature: Vec::new(),
+            ln_rho_m: Vec::new(),
+            n_today: f64::NAN,
+            nu: params.nu,
+            baryon_share: omega_b0/params.omega_m0,
+            helium,
+        };
+        let start = model.initial_state();
+        let mut stats = IntegrationStats::default();
+        integrator::integrate(model, &config.integration.method(), start.n, &start.to_vec(), MAX_EFOLDS,
+                              &mut stats, |n, y| {
+            let state = State::from_vec(n, y);
+            let temperature = model.radiation(&state).temperature;
+            if temperature < T_TABLE {
+                history.n.push(n);
+                history.ln_hubble.push(model.hubble(state.z).ln());
+                history.ln_temperature.push(temperature.ln());
+                history.ln_rho_m.push(state.ln_rho_m);
+            }
+            model.hubble(state.z) > params.h0
+        })?;
+        let rows = history.n.len();
+        if rows < 2 || history.ln_hubble[rows - 1] > params.h0.ln() {
+            return Err(ParamsError::Invalid(format!(
+                "the run does not cool below T = {:e} GeV before H = H0 = {:e} GeV", T_TABLE, params.h0)));
+        }
+        // Today where ln H crosses ln H0 in the last step
+        let (h_a, h_b) = (history.ln_hubble[rows - 2], history.ln_hubble[rows - 1]);
+        let u = (params.h0.ln() - h_a)/(h_b - h_a);
+        history.n_today = history.n[rows - 2] + u*(history.n[rows - 1] - history.n[rows - 2]);
+        if history.saha(&history.at(history.n_today)).1 > SAHA_LIMIT {
+            return Err(ParamsError::Invalid(format!(
+                "hydrogen is still ionised today (T0 = {:.4e} K); h0 and omega_r0 of the run are far from \
+                 those of our universe", history.temperature_today()*KELVIN_PER_GEV)));
+        }
+        Ok(history)
+    }
+
+    /// Start and end of the table in e-folds.
+    pub fn span(&self) -> (f64, f64) {
+        (self.n[0], self.n_today)
+    }
+
+    /// Photon temperature today, GeV.
+    pub fn temperature_today(&self) -> f64 {
+        self.at(self.n_today).temperature
+    }
+
+    /// Baryons and photons at e-fold N, interpolated linearly in the logarithms.
+    pub fn at(&self, n: f64) -> Plasma {
+        let last = self.n.len() - 1;
+        let n = n.clamp(self.n[0], self.n[last]);
+        let i = self.n.partition_point(|&x| x <= n).clamp(1, last) - 1;
+        let u = (n - self.n[i])/(self.n[i + 1] - self.n[i]);
+        let lerp = |v: &[f64]| (v[i] + u*(v[i + 1] - v[i])).exp();
+        let temperature = lerp(&self.ln_temperature);
+        let rho_b = self.baryon_share*lerp(&self.ln_rho_m);
+        let rho_gamma = PI*PI/15.0*temperature.powi(4);
+        Plasma {
+            temperature,
+            hubble: lerp(&self.ln_hubble),
+            hydrogen: (1.0 - self.helium)*rho_b/HYDROGEN_MASS,
+            baryon_to_photon: 0.75*rho_b/rho_gamma,
+        }
+    }
+
+    /// n_He/n_H
+    fn helium_ratio(&self) -> f64 {
+        self.helium/(4.0*(1.0 - self.helium))
+    }
+
+    /// Electrons per hydrogen nucleus from helium in Saha equilibrium, given n_e.
+    fn helium_electrons(&self, plasma: &Plasma, electrons: f64) -> f64 {
+        let t = plasma.temperature;
+        // n(He II)/n(He I) and n(He III)/n(He II), statistical weights 4 and 1
+        let first = 4.0*saha(t, E_HE1)/electrons;
+        let second = saha(t, E_HE2)/electrons;
+        let (neutral, single, double) = if first.is_finite() && second.is_finite() {
+            (1.0, first, first*second)
+        } else {
+            (0.0, 0.0, 1.0)
+        };
+        self.helium_ratio()*(single + 2.0*double)/(neutral + single + double)
+    }
+
+    /// (x_e, x_H) in Saha equilibrium of hydrogen and helium.
+    pub fn saha(&self, plasma: &Plasma) -> (f64, f64) {
+        let hydrogen = |electrons: f64| {
+            let ratio = saha(plasma.temperature, E_ION)/electrons; // n_p/n_H0
+            if ratio.is_finite() { ratio/(1.0 + ratio) } else { 1.0 }
+        };
+        // x_e = x_H(n_e) + He(n_e) is decreasing in x_e: bisect on the fixed point
+        let (mut lo, mut hi) = (0.0, 1.0 + 2.0*self.helium_ratio());
+        for _ in 0..BISECTIONS {
+            let mid = 0.5*(lo + hi);
+            let electrons = mid*plasma.hydrogen;
+            if hydrogen(electrons) + self.helium_electrons(plasma, electrons) > mid {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        let x_e = 0.5*(lo + hi);
+        (x_e, hydrogen(x_e*plasma.hydrogen))
+    }
+
+    /// x_e for hydrogen ionised to x_H, helium in Saha equilibrium.
+    pub fn electrons(&self, plasma: &Plasma, x_h: f64) -> f64 {
+        let (mut lo, mut hi) = (x_h, x_h + 2.0*self.helium_ratio());
+        for _ in 0..BISECTIONS {
+            let mid = 0.5*(lo + hi);
+            if x_h + self.helium_electrons(plasma, mid*plasma.hydrogen) > mid {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        0.5*(lo + hi)
+    }
+}
+
+/// Peebles' equation for x_H in e-folds.
+struct Peebles<'a> {
+    history: &'a LateHistory,
+}
+
+impl OdeSystem for Peebles<'_> {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, n: f64, y: &[f64], dydn: &mut [f64]) {
+        let plasma = self.history.at(n);
+        let (t, h, n_h) = (plasma.temperature, plasma.hubble, plasma.hydrogen);
+        let x_h = y[0];
+        let x_e = self.history.electrons(&plasma, x_h.clamp(0.0, 1.0));
+        let alpha = case_b(t);
+        let beta = alpha*saha(t, E_ION/4.0);
+        let lyman_alpha = h*E_ALPHA.powi(3)/(PI*PI*(1.0 - x_h)*n_h);
+        let c = 1.0/(1.0 + beta/(LAMBDA_2S1S*HBAR + lyman_alpha));
+        // β e^{−E_α/T} = α (m_e T/2π)^{3/2} e^{−E_ion/T}
+        dydn[0] = c*(alpha*saha(t, E_ION)*(1.0 - x_h) - alpha*n_h*x_e*x_h)/h;
+    }
+}
+
+/// One node of the recombination history.
+#[derive(Debug, Clone, Copy)]
+pub struct IonisationPoint {
+    pub z: f64,
+    pub x_e: f64,
+    pub x_h: f64,
+    pub temperature: f64,
+    pub tau: f64,
+    pub visibility: f64, // e^{−τ} dτ/dz
+}
+
+/// Recombination history with its characteristic epochs and scales (lengths in GeV⁻¹).
+#[derive(Debug, Clone)]
+pub struct Recombination {
+    pub points: Vec<IonisationPoint>,
+    pub last_scattering: f64,       // z_* at the peak of g
+    pub drag: f64,                  // z_d
+    pub sound_horizon_drag: f64,    // r_s(z_d)
+    pub sound_horizon_star: f64,    // r_s(z_*)
+    pub angular_distance_star: f64, // comoving D_M(z_*)
+}
+
+impl Recombination {
+    pub const CSV_HEADER: &'static str = "z,x_e,x_H,T,tau,visibility";
+
+    /// θ_* = r_s(z_*)/D_M(z_*)
+    pub fn acoustic_angle(&self) -> f64 {
+        self.sound_horizon_star/self.angular_distance_star
+    }
+}
+
+/// Ionisation and the acoustic scales along the late history.
+pub fn recombine(history: &LateHistory) -> Result<Recombination, IntegrationError> {
+    let (n0, n1) = history.span();
+    let count = ((n1 - n0)/GRID_STEP).ceil() as usize + 1;
+    let grid: Vec<f64> = (0..count).map(|k| n0 + (n1 - n0)*k as f64/(count - 1) as f64).collect();
+
+    // Saha while hydrogen is ionised, then Peebles from the switch on
+    let mut ionisation: Vec<(f64, f64)> = Vec::with_capacity(count);
+    let mut switch = None;
+    for (k, &n) in grid.iter().enumerate() {
+        let (x_e, x_h) = history.saha(&history.at(n));
+        if x_h < SAHA_LIMIT {
+            switch = Some(k);
+            break;
+        }
+        ionisation.push((x_e, x_h));
+    }
+    if let Some(k) = switch {
+        let peebles = Peebles { history };
+        let method = StiffMethod { tolerances: Tolerances { rtol: 1e-6, atol: 1e-10 }, initial_step: GRID_STEP };
+        let mut stats = IntegrationStats::default();
+        let mut x_h = ionisation.last().map_or(1.0, |&(_, x_h)| x_h);
+        for pair in grid[k - 1..].windows(2) {
+            x_h = integrator::integrate_stiff(&peebles, &method, pair[0], &[x_h], pair[1], &mut stats, |_, _| true)?.1[0];
+            ionisation.push((history.electrons(&history.at(pair[1]), x_h.clamp(0.0, 1.0)), x_h));
+        }
+    }
+
+    // dτ/dN, R and 1/(a H) on the grid
+    let sigma = SIGMA_THOMSON/(HBAR_C*HBAR_C);
+    let (mut kappa, mut r, mut conformal) = (Vec::new(), Vec::new(), Vec::new());
+    for (&n, &(x_e, _)) in grid.iter().zip(&ionisation) {
+        let plasma = history.at(n);
+        kappa.push(x_e*plasma.hydrogen*sigma/plasma.hubble);
+        r.push(plasma.baryon_to_photon);
+        conformal.push((history.n_today - n).exp()/plasma.hubble);
+    }
+    // Optical depths integrated back from today, trapezoids in N
+    let mut tau = vec![0.0; count];
+    let mut tau_drag = vec![0.0; count];
+    for k in (0..count - 1).rev() {
+        let dn = grid[k + 1] - grid[k];
+        tau[k] = tau[k + 1] + 0.5*dn*(kappa[k] + kappa[k + 1]);
+        tau_drag[k] = tau_drag[k + 1] + 0.5*dn*(kappa[k]/r[k] + kappa[k + 1]/r[k + 1]);
+    }
+    // Sound horizon from the start, the radiation era before it in closed form:
+    // H ∝ a^{−2(1−ν)} gives ∫ dt/a = 1/((1 − 2ν) a H)
+    let sound = |k: usize| 1.0/(3.0*(1.0 + r[k])).sqrt();
+    let mut horizon = vec![sound(0)*conformal[0]/(1.0 - 2.0*history.nu); count];
+    for k in 1..count {
+        horizon[k] = horizon[k - 1] + 0.5*(grid[k] - grid[k - 1])*(sound(k - 1)*conformal[k - 1] + sound(k)*conformal[k]);
+    }
+
+    let points: Vec<IonisationPoint> = (0..count).map(|k| {
+        let z = (history.n_today - grid[k]).exp() - 1.0;
+        IonisationPoint {
+            z,
+            x_e: ionisation[k].0,
+            x_h: ionisation[k].1,
+            temperature: history.at(grid[k]).temperature,
+            tau: tau[k],
+            visibility: (-tau[k]).exp()*kappa[k]/(1.0 + z),
+        }
+    }).collect();
+    let star = (0..count).max_by(|&a, &b| points[a].visibility.total_cmp(&points[b].visibility)).unwrap();
+    // Drag epoch: τ_drag falls through 1 towards today
+    let drag = (0..count - 1).find(|&k| tau_drag[k] >= 1.0 && tau_drag[k + 1] < 1.0).unwrap_or(0);
+    let u = (tau_drag[drag] - 1.0)/(tau_drag[drag] - tau_drag[drag + 1]);
+    let lerp = |v: &[f64]| v[drag] + u*(v[drag + 1] - v[drag]);
+    let n_drag = lerp(&grid);
+    // Comoving distance to last scattering, ∫ dN/(a H) from N_* to today
+    let angular_distance_star = (star..count - 1)
+        .map(|k| 0.5*(grid[k + 1] - grid[k])*(conformal[k] + conformal[k + 1]))
+        .sum();
+
+    Ok(Recombination {
+        last_scattering: points[star].z,
+        drag: (history.n_today - n_drag).exp() - 1.0,
+        sound_horizon_drag: lerp(&horizon),
+        sound_horizon_star: horizon[star],
+        angular_distance_star,
+        points,
+    })
+}
@@ -0,0 +1,170 @@
+// Reheating: the decay of an oscillating condensate into the thermal radiation era.
+//
+// The run is integrated until the primeval de Sitter phase ends (ε = 1, H = H_end). The
+// vacuum energy left there, V(H_end), is handed to a condensate φ of mass m oscillating
+// about its minimum (pressureless on average); the fluids of the run are the radiation
+// already present. With decay rate Γ the Boltzmann equations in e-folds N = ln a are
+//   dρ_φ/dN = −3 ρ_φ − (Γ/H) ρ_φ − R ρ_χ ρ_φ/(ρ_φ + ρ_χ)
+//   dρ_r/dN = −3 (1+w_r) ρ_r + (Γ/H) ρ_φ
+//   dρ_χ/dN = −4 ρ_χ + R ρ_χ ρ_φ/(ρ_φ + ρ_χ)
+//   3 MP² H² = ρ_φ + ρ_r + ρ_χ
+// with w_r(T) from the plasma of `plasma.rs`. The optional parametric resonance through
+// g² φ² χ² is the broad-resonance approximation: while q = g² Φ²/(4 m²) > 1, with
+// amplitude Φ = (2 ρ_φ)^{1/2}/m, χ grows from its vacuum seed ρ_χ = m⁴/(8π²) at the Floquet
+// rate R = 2 μ m/H per e-fold, and the factor ρ_φ/(ρ_φ + ρ_χ) stops the growth once the
+// produced χ backreacts on the condensate. The χ quanta are relativistic and are counted
+// as radiation for the temperature, i.e. they are taken to thermalise at once.
+//
+// The reheating temperature is that of the radiation when H = Γ; the instantaneous-decay
+// estimate is the T at which ρ_r(T) = 3 MP² Γ², i.e. (90/(π² g*))^{1/4} (Γ MP)^{1/2}.
+
+use std::f64::consts::PI;
+
+use crate::integrator::{self, IntegrationError, IntegrationStats, Method, OdeSystem};
+use crate::model::{RunningVacuum, State, MAX_EFOLDS};
+use crate::numeric::softplus;
+use crate::plasma::Plasma;
+
+/// ρ_φ/ρ_radiation below which the condensate counts as decayed.
+const DECAYED: f64 = 1e-10;
+
+/// Upper bound on the e-folds of reheating.
+pub const MAX_REHEATING_EFOLDS: f64 = 500.0;
+
+/// ln ρ_χ − ln ρ_φ at the start without resonance, i.e. no χ at all.
+const NO_CHI: f64 = -1000.0;
+
+/// Broad parametric resonance of the condensate into χ.
+#[derive(Debug, Clone, Copy)]
+pub struct Resonance {
+    pub coupling: f64, // g of g² φ² χ²
+    pub floquet: f64,  // μ, growth rate 2 μ m of ρ_χ
+}
+
+/// Condensate, radiation and resonantly produced χ; state [ln ρ_φ, ln ρ_r, ln ρ_χ, t].
+#[derive(Debug, Clone)]
+pub struct Reheating {
+    pub mp: f64,
+    pub gamma: f64,
+    pub mass: f64,
+    pub resonance: Option<Resonance>,
+    pub plasma: Plasma,
+}
+
+impl Reheating {
+    /// H from the Friedmann equation, formed in logarithms.
+    pub fn hubble(&self, y: &[f64]) -> f64 {
+        let ln_phi_chi = y[0] + softplus(y[2] - y[0]);
+        let ln_rho = ln_phi_chi + softplus(y[1] - ln_phi_chi);
+        (0.5*(ln_rho - 3.0_f64.ln()) - self.mp.ln()).exp()
+    }
+
+    /// Resonance parameter q = g² Φ²/(4 m²) with Φ² = 2 ρ_φ/m²; 0 without resonance.
+    pub fn q(&self, y: &[f64]) -> f64 {
+        self.resonance.map_or(0.0, |r| {
+            (2.0*r.coupling.ln() + 2.0_f64.ln() + y[0] - 4.0*self.mass.ln() - 4.0_f64.ln()).exp()
+        })
+    }
+
+    /// Temperature of the radiation, ρ_r + ρ_χ.
+    pub fn temperature(&self, y: &[f64]) -> f64 {
+        self.plasma.radiation(y[1] + softplus(y[2] - y[1])).temperature
+    }
+}
+
+impl OdeSystem for Reheating {
+    fn dim(&self) -> usize {
+        4
+    }
+
+    fn derivatives(&self, _n: f64, y: &[f64], dydn: &mut [f64]) {
+        let h = self.hubble(y);
+        let decay = self.gamma/h;
+        let growth = match self.resonance {
+            Some(r) if self.q(y) > 1.0 => 2.0*r.floquet*self.mass/h,
+            _ => 0.0,
+        };
+        let phi_share = 1.0/(1.0 + (y[2] - y[0]).exp()); // ρ_φ/(ρ_φ + ρ_χ)
+        let w_r = self.plasma.radiation(y[1]).w;
+        dydn[0] = -3.0 - decay - growth*(1.0 - phi_share);
+        dydn[1] = -3.0*(1.0 + w_r) + decay*(y[0] - y[1]).exp();
+        dydn[2] = -4.0 + growth*phi_share;
+        dydn[3] = 1.0/h;
+    }
+}
+
+/// Run state where the primeval de Sitter phase ends, the first step with ε ≥ 1.
+pub fn primeval_end(model: &RunningVacuum, method: &Method) -> Result<State, IntegrationError> {
+    let start = model.initial_state();
+    let mut stats = IntegrationStats::default();
+    let (n, y) = integrator::integrate(model, method, start.n, &start.to_vec(), MAX_EFOLDS, &mut stats,
+                                       |n, y| model.epsilon(&State::from_vec(n, y)) < 1.0)?;
+    Ok(State::from_vec(n, &y))
+}
+
+/// Result of a reheating run.
+#[derive(Debug, Clone)]
+pub struct ReheatingHistory {
+    pub reheating_temperature: Option<f64>, // T when H = Γ
+    pub estimate: f64,                      // T with ρ_r(T) = 3 MP² Γ²
+    pub max_temperature: f64,
+    pub efolds: f64,                        // from H_end until the condensate has decayed
+    pub resonance_end: Option<f64>,         // e-fold where q falls below 1
+    pub chi_share: f64,                     // ρ_χ/(ρ_r + ρ_χ) at the end
+    pub rows: Vec<[f64; 9]>,
+    pub stats: IntegrationStats,
+}
+
+impl ReheatingHistory {
+    pub const CSV_HEADER: &'static str = "N,t,H,rho_phi,rho_r,rho_chi,T,gstar,q";
+}
+
+/// Initial state [ln ρ_φ, ln ρ_r, ln ρ_χ, t] at the end of the primeval phase.
+pub fn initial_state(model: &RunningVacuum, end: &State, mass: f64, resonance: bool) -> Vec<f64> {
+    let ln_phi = model.ln_critical_density(end.z) + model.vacuum_fraction(end.z).ln();
+    let ln_r = end.ln_rho_r + softplus(end.ln_rho_m - end.ln_rho_r);
+    let ln_chi = if resonance { 4.0*mass.ln() - (8.0*PI*PI).ln() } else { ln_phi + NO_CHI };
+    vec![ln_phi, ln_r, ln_chi, end.t]
+}
+
+/// Integrates the reheating stage from `y0` until the condensate has decayed.
+pub fn reheat(system: &Reheating, method: &Method, y0: &[f64]) -> Result<ReheatingHistory, IntegrationError> {
+    let mp = system.mp;
+    let estimate = system.plasma.radiation((3.0*mp*mp*system.gamma*system.gamma).ln()).temperature;
+    let mut history = ReheatingHistory {
+        reheating_temperature: None,
+        estimate,
+        max_temperature: 0.0,
+        efolds: 0.0,
+        resonance_end: None,
+        chi_share: 0.0,
+        rows: Vec::new(),
+        stats: IntegrationStats::default(),
+    };
+    let mut previous: Option<(f64, f64)> = None; // (ln H, ln T) of the last step
+    let mut stats = IntegrationStats::default();
+    let (n, y) = integrator::integrate(system, method, 0.0, y0, MAX_REHEATING_EFOLDS, &mut stats, |n, y| {
+        let (h, t, q) = (system.hubble(y), system.temperature(y), system.q(y));
+        let g_star = system.plasma.radiation(y[1] + softplus(y[2] - y[1])).g_star;
+        history.rows.push([n, y[3], h, y[0].exp(), y[1].exp(), y[2].exp(), t, g_star, q]);
+        history.max_temperature = history.max_temperature.max(t);
+        if system.resonance.is_some() && q <= 1.0 {
+            history.resonance_end.get_or_insert(n);
+        }
+        if history.reheating_temperature.is_none() && h <= system.gamma {
+            // Interpolate ln T in ln H to H = Γ
+            let ln_t = match previous {
+                Some((ln_h0, ln_t0)) => ln_t0 + (t.ln() - ln_t0)*(system.gamma.ln() - ln_h0)/(h.ln() - ln_h0),
+                None => t.ln(),
+            };
+            history.reheating_temperature = Some(ln_t.exp());
+        }
+        previous = Some((h.ln(), t.ln()));
+        let ln_radiation = y[1] + softplus(y[2] - y[1]);
+        history.reheating_temperature.is_none() || y[0] - ln_radiation > DECAYED.ln()
+    })?;
+    history.efolds = n;
+    history.chi_share = 1.0/(1.0 + (y[1] - y[2]).exp());
+    history.stats = stats;
+    Ok(history)
+}
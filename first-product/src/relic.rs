@@ -0,0 +1,241 @@
+// Thermal relic abundance of a WIMP-like species in the expansion history of the run.
+//
+// A species χ of mass m with g_χ internal states annihilates into the plasma at the thermally
+// averaged cross-section <σv> = a + b/x, x = m/T (s- and p-wave). Its yield Y = n_χ/s obeys
+//   dY/dN = −(s <σv>/H) (Y² − Y_eq²) − Y d ln(s a³)/dN
+// in e-folds N of the run, with H and T from the run and s = (2π²/45) g*s(T) T³ from the
+// plasma of `plasma.rs`; the last term vanishes while the comoving entropy is conserved and
+// carries the vacuum decay into radiation for ν > 0. In equilibrium
+//   Y_eq = 45 g_χ x² K₂(x)/(4π⁴ g*s),  x² K₂(x) ≈ (π x³/2)^{1/2} e^{−x} (1 + 15/(8x) + 105/(128x²)),
+// the asymptotic series being good to 0.5% from x = 3. Deep in equilibrium the annihilation
+// rate exceeds H by ten orders of magnitude, so the equation is solved for ln Y with the
+// implicit integrator. Once annihilation has stopped (x = x_end) the comoving number n_χ a³
+// is conserved up to today, H = H0, which gives
+//   Ω_χ h² = m n_χ0/(3 MP² H100²),  H100 = 100 km/s/Mpc.
+// Freeze-out x_f is where Y first exceeds Y_eq by a factor 2.
+
+use std::f64::consts::PI;
+
+use crate::integrator::{self, IntegrationStats, OdeSystem, StiffMethod, Tolerances};
+use crate::model::{RunningVacuum, State, MAX_EFOLDS};
+use crate::params::{ParamsError, RunConfig};
+
+/// ħ in GeV s and ħc in GeV cm, for <σv> given in cm³/s.
+const HBAR: f64 = 6.582_119_569e-25;
+const HBAR_C: f64 = 1.973_269_804e-14;
+
+/// 100 km/s/Mpc in GeV.
+const H100: f64 = 2.133_119e-42;
+
+/// Y/Y_eq that marks freeze-out.
+const FREEZE_OUT: f64 = 2.0;
+
+/// Lowest x where the asymptotic Y_eq holds.
+pub const X_MIN: f64 = 3.0;
+
+/// Mass, internal states and annihilation cross-section (GeV⁻²) of the species.
+#[derive(Debug, Clone, Copy)]
+pub struct Species {
+    pub mass: f64,
+    pub dof: f64,
+    pub s_wave: f64,
+    pub p_wave: f64,
+}
+
+impl Species {
+    /// <σv> at x = m/T, GeV⁻².
+    pub fn sigma_v(&self, x: f64) -> f64 {
+        self.s_wave + self.p_wave/x
+    }
+}
+
+/// <σv> in cm³/s as GeV⁻².
+pub fn from_cm3_per_s(sigma_v: f64) -> f64 {
+    sigma_v*HBAR/HBAR_C.powi(3)
+}
+
+/// Equilibrium yield at x for g*s.
+fn equilibrium_yield(dof: f64, g_star_s: f64, x: f64) -> f64 {
+    let x2_k2 = (0.5*PI*x.powi(3)).sqrt()*(-x).exp()*(1.0 + 15.0/(8.0*x) + 105.0/(128.0*x*x));
+    45.0*dof*x2_k2/(4.0*PI.powi(4)*g_star_s)
+}
+
+/// Hubble rate, temperature and entropy density of the run from the freeze-out window on.
+#[derive(Debug, Clone)]
+pub struct Background {
+    n: Vec<f64>,
+    ln_hubble: Vec<f64>,
+    ln_temperature: Vec<f64>,
+    ln_entropy: Vec<f64>,
+    g_star_s: Vec<f64>,
+    pub n_today: f64,
+}
+
+impl Background {
+    /// Integrates the run until H = H0, keeping the rows below `t_top`.
+    pub fn of_run(model: &RunningVacuum, config: &RunConfig, t_top: f64) -> Result<Self, ParamsError> {
+        let h0 = config.cosmology.h0;
+        let mut background = Background {
+            n: Vec::new(),
+            ln_hubble: Vec::new(),
+            ln_temperature: Vec::new(),
+            ln_entropy: Vec::new(),
+            g_star_s: Vec::new(),
+            n_today: f64::NAN,
+        };
+        let start = model.initial_state();
+        if model.radiation(&start).temperature < t_top {
+            return Err(ParamsError::Invalid(format!(
+                "the run starts below T = {:e} GeV, the top of the freeze-out window", t_top)));
+        }
+        let mut stats = IntegrationStats::default();
+        integrator::integrate(model, &config.integration.method(), start.n, &start.to_vec(), MAX_EFOLDS,
+                              &mut stats, |n, y| {
+            let state = State::from_vec(n, y);
+            let temperature = model.radiation(&state).temperature;
+            if temperature < t_top {
+                let g_star_s = model.plasma.entropy_dof(temperature);
+                background.n.push(n);
+                background.ln_hubble.push(model.hubble(state.z).ln());
+                background.ln_temperature.push(temperature.ln());
+                background.ln_entropy.push((2.0*PI*PI/45.0*g_star_s).ln() + 3.0*temperature.ln());
+                background.g_star_s.push(g_star_s);
+            }
+            model.hubble(state.z) > h0
+        })?;
+        let rows = background.n.len();
+        if rows < 2 || background.ln_hubble[rows - 1] > h0.ln() {
+            return Err(ParamsError::Invalid(format!(
+                "the run does not cool below T = {:e} GeV before H = H0 = {:e} GeV", t_top, h0)));
+        }
+        let (h_a, h_b) = (background.ln_hubble[rows - 2], background.ln_hubble[rows - 1]);
+        let u = (h0.ln() - h_a)/(h_b - h_a);
+        background.n_today = background.n[rows - 2] + u*(background.n[rows - 1] - background.n[rows - 2]);
+        Ok(background)
+    }
+
+    /// Row interval holding e-fold N and the fraction of the way through it.
+    fn bracket(&self, n: f64) -> (usize, f64) {
+        let last = self.n.len() - 1;
+        let n = n.clamp(self.n[0], self.n[last]);
+        let i = self.n.partition_point(|&x| x <= n).clamp(1, last) - 1;
+        (i, (n - self.n[i])/(self.n[i + 1] - self.n[i]))
+    }
+
+    /// (H, T, s, g*s) at e-fold N, interpolated linearly in the logarithms.
+    pub fn at(&self, n: f64) -> (f64, f64, f64, f64) {
+        let (i, u) = self.bracket(n);
+        let lerp = |v: &[f64]| v[i] + u*(v[i + 1] - v[i]);
+        (lerp(&self.ln_hubble).exp(), lerp(&self.ln_temperature).exp(), lerp(&self.ln_entropy).exp(),
+         lerp(&self.g_star_s))
+    }
+
+    /// d ln(s a³)/dN at e-fold N, zero while the comoving entropy is conserved.
+    fn entropy_injection(&self, n: f64) -> f64 {
+        let (i, _) = self.bracket(n);
+        (self.ln_entropy[i + 1] - self.ln_entropy[i])/(self.n[i + 1] - self.n[i]) + 3.0
+    }
+
+    /// The e-fold where the temperature falls to T, None outside the table.
+    pub fn n_at_temperature(&self, temperature: f64) -> Option<f64> {
+        let ln_t = temperature.ln();
+        let last = self.n.len() - 1;
+        if ln_t > self.ln_temperature[0] || ln_t < self.ln_temperature[last] {
+            return None;
+        }
+        let i = self.ln_temperature.partition_point(|&l| l > ln_t).clamp(1, last) - 1;
+        let (a, b) = (self.ln_temperature[i], self.ln_temperature[i + 1]);
+        Some(self.n[i] + (ln_t - a)/(b - a)*(self.n[i + 1] - self.n[i]))
+    }
+}
+
+/// The Boltzmann equation for ln Y in e-folds.
+pub struct Boltzmann<'a> {
+    pub species: Species,
+    background: &'a Background,
+}
+
+impl Boltzmann<'_> {
+    /// (s <σv>/H, Y_eq, d ln(s a³)/dN) at e-fold N.
+    fn rates(&self, n: f64) -> (f64, f64, f64) {
+        let (hubble, temperature, entropy, g_star_s) = self.background.at(n);
+        let x = self.species.mass/temperature;
+        (entropy*self.species.sigma_v(x)/hubble, equilibrium_yield(self.species.dof, g_star_s, x),
+         self.background.entropy_injection(n))
+    }
+}
+
+impl OdeSystem for Boltzmann<'_> {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, n: f64, y: &[f64], dydn: &mut [f64]) {
+        let (lambda, y_eq, injection) = self.rates(n);
+        dydn[0] = -lambda*(y[0].exp() - y_eq*y_eq*(-y[0]).exp()) - injection;
+    }
+
+    fn jacobian(&self, n: f64, y: &[f64], jac: &mut [f64]) -> usize {
+        let (lambda, y_eq, _) = self.rates(n);
+        jac[0] = -lambda*(y[0].exp() + y_eq*y_eq*(-y[0]).exp());
+        0
+    }
+}
+
+/// One node of the yield history.
+#[derive(Debug, Clone, Copy)]
+pub struct YieldPoint {
+    pub x: f64,
+    pub temperature: f64,
+    pub g_star_s: f64,
+    pub y: f64,
+    pub y_eq: f64,
+}
+
+/// Yield history and relic density of the species.
+#[derive(Debug, Clone)]
+pub struct Relic {
+    pub points: Vec<YieldPoint>,
+    pub freeze_out: Option<f64>, // x_f
+    pub density_today: f64,      // ρ_χ0, GeV⁴
+    pub omega_h2: f64,
+}
+
+impl Relic {
+    pub const CSV_HEADER: &'static str = "x,T,gstar_s,Y,Y_eq";
+
+    /// Yield once annihilation has stopped.
+    pub fn final_yield(&self) -> f64 {
+        self.points.last().map_or(f64::NAN, |p| p.y)
+    }
+}
+
+/// Follows the yield of `species` from x_start to x_end, starting in equilibrium, and carries
+/// the comoving number to today.
+pub fn freeze_out(species: Species, background: &Background, mp: f64, x_range: [f64; 2]) -> Result<Relic, ParamsError> {
+    let n_of = |x: f64| background.n_at_temperature(species.mass/x).ok_or_else(|| ParamsError::Invalid(format!(
+        "x = {} (T = {:e} GeV) lies outside the history of the run", x, species.mass/x)));
+    let (n_start, n_end) = (n_of(x_range[0])?, n_of(x_range[1])?);
+    let boltzmann = Boltzmann { species, background };
+    let start = equilibrium_yield(species.dof, background.at(n_start).3, x_range[0]).ln();
+
+    let method = StiffMethod { tolerances: Tolerances { rtol: 1e-6, atol: 1e-8 }, initial_step: 1e-4 };
+    let mut stats = IntegrationStats::default();
+    let mut points = Vec::new();
+    let (_, y) = integrator::integrate_stiff(&boltzmann, &method, n_start, &[start], n_end, &mut stats, |n, y| {
+        let (_, temperature, _, g_star_s) = background.at(n);
+        let x = species.mass/temperature;
+        points.push(YieldPoint { x, temperature, g_star_s, y: y[0].exp(), y_eq: equilibrium_yield(species.dof, g_star_s, x) });
+        true
+    })?;
+    let freeze_out = points.iter().find(|p| p.y >= FREEZE_OUT*p.y_eq).map(|p| p.x);
+    // n_χ a³ is conserved from x_end on
+    let (_, _, entropy, _) = background.at(n_end);
+    let density_today = species.mass*y[0].exp()*entropy*(-3.0*(background.n_today - n_end)).exp();
+    Ok(Relic {
+        points,
+        freeze_out,
+        density_today,
+        omega_h2: density_today/(3.0*mp*mp*H100*H100),
+    })
+}
@@ -0,0 +1,294 @@
+// Parameter sweeps over the vacuum-deviation ν and the scale M.
+//
+// `first-product sweep [sweep flags] [run flags]` evaluates the run on a grid or a
+// Latin-hypercube sample of (ν, M), in parallel threads, and collects one summary row
+// per run into a single table. M is always sampled uniformly in log M.
+//
+// The sample is fixed by the seed, and every row carries its run index, so an
+// interrupted sweep is resumed by re-running the same command: rows already in the
+// table are kept and only the missing runs are evaluated. The sweep parameters are
+// written next to the table and a resume with different parameters is refused.
+//
+// A run that fails (the integrator cannot meet the tolerances, say) does not stop the
+// sweep: its row keeps the point with empty metrics and the reason in the `error` column.
+// A run stopped by a value that is not finite keeps the metrics up to the stop, and the
+// column and step where it stopped in the `error` column.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::model::Component;
+use crate::params::{ParamsError, RunConfig};
+use crate::{run, RunError, RunSummary};
+
+const TABLE_HEADER: &str = "run,nu,m,primeval_duration,radiation_duration,matter_duration,\
+final_h_over_hf,max_fluid_fraction,max_residual,flagged_steps,accepted_steps,rejected_steps,error";
+
+/// How the (ν, M) plane is sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Design {
+    Grid { nu_points: usize, m_points: usize },
+    LatinHypercube { samples: usize },
+}
+
+/// A sweep: the design, the ranges, and the run configuration shared by every point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepConfig {
+    pub design: Design,
+    pub nu_range: [f64; 2],
+    pub m_range: [f64; 2],
+    pub seed: u64,
+    pub table: PathBuf,
+    pub base: RunConfig,
+    #[serde(skip)]
+    pub threads: usize,
+}
+
+impl SweepConfig {
+    /// Parses the sweep flags; everything else is handed to `RunConfig::from_args`.
+    pub fn from_args(args: Vec<String>) -> Result<Self, ParamsError> {
+        if args.iter().any(|a| a == "--help" || a == "-h") {
+            return Err(ParamsError::Usage("first-product sweep: runs over a grid or a sample of (nu, M)".to_string()));
+        }
+        let mut design = Design::Grid { nu_points: 5, m_points: 5 };
+        let mut nu_range = [0.0, 0.01];
+        let mut m_range = [1.0e8, 1.0e10];
+        let mut seed = 42;
+        let mut threads = thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let mut table = PathBuf::from("sweep.csv");
+        let mut rest = Vec::new();
+
+        let mut iter = args.into_iter();
+        while let Some(flag) = iter.next() {
+            let sweep_flag = matches!(flag.as_str(),
+                "--grid" | "--lhs" | "--nu-range" | "--m-range" | "--seed" | "--threads" | "--table");
+            if !sweep_flag {
+                rest.push(flag);
+                continue;
+            }
+            let value = iter.next()
+                .ok_or_else(|| ParamsError::Usage(format!("{} needs a value", flag)))?;
+            match flag.as_str() {
+                "--grid" => {
+                    let [nu_points, m_points] = parse_pair::<usize>(&flag, &value)?;
+                    design = Design::Grid { nu_points, m_points };
+                }
+                "--lhs" => design = Design::LatinHypercube { samples: parse_one(&flag, &value)? },
+                "--nu-range" => nu_range = parse_pair(&flag, &value)?,
+                "--m-range" => m_range = parse_pair(&flag, &value)?,
+                "--seed" => seed = parse_one(&flag, &value)?,
+                "--threads" => threads = parse_one(&flag, &value)?,
+                _ => table = PathBuf::from(value),
+            }
+        }
+
+        let config = SweepConfig {
+            design,
+            nu_range,
+            m_range,
+            seed,
+            table,
+            base: RunConfig::from_args(rest)?,
+            threads: threads.max(1),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), ParamsError> {
+        let count = match self.design {
+            Design::Grid { nu_points, m_points } => nu_points*m_points,
+            Design::LatinHypercube { samples } => samples,
+        };
+        if count == 0 {
+            return Err(ParamsError::Invalid("the sweep has no points".to_string()));
+        }
+        if !(self.m_range[0] > 0.0 && self.m_range[0] <= self.m_range[1]) {
+            return Err(ParamsError::Invalid(format!("m range {:?} must be positive and ordered", self.m_range)));
+        }
+        if self.nu_range[0] > self.nu_range[1] {
+            return Err(ParamsError::Invalid(format!("nu range {:?} must be ordered", self.nu_range)));
+        }
+        // Every point must be a valid run (0 <= ν < 1/2, HF² > 0, fluid at the start, ...)
+        for (index, (nu, m)) in self.points().into_iter().enumerate() {
+            self.point_config(nu, m).validate().map_err(|e| match e {
+                ParamsError::Invalid(msg) => ParamsError::Invalid(format!("run {} (nu = {}, m = {:e}): {}", index, nu, m, msg)),
+                e => e,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The (ν, M) points of the sweep, in run-index order.
+    pub fn points(&self) -> Vec<(f64, f64)> {
+        let [nu_lo, nu_hi] = self.nu_range;
+        let [ln_m_lo, ln_m_hi] = [self.m_range[0].ln(), self.m_range[1].ln()];
+        let nu_at = |u: f64| nu_lo + u*(nu_hi - nu_lo);
+        let m_at = |u: f64| (ln_m_lo + u*(ln_m_hi - ln_m_lo)).exp();
+
+        match self.design {
+            Design::Grid { nu_points, m_points } => {
+                let node = |i: usize, n: usize| if n > 1 { i as f64/(n - 1) as f64 } else { 0.0 };
+                let mut points = Vec::with_capacity(nu_points*m_points);
+                for i in 0..nu_points {
+                    for j in 0..m_points {
+                        points.push((nu_at(node(i, nu_points)), m_at(node(j, m_points))));
+                    }
+                }
+                points
+            }
+            Design::LatinHypercube { samples } => {
+                // One sample per stratum in each dimension, strata paired by random permutations.
+                let mut rng = StdRng::seed_from_u64(self.seed);
+                let strata = |rng: &mut StdRng| {
+                    let mut order: Vec<usize> = (0..samples).collect();
+                    order.shuffle(rng);
+                    order.into_iter()
+                        .map(|k| (k as f64 + rng.gen::<f64>())/samples as f64)
+                        .collect::<Vec<f64>>()
+                };
+                let nu_u = strata(&mut rng);
+                let m_u = strata(&mut rng);
+                nu_u.into_iter().zip(m_u).map(|(a, b)| (nu_at(a), m_at(b))).collect()
+            }
+        }
+    }
+
+    fn point_config(&self, nu: f64, m: f64) -> RunConfig {
+        let mut config = self.base.clone();
+        config.cosmology.nu = nu;
+        config.cosmology.m = m;
+        config
+    }
+
+    /// The sweep parameters next to the table.
+    pub fn sidecar_path(&self) -> PathBuf {
+        self.table.with_extension("params.toml")
+    }
+}
+
+fn parse_one<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, ParamsError> {
+    value.trim().parse()
+        .map_err(|_| ParamsError::Usage(format!("{} got an invalid value '{}'", flag, value)))
+}
+
+fn parse_pair<T: std::str::FromStr>(flag: &str, value: &str) -> Result<[T; 2], ParamsError> {
+    let mut parts = value.split(',');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(a), Some(b), None) => Ok([parse_one(flag, a)?, parse_one(flag, b)?]),
+        _ => Err(ParamsError::Usage(format!("{} expects two comma-separated values, got '{}'", flag, value))),
+    }
+}
+
+/// The table row of run `index` at (ν, M): its metrics, or empty metrics and the error.
+/// A run stopped by a value that is not finite also has the error.
+fn table_row(index: usize, nu: f64, m: f64, outcome: &Result<RunSummary, RunError>) -> String {
+    match outcome {
+        Ok(summary) => format!("{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                               index, nu, m, summary.primeval_duration(),
+                               summary.duration(Component::Radiation), summary.duration(Component::Matter),
+                               summary.final_h_over_hf, summary.max_fluid_fraction, summary.max_residual,
+                               summary.flagged, summary.stats.accepted, summary.stats.rejected,
+                               summary.non_finite.map_or(String::new(), |(step, column)| format!(
+                                   "{} is not finite at step {}; the run stopped there", column, step))),
+        Err(e) => format!("{},{},{},,,,,,,,,,{}", index, nu, m, e.to_string().replace([',', '\n'], ";")),
+    }
+}
+
+/// Complete rows of an existing table, by run index. A partially written last line
+/// (interrupted sweep), which lacks its newline, is dropped.
+fn read_finished(path: &Path) -> std::io::Result<BTreeMap<usize, String>> {
+    let columns = TABLE_HEADER.split(',').count();
+    let mut rows = BTreeMap::new();
+    for line in fs::read_to_string(path)?.split_inclusive('\n').skip(1) {
+        let Some(line) = line.strip_suffix('\n') else { continue };
+        if line.split(',').count() != columns {
+            continue;
+        }
+        if let Ok(index) = line.split(',').next().unwrap_or("").parse::<usize>() {
+            rows.insert(index, line.to_string());
+        }
+    }
+    Ok(rows)
+}
+
+fn write_table(path: &Path, rows: &BTreeMap<usize, String>) -> std::io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "{}", TABLE_HEADER)?;
+    for row in rows.values() {
+        writeln!(file, "{}", row)?;
+    }
+    Ok(())
+}
+
+/// Opens the table of `config` for its `runs` runs: keeps the rows of a table left by the
+/// same sweep, refuses one from another sweep, and writes the sidecar. Returns the rows
+/// kept, by run index.
+pub fn resume_table(config: &SweepConfig, runs: usize) -> Result<BTreeMap<usize, String>, Box<dyn std::error::Error>> {
+    let mut finished = BTreeMap::new();
+    if config.table.exists() {
+        let previous: Option<SweepConfig> = fs::read_to_string(config.sidecar_path()).ok()
+            .and_then(|text| toml::from_str(&text).ok());
+        if previous.as_ref() != Some(&SweepConfig { threads: 0, ..config.clone() }) {
+            return Err(format!("{} was produced by a different sweep; remove it or choose another --table",
+                               config.table.display()).into());
+        }
+        finished = read_finished(&config.table)?;
+        finished.retain(|index, _| *index < runs);
+    }
+    fs::write(config.sidecar_path(),
+              format!("# Sweep that produced {}\n\n{}", config.table.display(), toml::to_string(config)?))?;
+    write_table(&config.table, &finished)?;
+    Ok(finished)
+}
+
+/// Runs the `points` missing from `finished` on `config.threads` workers and appends each
+/// row to the table as soon as its run ends; `report` sees every run with its outcome, in
+/// the order they end. The table is then rewritten in run order so that it is deterministic.
+pub fn run_missing<F>(config: &SweepConfig, points: &[(f64, f64)], finished: &mut BTreeMap<usize, String>,
+                      mut report: F) -> std::io::Result<()>
+where
+    F: FnMut(usize, &Result<RunSummary, RunError>),
+{
+    let pending: Vec<usize> = (0..points.len()).filter(|i| !finished.contains_key(i)).collect();
+    // Workers pull the next pending index and hand the outcome to this thread.
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    let mut table = OpenOptions::new().append(true).open(&config.table)?;
+    thread::scope(|scope| -> std::io::Result<()> {
+        for _ in 0..config.threads.min(pending.len()) {
+            let sender = sender.clone();
+            let (next, pending, config) = (&next, &pending, &config);
+            scope.spawn(move || {
+                while let Some(&index) = pending.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    let (nu, m) = points[index];
+                    if sender.send((index, run(&config.point_config(nu, m), None))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+        for (index, outcome) in receiver {
+            let (nu, m) = points[index];
+            let row = table_row(index, nu, m, &outcome);
+            writeln!(table, "{}", row)?;
+            table.flush()?;
+            finished.insert(index, row);
+            report(index, &outcome);
+        }
+        Ok(())
+    })?;
+    write_table(&config.table, finished)
+}
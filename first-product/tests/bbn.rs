@@ -0,0 +1,83 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{first_product, our_universe, read_table, scratch, OUR_UNIVERSE};
+use first_product::bbn::{self, Abundances, ExpansionHistory, Network, RateTable};
+use first_product::model::RunningVacuum;
+
+/// The MeV era of our universe with the running `nu` and the yields at each of `etas`.
+fn bbn(nu: &str, etas: &[f64]) -> (ExpansionHistory, Vec<Abundances>) {
+    let config = our_universe(&["--nu", nu]);
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let expansion = ExpansionHistory::of_run(&model, &config).unwrap();
+    assert!(expansion.matter_ratio.iter().all(|&r| r < 1e-3), "not a radiation era");
+    let rates = RateTable::bundled().unwrap();
+    let yields = etas.iter()
+        .map(|&eta| Network::new(eta, &expansion, &model.plasma, &rates).evolve().unwrap())
+        .collect();
+    (expansion, yields)
+}
+
+#[test]
+fn standard_expansion_reproduces_the_light_elements() {
+    let (_, yields) = bbn("0", &[6.1e-10]);
+    let a = yields[0];
+    assert!((0.238..0.248).contains(&a.helium), "Y_p = {}", a.helium);
+    assert!((2.3e-5..2.9e-5).contains(&a.deuterium), "D/H = {}", a.deuterium);
+    assert!((0.9e-5..1.2e-5).contains(&a.helium3), "3He/H = {}", a.helium3);
+    assert!((3e-10..6e-10).contains(&a.lithium7), "7Li/H = {}", a.lithium7);
+    assert!(!a.excluded(), "chi2 = {}", a.chi2());
+}
+
+#[test]
+fn yields_follow_the_baryon_density() {
+    let (_, yields) = bbn("0", &[3e-10, 4.5e-10, 6.7e-10, 1e-9]);
+    for pair in yields.windows(2) {
+        // More baryons burn deuterium further and start fusion earlier, with more neutrons left
+        assert!(pair[1].helium > pair[0].helium && pair[1].deuterium < pair[0].deuterium, "{:?}", pair);
+    }
+}
+
+#[test]
+fn helium_and_deuterium_bound_the_running_vacuum() {
+    let (_, standard) = bbn("0", &[6.1e-10]);
+    let etas = [5e-10, 5.9e-10, 7e-10];
+    let (expansion, running) = bbn("0.1", &etas);
+
+    // The vacuum decaying into radiation slows the cooling to (1 − ν)^{1/2} H_r, and
+    // neutrons decay for longer
+    let cooling = expansion.cooling_at(5e-3);
+    assert!((cooling - 0.9_f64.sqrt()).abs() < 0.01, "-dlnT/dt = {} H_r", cooling);
+    assert!(running[1].helium < standard[0].helium - 0.005, "Y_p = {} vs {}", running[1].helium, standard[0].helium);
+    let (eta, best) = bbn::best_fit(&etas, &running);
+    assert!((etas[0]..=etas[2]).contains(&eta));
+    assert!(best.excluded(), "chi2 = {} at eta = {:e}", best.chi2(), eta);
+}
+
+#[test]
+fn bbn_writes_the_yields_of_each_eta() {
+    let dir = scratch("bbn-table");
+    let table = dir.join("scan.csv");
+    let table_arg = table.display().to_string();
+    let args = [&["bbn", "--nu", "0", "--eta", "3e-10,1e-9", "--points", "4", "--table", &table_arg][..],
+                &OUR_UNIVERSE].concat();
+    let output = first_product(&args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let (header, rows) = read_table(&table);
+    assert_eq!(header.join(","), Abundances::CSV_HEADER);
+    assert_eq!(rows.len(), 4);
+    assert!((rows[3][0] - 1e-9).abs() < 1e-21, "{:?}", rows[3]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rate_table_must_list_every_reaction() {
+    let err = RateTable::parse("T9,p+n->d+g\n0.1,4.5e4\n1.0,4.4e4\n", Path::new("rates.csv")).unwrap_err();
+    assert!(err.to_string().contains("rates.csv:1: missing column 'd+p->3He+g'"), "{}", err);
+
+    let output = first_product(&["bbn", "--rates", "missing.csv", "--table", "unused.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("missing.csv"));
+}
@@ -0,0 +1,59 @@
+// Fixtures shared by the integration tests; each test binary uses only some of them.
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::OnceLock;
+
+use first_product::model::RunningVacuum;
+use first_product::params::{CosmologyParams, PlasmaParams, RunConfig};
+
+/// Present-day expansion and radiation of our universe. The default run is far from them;
+/// with these flags it reaches a radiation era at BBN, recombines and ends at H0.
+pub const OUR_UNIVERSE: [&str; 4] = ["--h0", "1.44e-42", "--omega-r0", "9.1e-5"];
+
+/// The model under `params`; the plasma table is built once and shared.
+pub fn model(params: CosmologyParams) -> RunningVacuum {
+    static BASE: OnceLock<RunningVacuum> = OnceLock::new();
+    let base = BASE.get_or_init(|| RunningVacuum::new(&CosmologyParams::default(), &PlasmaParams::default()).unwrap());
+    RunningVacuum { params, plasma: base.plasma.clone() }
+}
+
+/// Fresh scratch directory for the files of one test.
+pub fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("first-product-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs the binary with `args`.
+pub fn first_product(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_first-product")).args(args).output().unwrap()
+}
+
+/// The run configuration of the command-line flags.
+pub fn config(flags: &[&str]) -> RunConfig {
+    RunConfig::from_args(flags.iter().map(|f| f.to_string())).unwrap()
+}
+
+/// The run configuration of our universe with further flags.
+pub fn our_universe(flags: &[&str]) -> RunConfig {
+    config(&[&OUR_UNIVERSE[..], flags].concat())
+}
+
+/// A CSV table as (header, rows); non-numeric fields read as NaN.
+pub fn read_table(path: &Path) -> (Vec<String>, Vec<Vec<f64>>) {
+    let text = fs::read_to_string(path).unwrap();
+    let mut lines = text.lines();
+    let header: Vec<String> = lines.next().unwrap().split(',').map(String::from).collect();
+    let rows = lines.map(|l| l.split(',').map(|v| v.parse().unwrap_or(f64::NAN)).collect()).collect();
+    (header, rows)
+}
+
+/// The column `name` of a table.
+pub fn column(header: &[String], rows: &[Vec<f64>], name: &str) -> Vec<f64> {
+    let c = header.iter().position(|h| h == name).unwrap();
+    rows.iter().map(|r| r[c]).collect()
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use common::model;
+use first_product::integrator::{IntegrationStats, Method};
+use first_product::model::{self, Component, Cosmology, Era, FieldPoint, RunningVacuum};
+use first_product::params::{CosmologyParams, IntegrationParams, PlasmaModel, PlasmaParams};
+use proptest::prelude::*;
+
+/// Default parameters with ν, M = e^{ln_m} and HI = e^{ln_hi}.
+fn params(nu: f64, ln_m: f64, ln_hi: f64) -> CosmologyParams {
+    CosmologyParams { nu, m: ln_m.exp(), hi: ln_hi.exp(), ..CosmologyParams::default() }
+}
+
+/// |a/b − 1|
+fn relative(a: f64, b: f64) -> f64 {
+    (a/b - 1.0).abs()
+}
+
+#[test]
+fn radiation_era_kinetic_to_potential_ratio() {
+    // ρ_k/V = (1−ν)(αφ)^4/[1+ν(αφ)^4] while HF is negligible
+    let cosmology = model(CosmologyParams::default());
+    let (nu, alpha) = (0.001, model::alpha_param(&CosmologyParams::default()));
+    for x in [1e-3_f64, 0.1, 1.0, 10.0, 1e3] {
+        let phi = x.powf(0.25)/alpha;
+        let ratio = cosmology.kinetic_of_phi(Era::Radiation, phi)/cosmology.potential_of_phi(Era::Radiation, phi);
+        assert!(relative(ratio, (1.0 - nu)*x/(1.0 + nu*x)) < 1e-9, "x = {}: {}", x, ratio);
+    }
+}
+
+#[test]
+fn radiation_era_potential_matches_the_appendix() {
+    // V(φ) = VI [1+(αφ)^4]^-2 [1+ν(αφ)^4], VI = 3 MP² HI²
+    let p = CosmologyParams::default();
+    let cosmology = model(p);
+    let vi = 3.0*p.mp.powi(2)*p.hi.powi(2);
+    for x in [1e-2_f64, 1.0, 1e2] {
+        let phi = x.powf(0.25)/model::alpha_param(&p);
+        let expected = vi*(1.0 + p.nu*x)/(1.0 + x).powi(2);
+        assert!(relative(cosmology.potential_of_phi(Era::Radiation, phi), expected) < 1e-12, "x = {}", x);
+    }
+}
+
+#[test]
+fn matter_era_kinetic_to_potential_ratio() {
+    // ρ_k/V = (1−ν)(σφ)^-3/[1+ν(σφ)^-3] while H ≪ HI
+    let p = CosmologyParams::default();
+    let cosmology = model(p);
+    let sigma = model::sigma_param(&p);
+    for y in [1e-3_f64, 0.1, 1.0, 10.0, 1e3] {
+        let phi = y.powf(-1.0/3.0)/sigma;
+        let ratio = cosmology.kinetic_of_phi(Era::Matter, phi)/cosmology.potential_of_phi(Era::Matter, phi);
+        assert!(relative(ratio, (1.0 - p.nu)*y/(1.0 + p.nu*y)) < 1e-9, "y = {}: {}", y, ratio);
+    }
+}
+
+#[test]
+fn radiation_era_solution_is_the_early_history() {
+    // With a conformal plasma the integrated H follows H(φ) of the radiation era, φ ∝ a^{1−ν}
+    let p = CosmologyParams::default();
+    let plasma = PlasmaParams { model: PlasmaModel::Conformal, ..PlasmaParams::default() };
+    let cosmology = RunningVacuum::new(&p, &plasma).unwrap();
+    let mut stats = IntegrationStats::default();
+    let mut checked = 0;
+    let end = cosmology.evolve(&IntegrationParams::default().method(), &mut stats, |state| {
+        let (radiation, matter) = cosmology.fluid_fractions(state);
+        if matter > 1e-6*radiation {
+            return false;
+        }
+        let expected = cosmology.hubble_of_phi(Era::Radiation, cosmology.phi(state.n));
+        assert!(relative(cosmology.hubble(state.z), expected) < 1e-6, "N = {}", state.n);
+        checked += 1;
+        true
+    }).unwrap();
+    assert!(checked > 10 && cosmology.dominant(&end) == Component::Radiation, "{} steps", checked);
+    assert!(stats.accepted + 1 >= checked);
+}
+
+#[test]
+fn field_stays_on_the_era_solution_with_every_method() {
+    // From the primeval de Sitter phase, (αφ)^4 = 1e-8, into radiation, (αφ)^4 = 1e8
+    let p = CosmologyParams::default();
+    let cosmology = model(p);
+    let alpha = model::alpha_param(&p);
+    let start = FieldPoint { t: 0.0, phi: 1e-2/alpha, phidot: cosmology.phidot_of_phi(Era::Radiation, 1e-2/alpha) };
+    let methods = [
+        (IntegrationParams::default().method(), 1e-6),
+        (Method::Rk4 { step: 1e-2 }, 1e-6),
+        (Method::Leapfrog { fraction: 1e-3 }, 1e-3),
+    ];
+    let mut times = Vec::new();
+    for (method, tolerance) in methods {
+        let mut stats = IntegrationStats::default();
+        let mut worst = 0.0_f64;
+        let end = cosmology.evolve_field(Era::Radiation, &method, start, 1e2/alpha, &mut stats, |point| {
+            worst = worst.max(relative(point.phidot, cosmology.phidot_of_phi(Era::Radiation, point.phi)));
+            true
+        }).unwrap();
+        assert!(worst < tolerance, "{:?}: φ̇ off by {:e}", method, worst);
+        assert!(end.phi >= (1.0 - 1e-12)*1e2/alpha && stats.accepted > 0, "{:?}: {:?}", method, end);
+        // t at φ_end: the symplectic steps overshoot φ_end by up to one step of ~1e-3 e-folds
+        times.push(end.t*(1e2/alpha/end.phi).powi(2));
+    }
+    for t in &times[1..] {
+        assert!(relative(*t, times[0]) < 1e-2, "{:?}", times);
+    }
+}
+
+proptest! {
+    #[test]
+    fn fluids_and_vacuum_close_the_friedmann_equation(nu in 0.0..0.1_f64, ln_m in 5.0..30.0_f64, ln_x in -5.0..5.0_f64) {
+        let p = params(nu, ln_m, 42.0);
+        let cosmology = model(p);
+        let phi = (0.25*ln_x - model::ln_alpha(&p)).exp();
+        let h = cosmology.hubble_of_phi(Era::Radiation, phi);
+        let total = cosmology.kinetic_of_phi(Era::Radiation, phi) + cosmology.potential_of_phi(Era::Radiation, phi);
+        prop_assert!(relative(total, 3.0*p.mp.powi(2)*h*h) < 1e-12);
+    }
+
+    #[test]
+    fn alpha_follows_the_appendix(nu in 0.0..0.4_f64, ln_m in 0.0..40.0_f64, ln_hi in 10.0..42.0_f64) {
+        // α = ((1−ν)^3 (1−2ν))^{1/4} HI/(2^{1/4} MP M)
+        let p = params(nu, ln_m, ln_hi);
+        let expected = ((1.0 - nu).powi(3)*(1.0 - 2.0*nu)).powf(0.25)*p.hi/(2.0_f64.powf(0.25)*p.mp*p.m);
+        prop_assert!(relative(model::alpha_param(&p), expected) < 1e-12);
+    }
+
+    #[test]
+    fn hubble_derivative_matches_finite_differences(nu in 0.0..0.1_f64, ln_m in 5.0..30.0_f64, ln_x in -4.0..4.0_f64,
+                                                     matter in any::<bool>()) {
+        let p = params(nu, ln_m, 42.0);
+        let cosmology = model(p);
+        let (era, phi) = if matter {
+            (Era::Matter, (-ln_x/3.0).exp()/model::sigma_param(&p))
+        } else {
+            (Era::Radiation, (0.25*ln_x - model::ln_alpha(&p)).exp())
+        };
+        let step = 1e-5*phi;
+        let central = (cosmology.hubble_of_phi(era, phi + step) - cosmology.hubble_of_phi(era, phi - step))/(2.0*step);
+        prop_assert!(relative(cosmology.dhubble_dphi(era, phi), central) < 1e-6);
+    }
+
+    #[test]
+    fn field_velocity_and_acceleration_follow_the_expansion(nu in 0.0..0.1_f64, ln_x in -4.0..4.0_f64) {
+        // φ̇ = (1−ν) φ H, and φ̈ = d(φ̇)/dφ φ̇ from the chain rule
+        let p = params(nu, 20.0, 42.0);
+        let cosmology = model(p);
+        let phi = (0.25*ln_x - model::ln_alpha(&p)).exp();
+        let h = cosmology.hubble_of_phi(Era::Radiation, phi);
+        let phidot = cosmology.phidot_of_phi(Era::Radiation, phi);
+        prop_assert!(relative(phidot, (1.0 - nu)*phi*h) < 1e-14);
+        let step = 1e-5*phi;
+        let slope = (cosmology.phidot_of_phi(Era::Radiation, phi + step)
+            - cosmology.phidot_of_phi(Era::Radiation, phi - step))/(2.0*step);
+        let phiddot = cosmology.phiddot_of_phi(Era::Radiation, phi);
+        prop_assert!((phiddot - slope*phidot).abs() <= 1e-6*(slope*phidot).abs().max(phidot*h));
+    }
+}
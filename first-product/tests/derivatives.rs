@@ -0,0 +1,93 @@
+mod common;
+
+use common::model;
+use first_product::dual::Dual;
+use first_product::model::{self, Cosmology, Era};
+use first_product::params::CosmologyParams;
+use proptest::prelude::*;
+
+/// φ across an era: (αφ)^4 or (σφ)^-3 from 1e-8 to 1e8.
+fn phi_range(p: &CosmologyParams, era: Era) -> Vec<f64> {
+    (-16..=16).map(|k| {
+        let x = 10.0_f64.powf(k as f64/2.0);
+        match era {
+            Era::Radiation => x.powf(0.25)/model::alpha_param(p),
+            Era::Matter => x.powf(-1.0/3.0)/model::sigma_param(p),
+        }
+    }).collect()
+}
+
+/// Central difference of `f` at φ with a relative step.
+fn central(f: impl Fn(f64) -> f64, phi: f64) -> f64 {
+    let step = 1e-6*phi;
+    (f(phi + step) - f(phi - step))/(2.0*step)
+}
+
+/// Agreement of two derivatives to `rtol` relative to the larger, plus `atol` absolute.
+fn close(a: f64, b: f64, rtol: f64, atol: f64) -> bool {
+    (a - b).abs() <= rtol*a.abs().max(b.abs()) + atol
+}
+
+#[test]
+fn dual_numbers_follow_the_rules_of_differentiation() {
+    let x = Dual::variable(1.7);
+    let f = (x*x.exp() + 3.0)/(2.0 - x.ln()).sqrt() - x.powi(-3)*x.powf(0.25);
+    let g = |x: f64| (x*x.exp() + 3.0)/(2.0 - x.ln()).sqrt() - x.powi(-3)*x.powf(0.25);
+    assert_eq!(f.value, g(1.7));
+    assert!(close(f.derivative, central(g, 1.7), 1e-8, 0.0), "{} vs {}", f.derivative, central(g, 1.7));
+    assert_eq!(Dual::constant(2.0).exp().derivative, 0.0);
+    assert_eq!((-x).derivative, -1.0);
+}
+
+#[test]
+fn hand_derivatives_match_ad_across_the_eras() {
+    let p = CosmologyParams::default();
+    let cosmology = model(p);
+    for era in [Era::Radiation, Era::Matter] {
+        for phi in phi_range(&p, era) {
+            let h = cosmology.hubble_of_phi(era, phi);
+            let (hand, ad) = (cosmology.dhubble_dphi(era, phi), cosmology.dhubble_dphi_ad(era, phi));
+            assert!(close(hand, ad, 1e-12, 0.0), "{:?} φ = {:e}: dH/dφ {:e} by hand, {:e} by AD", era, phi, hand, ad);
+
+            let (hand, ad) = (cosmology.dphidot_dphi(era, phi), cosmology.dphidot_dphi_ad(era, phi));
+            // H + φ dH/dφ cancels at (αφ)^4 = 1, so the tolerance there is set by H itself
+            assert!(close(hand, ad, 1e-12, 1e-12*h), "{:?} φ = {:e}: dφ̇/dφ {:e} by hand, {:e} by AD", era, phi, hand, ad);
+        }
+    }
+}
+
+#[test]
+fn potential_slope_is_the_chain_rule_through_h() {
+    // dV/dφ = V'(H) dH/dφ, V'(H) = 3 MP² [2ν H + 4(1−ν) H³/HI²]
+    let p = CosmologyParams::default();
+    let cosmology = model(p);
+    for era in [Era::Radiation, Era::Matter] {
+        for phi in phi_range(&p, era) {
+            let h = cosmology.hubble_of_phi(era, phi);
+            let dual = cosmology.potential_dual(Dual::constant(h)).value;
+            assert!(close(dual, cosmology.potential(h), 1e-15, 0.0), "V({:e}) = {:e} on duals", h, dual);
+
+            let v_prime = 3.0*p.mp.powi(2)*(2.0*p.nu*h + 4.0*(1.0 - p.nu)*h.powi(3)/p.hi.powi(2));
+            let hand = v_prime*cosmology.dhubble_dphi(era, phi);
+            let ad = cosmology.dpotential_dphi_ad(era, phi);
+            assert!(close(hand, ad, 1e-12, 0.0), "{:?} φ = {:e}: dV/dφ {:e} by hand, {:e} by AD", era, phi, hand, ad);
+        }
+    }
+}
+
+proptest! {
+    #[test]
+    fn derivatives_agree_for_any_parameters(nu in 0.0..0.1_f64, ln_m in 5.0..30.0_f64, ln_x in -8.0..8.0_f64,
+                                            matter in any::<bool>()) {
+        let p = CosmologyParams { nu, m: ln_m.exp(), ..CosmologyParams::default() };
+        let cosmology = model(p);
+        let (era, phi) = if matter {
+            (Era::Matter, (-ln_x/3.0).exp()/model::sigma_param(&p))
+        } else {
+            (Era::Radiation, (0.25*ln_x - model::ln_alpha(&p)).exp())
+        };
+        prop_assert!(close(cosmology.dhubble_dphi(era, phi), cosmology.dhubble_dphi_ad(era, phi), 1e-12, 0.0));
+        let h = cosmology.hubble_of_phi(era, phi);
+        prop_assert!(close(cosmology.dphidot_dphi(era, phi), cosmology.dphidot_dphi_ad(era, phi), 1e-12, 1e-12*h));
+    }
+}
@@ -0,0 +1,35 @@
+use first_product::diagnostics::Diagnostics;
+use first_product::model::{RunningVacuum, State};
+use first_product::numeric::Precision;
+use first_product::params::{CosmologyParams, PlasmaParams};
+
+/// The default start with both fluids scaled so that Ω_r + Ω_m + Ω_V − 1 grows by `excess`.
+fn overfilled(model: &RunningVacuum, excess: f64) -> State {
+    let state = model.initial_state();
+    let (omega_r, omega_m) = model.fluid_fractions(&state);
+    let shift = (excess/(omega_r + omega_m)).ln_1p();
+    State { ln_rho_r: state.ln_rho_r + shift, ln_rho_m: state.ln_rho_m + shift, ..state }
+}
+
+#[test]
+fn residuals_above_the_threshold_are_flagged() {
+    let model = RunningVacuum::new(&CosmologyParams::default(), &PlasmaParams::default()).unwrap();
+    for precision in [Precision::Double, Precision::Exact] {
+        let closed = Diagnostics::compute(&model, &model.initial_state(), 1e-10, precision);
+        assert!(closed.friedmann_residual.abs() < 1e-12 && !closed.flagged, "{:?}", closed);
+        assert_eq!(closed.q, closed.epsilon - 1.0);
+
+        // A state off the constraint by 1e-4 passes a looser threshold and is flagged by a tighter one
+        let state = overfilled(&model, 1e-4);
+        for (threshold, flagged) in [(1e-2, false), (1e-6, true)] {
+            let diag = Diagnostics::compute(&model, &state, threshold, precision);
+            assert!((diag.friedmann_residual/1e-4 - 1.0).abs() < 1e-6, "{:?}", diag);
+            assert_eq!(diag.flagged, flagged, "threshold {:e}: {:?}", threshold, diag);
+        }
+    }
+
+    // A residual that is not a number is never below the threshold
+    let broken = State { ln_rho_r: f64::NAN, ..model.initial_state() };
+    let diag = Diagnostics::compute(&model, &broken, f64::INFINITY, Precision::Double);
+    assert!(diag.friedmann_residual.is_nan() && diag.flagged);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+use common::{first_product, read_table, scratch};
+use first_product::fit::{self, FitModel, Likelihood, SamplerSettings};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn data_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("data").join(name)
+}
+
+/// The synthetic supernovae and chronometers, made with ν = 0.01, Ω_m0 = 0.3 and H0 = 70.
+fn synthetic() -> Likelihood {
+    Likelihood {
+        supernovae: fit::read_table(&data_path("synthetic_sn.csv"), "mu").unwrap(),
+        chronometers: fit::read_table(&data_path("synthetic_hz.csv"), "H").unwrap(),
+        ..Likelihood::default()
+    }
+}
+
+#[test]
+fn fit_recovers_synthetic_truth() {
+    let likelihood = synthetic();
+    let settings = SamplerSettings { walkers: 16, steps: 400, burn: 200, seed: 3 };
+    let rvm = fit::fit(&likelihood, FitModel::RunningVacuum, &settings).unwrap();
+    assert_eq!(rvm.model.parameter_names(), ["nu", "omega_m0", "h0"]);
+    let (nu, h0) = (rvm.marginals[0].mean, rvm.marginals[2].mean);
+    assert!((h0 - 70.0).abs() < 2.5, "running vacuum H0 = {}", h0);
+    assert!((0.0..=0.1).contains(&nu), "nu = {}", nu);
+
+    // ν = 0.01 ≈ 0: ΛCDM must recover Ω_m0 and H0, with one parameter less to pay for
+    let lcdm = fit::fit(&likelihood, FitModel::LambdaCdm, &settings).unwrap();
+    assert_eq!(lcdm.model.parameter_names(), ["omega_m0", "h0"]);
+    let (omega_m0, h0) = (lcdm.marginals[0].mean, lcdm.marginals[1].mean);
+    assert!((omega_m0 - 0.3).abs() < 0.06, "lcdm omega_m0 = {}", omega_m0);
+    assert!((h0 - 70.0).abs() < 2.5, "lcdm H0 = {}", h0);
+    assert!((lcdm.aic - lcdm.chi2_min - 4.0).abs() < 1e-12 && (rvm.aic - rvm.chi2_min - 6.0).abs() < 1e-12);
+
+    // Every step of every walker is written
+    let dir = scratch("fit-chain");
+    let path = dir.join("synthetic.lcdm.chain.csv");
+    fit::write_chain(&path, &lcdm).unwrap();
+    let (header, rows) = read_table(&path);
+    assert_eq!(header, ["step", "walker", "omega_m0", "h0", "log_posterior"]);
+    assert_eq!(rows.len(), 400*16);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn fit_is_reproducible_for_a_seed() {
+    let likelihood = Likelihood { supernovae: Vec::new(), ..synthetic() };
+    let settings = SamplerSettings { walkers: 8, steps: 50, burn: 25, seed: 7 };
+    let chain = || fit::fit(&likelihood, FitModel::LambdaCdm, &settings).unwrap().chain.positions;
+    assert_eq!(chain(), chain());
+}
+
+#[test]
+fn sampler_fails_instead_of_hanging() {
+    // Ω_m0 > 1 leaves E² < 0 today: no walker can start in the box
+    let likelihood = Likelihood { omega_m_prior: [1.0, 2.0], ..synthetic() };
+    let settings = SamplerSettings { walkers: 8, steps: 50, burn: 25, seed: 7 };
+    let err = fit::fit(&likelihood, FitModel::LambdaCdm, &settings).unwrap_err().to_string();
+    assert!(err.contains("no point of finite posterior"), "{}", err);
+
+    // The stretch move pairs each walker with another one
+    let err = fit::ensemble_sample(|_| 0.0, vec![vec![0.5]], 10, &mut StdRng::seed_from_u64(1)).unwrap_err();
+    assert!(err.to_string().contains("at least 2 walkers"), "{}", err);
+}
+
+#[test]
+fn fit_rejects_missing_and_malformed_data() {
+    let output = first_product(&["fit", "--walkers", "8"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no data"));
+
+    let dir = scratch("fit-malformed");
+    let bad = dir.join("bad.csv");
+    fs::write(&bad, "z,mu,sigma\n0.1,38.3,0.1\n0.2,oops,0.1\n").unwrap();
+    let err = fit::read_table(&bad, "mu").unwrap_err().to_string();
+    assert!(err.contains("bad.csv:3:"), "{}", err);
+    fs::remove_dir_all(&dir).unwrap();
+}
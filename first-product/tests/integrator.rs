@@ -0,0 +1,193 @@
+use first_product::integrator::{self, IntegrationError, IntegrationStats, Method, OdeSystem, SecondOrderSystem,
+                                StiffMethod, Tolerances};
+use first_product::params::RunConfig;
+
+/// y' = λ y, solved by y0 e^{λx}.
+struct Exponential {
+    rate: f64,
+}
+
+impl OdeSystem for Exponential {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+        dydx[0] = self.rate*y[0];
+    }
+}
+
+/// y' = y², which leaves every finite value at x = 1 from y(0) = 1.
+struct Blowup;
+
+impl OdeSystem for Blowup {
+    fn dim(&self) -> usize {
+        1
+    }
+
+    fn derivatives(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+        dydx[0] = y[0]*y[0];
+    }
+}
+
+/// q̈ = q with unit time scale, whose first integral q̇² − q² vanishes on q = q̇ = e^t.
+struct Unstable;
+
+impl SecondOrderSystem for Unstable {
+    fn acceleration(&self, q: f64) -> f64 {
+        q
+    }
+
+    fn time_scale(&self, _q: f64) -> f64 {
+        1.0
+    }
+}
+
+/// Two decoupled relaxations, one stiff, with the Jacobian left to finite differences.
+struct Relaxation;
+
+impl OdeSystem for Relaxation {
+    fn dim(&self) -> usize {
+        2
+    }
+
+    fn derivatives(&self, _x: f64, y: &[f64], dydx: &mut [f64]) {
+        dydx[0] = -1e4*(y[0] - 1.0);
+        dydx[1] = -y[1];
+    }
+}
+
+/// `Relaxation` with its Jacobian given.
+struct Analytic;
+
+impl OdeSystem for Analytic {
+    fn dim(&self) -> usize {
+        2
+    }
+
+    fn derivatives(&self, x: f64, y: &[f64], dydx: &mut [f64]) {
+        Relaxation.derivatives(x, y, dydx);
+    }
+
+    fn jacobian(&self, _x: f64, _y: &[f64], jac: &mut [f64]) -> usize {
+        jac.copy_from_slice(&[-1e4, 0.0, 0.0, -1.0]);
+        0
+    }
+}
+
+/// Integrates `system` over [0, 1] with linearised backward Euler.
+fn relax<S: OdeSystem>(system: &S) -> (Vec<f64>, IntegrationStats) {
+    let method = StiffMethod { tolerances: Tolerances { rtol: 1e-6, atol: 1e-9 }, initial_step: 1e-3 };
+    let mut stats = IntegrationStats::default();
+    let (_, y) = integrator::integrate_stiff(system, &method, 0.0, &[0.0, 1.0], 1.0, &mut stats, |_, _| true).unwrap();
+    (y, stats)
+}
+
+fn rk4_error(h: f64) -> f64 {
+    let system = Exponential { rate: 1.0 };
+    let mut stats = IntegrationStats::default();
+    let (_, y) = integrator::integrate(&system, &Method::Rk4 { step: h }, 0.0, &[1.0], 1.0, &mut stats, |_, _| true)
+        .unwrap();
+    (y[0] - 1.0_f64.exp()).abs()
+}
+
+#[test]
+fn rk4_converges_at_fourth_order() {
+    for h in [0.1, 0.05, 0.025] {
+        let order = (rk4_error(h)/rk4_error(0.5*h)).log2();
+        assert!((order - 4.0).abs() < 0.1, "h = {}: order {}", h, order);
+    }
+}
+
+#[test]
+fn dormand_prince_rejects_steps_above_the_tolerance() {
+    let system = Exponential { rate: -1.0 };
+    let tolerances = Tolerances { rtol: 1e-8, atol: 1e-12 };
+    // The error norm of one step is the 4th-order local error, O(h⁵), against the tolerance
+    let (_, coarse) = integrator::dormand_prince_step(&system, 0.0, &[1.0], 0.4, &tolerances);
+    let (_, fine) = integrator::dormand_prince_step(&system, 0.0, &[1.0], 0.2, &tolerances);
+    assert!(coarse > 1.0 && fine < coarse, "err {} then {}", coarse, fine);
+    assert!(((coarse/fine).log2() - 5.0).abs() < 0.3, "err ratio {}", coarse/fine);
+
+    // Too long a first step is rejected and shortened; the accepted ones meet the tolerance
+    let method = Method::DormandPrince45 { tolerances, initial_step: 5.0 };
+    let mut stats = IntegrationStats::default();
+    let mut last = (0.0, 1.0);
+    let (x, y) = integrator::integrate(&system, &method, 0.0, &[1.0], 10.0, &mut stats, |x, y| {
+        // Each accepted step is exact to within a few tolerances of the value it started from
+        let expected = last.1*(-(x - last.0)).exp();
+        assert!((y[0] - expected).abs() <= 10.0*(tolerances.atol + tolerances.rtol*expected), "x = {}", x);
+        last = (x, y[0]);
+        true
+    }).unwrap();
+    assert_eq!(x, 10.0);
+    assert!(stats.rejected >= 1 && stats.accepted > 10, "{:?}", stats);
+    assert_eq!(stats.evaluations, 7*(stats.accepted + stats.rejected));
+    assert!((y[0]/(-10.0_f64).exp() - 1.0).abs() < 1e-6);
+
+    // A looser tolerance takes fewer steps
+    let loose = Method::DormandPrince45 { tolerances: Tolerances { rtol: 1e-4, atol: 1e-8 }, initial_step: 5.0 };
+    let mut loose_stats = IntegrationStats::default();
+    integrator::integrate(&system, &loose, 0.0, &[1.0], 10.0, &mut loose_stats, |_, _| true).unwrap();
+    assert!(loose_stats.accepted < stats.accepted);
+}
+
+#[test]
+fn leapfrog_keeps_the_first_integral_to_second_order() {
+    // The largest relative drift of q̇² − q² over 5 e-folds
+    let drift = |fraction: f64| {
+        let mut stats = IntegrationStats::default();
+        let mut drift = 0.0_f64;
+        let (t, q, _) = integrator::leapfrog(&Unstable, fraction, (0.0, 1.0, 1.0), 5.0_f64.exp(), &mut stats,
+            |_, q, v| {
+                drift = drift.max(((v*v - q*q)/(q*q)).abs());
+                true
+            }).unwrap();
+        assert!(q >= 5.0_f64.exp() && (t - 5.0).abs() <= fraction, "t = {}, q = {}", t, q);
+        assert_eq!(stats.evaluations, 2*stats.accepted);
+        drift
+    };
+    let ratio = drift(0.02)/drift(0.01);
+    assert!(drift(0.01) < 1e-4 && (ratio - 4.0).abs() < 0.2, "drift {:e}, ratio {}", drift(0.01), ratio);
+
+    // The first-order drivers refuse the symplectic scheme instead of guessing
+    let mut stats = IntegrationStats::default();
+    let leapfrog = Method::Leapfrog { fraction: 0.01 };
+    let err = integrator::integrate(&Exponential { rate: 1.0 }, &leapfrog, 0.0, &[1.0], 1.0, &mut stats, |_, _| true);
+    assert_eq!(err, Err(IntegrationError::NotFirstOrder));
+}
+
+#[test]
+fn underflowing_steps_are_an_error() {
+    let method = Method::DormandPrince45 { tolerances: Tolerances { rtol: 1e-10, atol: 0.0 }, initial_step: 1e-3 };
+    let mut stats = IntegrationStats::default();
+    let err = integrator::integrate(&Blowup, &method, 0.0, &[1.0], 2.0, &mut stats, |_, _| true).unwrap_err();
+    let IntegrationError::StepUnderflow { x } = err else { panic!("{}", err) };
+    assert!((x - 1.0).abs() < 1e-3, "stuck at x = {}", x);
+
+    let stiff = StiffMethod { tolerances: Tolerances { rtol: 1e-10, atol: 0.0 }, initial_step: 1e-3 };
+    let err = integrator::integrate_stiff(&Blowup, &stiff, 0.0, &[1.0], 2.0, &mut stats, |_, _| true).unwrap_err();
+    let IntegrationError::StepUnderflow { x } = err else { panic!("{}", err) };
+    assert!((x - 1.0).abs() < 1e-3, "stuck at x = {}", x);
+}
+
+#[test]
+fn stiff_steps_count_the_jacobian_evaluations() {
+    // Three linearised solves per attempt, each evaluating f once plus what its Jacobian
+    // took: nothing when it is analytic, 1 + dim by finite differences
+    for ((y, stats), per_attempt) in [(relax(&Analytic), 3), (relax(&Relaxation), 3*(1 + 1 + 2))] {
+        assert_eq!(stats.evaluations, per_attempt*(stats.accepted + stats.rejected), "{:?}", stats);
+        assert!((y[0] - 1.0).abs() < 1e-6 && (y[1] - (-1.0_f64).exp()).abs() < 1e-4, "{:?}", y);
+    }
+}
+
+#[test]
+fn the_run_keeps_the_friedmann_constraint_to_its_threshold() {
+    let config = RunConfig::default();
+    let summary = first_product::run(&config, None).unwrap();
+    let threshold = config.integration.residual_threshold;
+    assert!(summary.max_residual <= threshold, "residual {:e} above {:e}", summary.max_residual, threshold);
+    assert_eq!(summary.flagged, 0);
+    assert!(summary.stats.accepted > 0);
+    assert!((summary.final_h_over_hf - 1.0).abs() < 10.0*config.integration.h_final_tolerance);
+}
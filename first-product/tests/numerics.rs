@@ -0,0 +1,127 @@
+mod common;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use common::config;
+
+/// Output path of one test run; the sidecar and observables land next to it.
+fn output(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("first-product-numerics-{}-{}.csv", name, std::process::id()))
+}
+
+fn run(out: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_first-product"))
+        .args(["--output", &out.display().to_string()])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn cleanup(out: &Path) {
+    for path in [out.to_path_buf(), out.with_extension("params.toml"), out.with_extension("observables.csv")] {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// The trajectory as (header, rows), with the Era column dropped.
+fn read_trajectory(out: &Path) -> (Vec<String>, Vec<Vec<f64>>) {
+    let text = fs::read_to_string(out).unwrap();
+    let mut lines = text.lines();
+    let header: Vec<String> = lines.next().unwrap().split(',').map(String::from).collect();
+    let era = header.iter().position(|h| h == "Era").unwrap();
+    let rows = lines
+        .map(|l| l.split(',').enumerate()
+            .filter(|(i, _)| *i != era)
+            .map(|(_, v)| v.parse::<f64>().unwrap())
+            .collect())
+        .collect();
+    (header.into_iter().filter(|h| h != "Era").collect(), rows)
+}
+
+/// (step, column) of the first NaN or infinite entry.
+fn first_non_finite(header: &[String], rows: &[Vec<f64>]) -> Option<(usize, String)> {
+    rows.iter().enumerate().find_map(|(step, row)| {
+        row.iter().position(|v| !v.is_finite()).map(|c| (step, header[c].clone()))
+    })
+}
+
+/// Runs the model without a trajectory and checks that it ends at HF without flagged steps.
+fn converges(flags: &[&str]) {
+    let summary = first_product::run(&config(flags), None).unwrap();
+    assert!(summary.flagged == 0 && summary.non_finite.is_none(), "{:?}", summary);
+    assert!((summary.final_h_over_hf - 1.0).abs() < 1e-5, "{:?}", summary);
+}
+
+#[test]
+fn default_run_is_finite_and_converges() {
+    let out = output("default");
+    converges(&[]);
+    let result = run(&out, &[]);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    let (header, rows) = read_trajectory(&out);
+    assert!(rows.len() > 100);
+    assert_eq!(first_non_finite(&header, &rows), None);
+    cleanup(&out);
+}
+
+#[test]
+fn three_hundred_orders_of_magnitude_stay_finite() {
+    // HI = 1e-100 down to H0 = 1e-200 GeV: H², ρ and V all leave the f64 range
+    let out = output("extreme");
+    let flags = ["--hi", "1e-100", "--h0", "1e-200", "--m", "1e-110"];
+    converges(&flags);
+    let result = run(&out, &flags);
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    let (header, rows) = read_trajectory(&out);
+    assert_eq!(first_non_finite(&header, &rows), None);
+    let residual = header.iter().position(|h| h == "FriedmannResidual").unwrap();
+    assert!(rows.iter().all(|r| r[residual].abs() < 1e-6));
+    cleanup(&out);
+}
+
+#[test]
+fn exact_backend_agrees_with_double() {
+    let (double, exact) = (output("double"), output("exact"));
+    assert!(run(&double, &[]).status.success());
+    assert!(run(&exact, &["--precision", "exact"]).status.success());
+
+    let (header, a) = read_trajectory(&double);
+    let (_, b) = read_trajectory(&exact);
+    assert_eq!(a.len(), b.len());
+    for name in ["FriedmannResidual", "AccelerationResidual"] {
+        let c = header.iter().position(|h| h == name).unwrap();
+        let worst = a.iter().zip(&b).map(|(x, y)| (x[c] - y[c]).abs()).fold(0.0, f64::max);
+        assert!(worst < 1e-13, "{} differs by {}", name, worst);
+    }
+    assert!(fs::read_to_string(exact.with_extension("params.toml")).unwrap().contains("precision = \"exact\""));
+    cleanup(&double);
+    cleanup(&exact);
+}
+
+#[test]
+fn non_finite_values_stop_the_run() {
+    // A 300 e-fold RK4 step overshoots far past HF and overflows the cosmic time
+    let out = output("overflow");
+    let result = run(&out, &["--method", "rk4", "--rk4-step", "300"]);
+    assert_eq!(result.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&result.stderr).contains("is not finite at step"));
+
+    // The offending row is the last one written
+    let (header, rows) = read_trajectory(&out);
+    let (step, _) = first_non_finite(&header, &rows).unwrap();
+    assert_eq!(step, rows.len() - 1);
+    cleanup(&out);
+}
+
+#[test]
+fn start_without_fluid_is_rejected() {
+    let out = output("no-fluid");
+    let result = run(&out, &["--m", "1e100"]);
+    assert_eq!(result.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&result.stderr).contains("leaves no fluid"));
+    cleanup(&out);
+}
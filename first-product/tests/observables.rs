@@ -0,0 +1,65 @@
+use first_product::model::RunningVacuum;
+use first_product::observables::{self, History, LambdaCdm};
+use first_product::params::{CosmologyParams, IntegrationParams, PlasmaModel, PlasmaParams};
+
+/// |a/b − 1|
+fn relative(a: f64, b: f64) -> f64 {
+    (a/b - 1.0).abs()
+}
+
+/// Observables of the flat ΛCDM reference today and at z = 1.
+fn lambda_cdm(h0: f64, omega_m0: f64, omega_r0: f64) -> History {
+    let reference = LambdaCdm { h0, omega_m0, omega_r0 };
+    observables::lambda_cdm(&reference, &IntegrationParams::default().method(), &[0.0, 1.0]).unwrap()
+}
+
+#[test]
+fn lambda_cdm_age_and_distances_match_the_flat_model() {
+    // Without radiation H0 t0 = 2/(3 Ω_Λ^{1/2}) asinh((Ω_Λ/Ω_m)^{1/2}) and, for Ω_m = 0.3,
+    // H0 d_L(z=1) = 2 ∫_0^1 dz/E(z) = 1.5428541 (Simpson, 2e5 intervals)
+    let h0 = 1e-33;
+    let history = lambda_cdm(h0, 0.3, 0.0);
+    let age = 2.0/(3.0*0.7_f64.sqrt())*(0.7_f64/0.3).sqrt().asinh();
+    assert!(relative(history.age*h0, age) < 1e-8, "H0 t0 = {}", history.age*h0);
+    assert!(relative(age, 0.9640994) < 1e-6);
+
+    let [today, z1] = [history.points[0], history.points[1]];
+    assert_eq!(today.comoving_distance, 0.0);
+    assert!(relative(today.hubble, h0) < 1e-12);
+    assert!(relative(z1.luminosity_distance*h0, 1.5428541) < 1e-6, "H0 d_L = {}", z1.luminosity_distance*h0);
+    assert!(relative(z1.angular_diameter_distance, z1.luminosity_distance/4.0) < 1e-12);
+    assert!(relative(z1.hubble, h0*(0.3*8.0 + 0.7_f64).sqrt()) < 1e-12);
+}
+
+#[test]
+fn planck_parameters_give_the_measured_age() {
+    // H0 = 67.4 km/s/Mpc, Ω_m = 0.315 with photons and neutrinos, Ω_r = 9.1e-5: the
+    // universe is 13.79 Gyr old and z = 1 lies 6802 Mpc away in luminosity distance
+    let h0 = 67.4e3/3.085_677_581_491_367e22*6.582119569e-25;
+    let history = lambda_cdm(h0, 0.315, 9.1e-5);
+    assert!((observables::to_gyr(history.age) - 13.79).abs() < 0.01, "{} Gyr", observables::to_gyr(history.age));
+    let distance = observables::to_mpc(history.points[1].luminosity_distance);
+    assert!((distance - 6802.0).abs() < 1.0, "d_L = {} Mpc", distance);
+}
+
+#[test]
+fn hf_leaves_the_lambda_cdm_vacuum_today() {
+    // HF² = H0² (Ω_Λ0 − ν)/(1−ν) makes V(H0) = 3 MP² H0² Ω_Λ0 for every ν; the older
+    // H0² Ω_m0/(1−ν) put a vacuum of Ω_m0 today instead of Ω_Λ0
+    for nu in [0.0, 0.001, 0.01, 0.1] {
+        let p = CosmologyParams { nu, ..CosmologyParams::default() };
+        let model = RunningVacuum::new(&p, &PlasmaParams::default()).unwrap();
+        let omega_lambda0 = 1.0 - p.omega_m0 - p.omega_r0;
+        assert!(relative(model.potential(p.h0)/(3.0*p.mp.powi(2)*p.h0.powi(2)), omega_lambda0) < 1e-12, "nu = {}", nu);
+    }
+
+    // Without running the history from HI down to today is the ΛCDM one
+    let p = CosmologyParams { nu: 0.0, ..CosmologyParams::default() };
+    let plasma = PlasmaParams { model: PlasmaModel::Conformal, ..PlasmaParams::default() };
+    let method = IntegrationParams::default().method();
+    let running = observables::running_vacuum(&RunningVacuum::new(&p, &plasma).unwrap(), &method, &[0.0, 1.0]).unwrap();
+    let reference = observables::lambda_cdm(&LambdaCdm::new(&p), &method, &[0.0, 1.0]).unwrap();
+    assert!(relative(running.age, reference.age) < 1e-6, "{} vs {}", running.age, reference.age);
+    let distances = [running.points[1].luminosity_distance, reference.points[1].luminosity_distance];
+    assert!(relative(distances[0], distances[1]) < 1e-6, "{:?}", distances);
+}
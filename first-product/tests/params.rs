@@ -0,0 +1,84 @@
+mod common;
+
+use std::fs;
+
+use common::{first_product, scratch};
+use first_product::params::{ParamsError, RunConfig};
+
+fn from_args(args: &[&str]) -> Result<RunConfig, ParamsError> {
+    RunConfig::from_args(args.iter().map(|a| a.to_string()))
+}
+
+#[test]
+fn out_of_range_parameters_are_invalid() {
+    for args in [
+        &["--nu", "0.5"][..],                          // (1−2ν)^{1/4} in α vanishes
+        &["--nu", "-0.01"],
+        &["--omega-m0", "0.7", "--nu", "0.3"],         // Ω_Λ0 ≤ ν leaves HF² ≤ 0
+        &["--omega-m0", "0.8", "--omega-r0", "0.3"],
+        &["--hi", "1e19"],                             // above the reduced Planck mass
+        &["--h0", "0"],
+    ] {
+        match from_args(args) {
+            Err(ParamsError::Invalid(_)) => {}
+            other => panic!("{:?}: {:?}", args, other),
+        }
+    }
+    assert!(from_args(&["--nu", "0.49", "--omega-m0", "0.3"]).is_ok());
+}
+
+#[test]
+fn malformed_flags_are_usage_errors() {
+    for args in [&["--nu", "small"][..], &["--nu"], &["--method", "euler"], &["--frobnicate", "1"]] {
+        match from_args(args) {
+            Err(ParamsError::Usage(_)) => {}
+            other => panic!("{:?}: {:?}", args, other),
+        }
+    }
+}
+
+#[test]
+fn usage_follows_malformed_flags() {
+    for (args, usage) in [(&["--frobnicate", "1"][..], "usage: first-product [--config"),
+                          (&["bbn", "--help"], "usage: first-product bbn"),
+                          (&["recombination", "--nu", "small"], "usage: first-product recombination")] {
+        let output = first_product(args);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains(usage), "{:?}", args);
+    }
+    // Invalid values are not usage errors
+    let output = first_product(&["plasma", "--bag-root", "0.01"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("usage:"));
+}
+
+#[test]
+fn config_files_are_validated_and_overridden_by_flags() {
+    let dir = scratch("params-config");
+    let file = dir.join("run.toml");
+    let path = file.display().to_string();
+
+    fs::write(&file, "[cosmology]\nnu = 0.02\nomega_m0 = 0.25\n").unwrap();
+    let config = from_args(&["--config", &path]).unwrap();
+    assert_eq!((config.cosmology.nu, config.cosmology.omega_m0), (0.02, 0.25));
+    assert_eq!(from_args(&["--nu", "0.03", "--config", &path]).unwrap().cosmology.nu, 0.03);
+
+    // Values in the file are checked like flags, unknown keys are refused
+    fs::write(&file, "[cosmology]\nnu = 0.7\n").unwrap();
+    assert!(matches!(from_args(&["--config", &path]), Err(ParamsError::Invalid(_))));
+    fs::write(&file, "[cosmology]\nneutrinos = 3\n").unwrap();
+    assert!(matches!(from_args(&["--config", &path]), Err(ParamsError::Toml(_))));
+    let missing = dir.join("missing.toml").display().to_string();
+    assert!(matches!(from_args(&["--config", &missing]), Err(ParamsError::Io(..))));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn the_sidecar_reads_back_as_the_run_configuration() {
+    let dir = scratch("params-sidecar");
+    let output = dir.join("run.csv").display().to_string();
+    let config = from_args(&["--output", &output, "--nu", "0.01", "--method", "rk4", "--format", "jsonl"]).unwrap();
+    let sidecar = config.write_sidecar(1.0).unwrap();
+    assert_eq!(sidecar, dir.join("run.params.toml"));
+    assert_eq!(RunConfig::from_file(&sidecar).unwrap(), config);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use std::fs;
+
+use common::{config, first_product, our_universe, read_table, scratch};
+use first_product::integrator::IntegrationStats;
+use first_product::model::{Cosmology, RunningVacuum};
+use first_product::observables::to_kelvin;
+use first_product::params::PlasmaParams;
+use first_product::plasma::Plasma;
+use first_product::recombination::LateHistory;
+
+#[test]
+fn degrees_of_freedom_follow_the_standard_model() {
+    let plasma = Plasma::new(&PlasmaParams::default()).unwrap();
+    let points = &plasma.points;
+    let at = |temperature: f64| points.iter().find(|p| p.temperature >= temperature).unwrap();
+    let (first, last) = (points[0], points[points.len() - 1]);
+    // All of the Standard Model, the e± and ν plasma of a few MeV, photons and relic neutrinos
+    assert!((last.g_star() - 106.75).abs() < 0.01, "g* = {}", last.g_star());
+    assert!((at(5e-3).g_star() - 10.75).abs() < 0.05, "g* = {}", at(5e-3).g_star());
+    assert!((first.g_star() - 3.36).abs() < 0.05 && (first.g_star_s() - 3.91).abs() < 0.05,
+            "g* = {}, g*s = {}", first.g_star(), first.g_star_s());
+
+    // The crossover softens the plasma between the hadron gas and the bag plasma
+    let softest = points.iter().min_by(|a, b| a.w().total_cmp(&b.w())).unwrap();
+    assert!((0.12..0.2).contains(&softest.temperature) && softest.w() < 0.25,
+            "w = {} at T = {}", softest.w(), softest.temperature);
+    assert!(at(0.1).g_star() < 30.0 && at(0.3).g_star() > 60.0);
+    assert!(points.iter().all(|p| p.w() > 0.0 && p.w() <= 1.0/3.0 + 1e-9));
+}
+
+#[test]
+fn qcd_crossover_leaves_an_imprint_on_the_expansion() {
+    // H t where T first falls below the crossover
+    let crossover_ht = |plasma: &str| {
+        let summary = first_product::run(&config(&["--plasma", plasma]), None).unwrap();
+        assert!(summary.flagged == 0 && summary.non_finite.is_none(), "{:?}", summary);
+        let (t, h) = summary.crossover.unwrap();
+        h*t
+    };
+    let (ht_qgp, ht_conformal) = (crossover_ht("qgp"), crossover_ht("conformal"));
+
+    // Pure radiation expands as H t = 1/2; the softer plasma lags behind it
+    assert!((ht_conformal - 0.5).abs() < 0.01, "conformal H t = {}", ht_conformal);
+    assert!(ht_qgp > ht_conformal + 0.02, "qgp H t = {}", ht_qgp);
+
+    // The radiation of the run passes through every stage of the plasma
+    let config = config(&["--plasma", "qgp"]);
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let mut radiation = Vec::new();
+    model.evolve(&config.integration.method(), &mut IntegrationStats::default(), |state| {
+        radiation.push(model.radiation(state));
+        radiation.last().unwrap().temperature > 1e-5
+    }).unwrap();
+    let g = radiation.iter().map(|r| r.g_star);
+    assert!(g.clone().fold(0.0, f64::max) > 106.0 && g.fold(f64::MAX, f64::min) < 3.5);
+    let crossover_w = radiation.iter()
+        .filter(|r| (0.1..0.3).contains(&r.temperature))
+        .map(|r| r.w)
+        .fold(f64::MAX, f64::min);
+    assert!(crossover_w < 0.3, "w = {} across the crossover", crossover_w);
+}
+
+#[test]
+fn our_universe_ends_at_the_cmb_temperature() {
+    // The annihilations heat the radiation on its way down, but the run is seeded so that
+    // ρ_r today is still Ω_r0 of the critical density, as in the conformal plasma
+    for plasma in ["qgp", "conformal"] {
+        let config = our_universe(&["--plasma", plasma]);
+        let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+        let history = LateHistory::of_run(&model, &config, 0.049, 0.245).unwrap();
+        let t0 = to_kelvin(history.temperature_today());
+        assert!((t0 - 2.725).abs() < 0.01, "{} plasma: T0 = {} K", plasma, t0);
+    }
+}
+
+#[test]
+fn plasma_writes_the_equation_of_state_table() {
+    let dir = scratch("plasma-table");
+    let table = dir.join("eos.csv");
+    let output = first_product(&["plasma", "--table", &table.display().to_string()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let (header, rows) = read_table(&table);
+    assert_eq!(header, ["T", "g_star", "g_star_s", "p_over_T4", "rho_over_T4", "s_over_T3", "w"]);
+    assert_eq!(rows.len(), Plasma::new(&PlasmaParams::default()).unwrap().points.len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bag_constant_without_crossover_is_rejected() {
+    let output = first_product(&["plasma", "--bag-root", "0.01", "--table", "unused.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no crossover"));
+}
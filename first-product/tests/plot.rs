@@ -0,0 +1,49 @@
+mod common;
+
+use std::fs;
+
+use common::{first_product, scratch};
+
+#[test]
+fn plot_writes_every_figure_of_a_run() {
+    let dir = scratch("plot-run");
+    let csv = dir.join("run.csv").display().to_string();
+    assert!(first_product(&["--output", &csv]).status.success());
+
+    let output = first_product(&["plot", &csv]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    for figure in ["phase", "hubble", "w", "fractions"] {
+        let png = fs::read(dir.join(format!("run.{}.png", figure))).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n", "{}", figure);
+        let svg = fs::read_to_string(dir.join(format!("run.{}.svg", figure))).unwrap();
+        assert!(svg.contains("<svg") && (svg.contains("<circle") || svg.contains("<polyline")), "{}", figure);
+    }
+    // Only the figures the columns allow
+    assert!(!dir.join("run.pv.png").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plot_draws_the_carnot_cycle() {
+    let dir = scratch("plot-carnot");
+    let csv = dir.join("cycle.csv");
+    fs::write(&csv, "step,V,P,T,S,Q,W,phase\n1,1.0,500,500,0,0,0,a\n2,2.0,250,500,0.3,1,1,a\n3,3.0,120,300,0.3,0,1,b\n").unwrap();
+    let out = dir.join("figures");
+    let output = first_product(&["plot", &csv.display().to_string(), "--out-dir", &out.display().to_string(),
+                                 "--format", "svg"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(out.join("cycle.pv.svg").exists());
+    assert!(!out.join("cycle.pv.png").exists());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn plot_rejects_tables_without_known_columns() {
+    let dir = scratch("plot-unknown");
+    let csv = dir.join("other.csv");
+    fs::write(&csv, "a,b\n1,2\n").unwrap();
+    let output = first_product(&["plot", &csv.display().to_string()]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no figure can be drawn"));
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::fs;
+
+use common::{first_product, our_universe, read_table, scratch, OUR_UNIVERSE};
+use first_product::model::RunningVacuum;
+use first_product::observables;
+use first_product::recombination::{self, LateHistory, Recombination};
+
+/// Recombination in our universe with the running `nu`, Ω_b0 = 0.049 and Y_p = 0.245.
+fn recombination(nu: &str) -> Recombination {
+    let config = our_universe(&["--nu", nu]);
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let history = LateHistory::of_run(&model, &config, 0.049, 0.245).unwrap();
+    recombination::recombine(&history).unwrap()
+}
+
+#[test]
+fn hydrogen_recombines_near_a_quarter_of_an_electronvolt() {
+    let result = recombination("0");
+    let (first, last) = (result.points[0], result.points[result.points.len() - 1]);
+    // Fully ionised hydrogen and helium at 1 keV, a frozen-out trace today
+    assert!((first.x_e - 1.0 - 2.0*0.245/(4.0*0.755)).abs() < 1e-6, "x_e = {}", first.x_e);
+    assert!((1e-4..1e-3).contains(&last.x_e), "x_e today = {}", last.x_e);
+    for pair in result.points.windows(2) {
+        assert!(pair[1].z < pair[0].z && pair[1].x_h <= pair[0].x_h + 1e-9, "{:?}", pair);
+        assert!(pair[1].tau <= pair[0].tau, "{:?}", pair);
+    }
+
+    // The CMB today, last scattering at T ≈ 0.25 eV and z_* ≈ 1090, the baryons released shortly after
+    let t0 = observables::to_kelvin(last.temperature);
+    assert!((t0/2.725 - 1.0).abs() < 0.01, "T0 = {} K", t0);
+    let star = result.points.iter().max_by(|a, b| a.visibility.total_cmp(&b.visibility)).unwrap();
+    assert_eq!(star.z, result.last_scattering);
+    assert!((0.22e-9..0.28e-9).contains(&star.temperature), "T_* = {} GeV", star.temperature);
+    let (z_star, z_drag) = (result.last_scattering, result.drag);
+    assert!((z_star/1090.0 - 1.0).abs() < 0.01, "z_* = {}", z_star);
+    assert!(z_drag < z_star && z_drag > 0.9*z_star, "z_d = {}, z_* = {}", z_drag, z_star);
+    let sound_horizon = observables::to_mpc(result.sound_horizon_drag);
+    assert!((130.0..170.0).contains(&sound_horizon), "r_s(z_d) = {} Mpc", sound_horizon);
+    assert!((0.009..0.012).contains(&result.acoustic_angle()), "theta_* = {}", result.acoustic_angle());
+}
+
+#[test]
+fn running_vacuum_moves_the_sound_horizon() {
+    let (standard, running) = (recombination("0"), recombination("0.001"));
+    let r_standard = observables::to_mpc(standard.sound_horizon_drag);
+    let r_running = observables::to_mpc(running.sound_horizon_drag);
+    assert!((r_running - r_standard).abs() > 0.1, "{} vs {}", r_running, r_standard);
+}
+
+#[test]
+fn recombination_writes_the_ionisation_history() {
+    let dir = scratch("recombination-table");
+    let table = dir.join("history.csv");
+    let table_arg = table.display().to_string();
+    let output = first_product(&[&["recombination", "--nu", "0", "--table", &table_arg][..], &OUR_UNIVERSE].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let (header, rows) = read_table(&table);
+    assert_eq!(header.join(","), Recombination::CSV_HEADER);
+    assert_eq!(rows.len(), recombination("0").points.len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_hot_universe_today_is_rejected() {
+    let output = first_product(&["recombination", "--table", "unused.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("hydrogen is still ionised today"));
+}
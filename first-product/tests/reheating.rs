@@ -0,0 +1,69 @@
+mod common;
+
+use std::fs;
+
+use common::{config, first_product, scratch};
+use first_product::model::RunningVacuum;
+use first_product::reheating::{self, Reheating, ReheatingHistory, Resonance};
+
+/// Reheats the condensate left by the default run at decay rate `gamma` and mass `mass`
+/// (GeV), through the broad resonance of `resonance` if given.
+fn reheat(gamma: f64, mass: Option<f64>, resonance: Option<Resonance>) -> ReheatingHistory {
+    let config = config(&[]);
+    let method = config.integration.method();
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let end = reheating::primeval_end(&model, &method).unwrap();
+    let mass = mass.unwrap_or(model.hubble(end.z));
+    let system = Reheating { mp: config.cosmology.mp, gamma, mass, resonance, plasma: model.plasma.clone() };
+    let y0 = reheating::initial_state(&model, &end, mass, resonance.is_some());
+    let history = reheating::reheat(&system, &method, &y0).unwrap();
+    assert!(history.reheating_temperature.is_some(), "H never fell to gamma = {:e}", gamma);
+    history
+}
+
+#[test]
+fn reheating_temperature_follows_the_decay_rate() {
+    let (slow, fast) = (reheat(1e10, None, None), reheat(1e12, None, None));
+
+    // T_reh ~ (90/(π² g*))^{1/4} (Γ MP)^{1/2} up to the O(1) factor of gradual decay
+    for history in [&slow, &fast] {
+        let t = history.reheating_temperature.unwrap();
+        assert!((0.6..1.2).contains(&(t/history.estimate)), "T_reh = {}, estimate {}", t, history.estimate);
+        assert!(history.resonance_end.is_none() && history.chi_share < 1e-100, "{}", history.chi_share);
+    }
+    let ratio = fast.reheating_temperature.unwrap()/slow.reheating_temperature.unwrap();
+    assert!((ratio/10.0 - 1.0).abs() < 0.05, "T_reh ratio {} for 100 x gamma", ratio);
+}
+
+#[test]
+fn parametric_resonance_speeds_up_the_transfer() {
+    let perturbative = reheat(1e8, Some(1e13), None);
+    let resonant = reheat(1e8, Some(1e13), Some(Resonance { coupling: 1e-2, floquet: 0.175 }));
+
+    // The resonance drains the condensate before the decay does, so the radiation is
+    // produced earlier and is hotter at H = Γ
+    assert!(resonant.resonance_end.is_some() && resonant.chi_share > 0.5, "chi share {}", resonant.chi_share);
+    let (t_resonant, t_perturbative) = (resonant.reheating_temperature.unwrap(),
+                                        perturbative.reheating_temperature.unwrap());
+    assert!(t_resonant > t_perturbative*1.1 && t_resonant < perturbative.estimate*1.05,
+            "{} vs {}", t_resonant, t_perturbative);
+}
+
+#[test]
+fn reheat_writes_the_history_table() {
+    let dir = scratch("reheating-table");
+    let table = dir.join("slow.csv");
+    let output = first_product(&["reheat", "--gamma", "1e10", "--table", &table.display().to_string()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let text = fs::read_to_string(&table).unwrap();
+    assert!(text.starts_with(&format!("{}\n", ReheatingHistory::CSV_HEADER)));
+    assert_eq!(text.lines().count(), reheat(1e10, None, None).rows.len() + 1);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn decay_faster_than_the_expansion_is_rejected() {
+    let output = first_product(&["reheat", "--gamma", "1e20", "--table", "unused.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("below H_end"));
+}
@@ -0,0 +1,79 @@
+mod common;
+
+use std::fs;
+
+use common::{first_product, our_universe, read_table, scratch, OUR_UNIVERSE};
+use first_product::model::RunningVacuum;
+use first_product::relic::{self, Background, Relic, Species};
+
+/// Freeze-out from x = 3 to 1000 of two states of 100 GeV annihilating at
+/// <σv> = a + b/x (cm³/s) in our universe without running; returns the relic with g*s at x_f.
+fn relic(s_wave: f64, p_wave: f64) -> (Relic, f64) {
+    let config = our_universe(&["--nu", "0"]);
+    let model = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let x_range = [3.0, 1000.0];
+    let background = Background::of_run(&model, &config, 2.0*100.0/x_range[0]).unwrap();
+    let species = Species {
+        mass: 100.0,
+        dof: 2.0,
+        s_wave: relic::from_cm3_per_s(s_wave),
+        p_wave: relic::from_cm3_per_s(p_wave),
+    };
+    let relic = relic::freeze_out(species, &background, config.cosmology.mp, x_range).unwrap();
+    let x_f = relic.freeze_out.expect("the species stays in equilibrium");
+    let g_star_s = model.plasma.entropy_dof(100.0/x_f);
+    (relic, g_star_s)
+}
+
+#[test]
+fn weak_scale_annihilation_freezes_out_near_x_of_twenty() {
+    let (relic, g_star_s) = relic(2.2e-26, 0.0);
+    // In equilibrium at the start, frozen at the end
+    let (first, last) = (relic.points[0], relic.points[relic.points.len() - 1]);
+    assert!((first.y/first.y_eq - 1.0).abs() < 1e-6, "{:?}", first);
+    assert!(last.y > 1e6*last.y_eq, "{:?}", last);
+    let x_f = relic.freeze_out.unwrap();
+    assert!((20.0..26.0).contains(&x_f), "x_f = {}", x_f);
+
+    // Kolb & Turner: Y = 3.79 x_f/((g*s/g*^{1/2}) M_Pl m <σv>), with M_Pl = 1.22e19 GeV and
+    // 2.2e-26 cm³/s = 1.885e-9 GeV⁻²
+    let estimate = 3.79*x_f/(g_star_s.sqrt()*1.22e19*100.0*1.885e-9);
+    let y = relic.final_yield();
+    assert!((y/estimate - 1.0).abs() < 0.15, "Y = {} vs {}", y, estimate);
+    // Without vacuum decay the comoving entropy is conserved, up to the interpolated g*s of the
+    // QCD crossover, and annihilation only depletes Y
+    for pair in relic.points.windows(2) {
+        assert!(pair[1].y <= pair[0].y*(1.0 + 1e-3), "{:?}", pair);
+    }
+}
+
+#[test]
+fn relic_density_falls_with_the_cross_section() {
+    let weak = relic(2.2e-26, 0.0).0.omega_h2;
+    let strong = relic(4.4e-26, 0.0).0.omega_h2;
+    let p_wave = relic(0.0, 4.4e-25).0.omega_h2;
+    // Ω h² ∝ x_f/<σv>, with x_f growing by ln 2
+    assert!((0.45..0.55).contains(&(strong/weak)), "{} vs {}", strong, weak);
+    // b/x_f ≈ 2e-26 at freeze-out, but annihilation continues for less long
+    assert!(p_wave > weak, "{} vs {}", p_wave, weak);
+}
+
+#[test]
+fn relic_writes_the_yield_history() {
+    let dir = scratch("relic-table");
+    let table = dir.join("wimp.csv");
+    let table_arg = table.display().to_string();
+    let output = first_product(&[&["relic", "--nu", "0", "--table", &table_arg][..], &OUR_UNIVERSE].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let (header, rows) = read_table(&table);
+    assert_eq!(header.join(","), Relic::CSV_HEADER);
+    assert_eq!(rows.len(), relic(2.2e-26, 0.0).0.points.len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn freeze_out_window_must_lie_in_the_run() {
+    let output = first_product(&[&["relic", "--x", "3,1e15", "--table", "unused.csv"][..], &OUR_UNIVERSE].concat());
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("outside the history of the run"));
+}
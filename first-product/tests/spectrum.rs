@@ -0,0 +1,57 @@
+mod common;
+
+use std::fs;
+
+use common::{config, first_product, read_table, scratch};
+use first_product::model::RunningVacuum;
+use first_product::params::IntegrationParams;
+use first_product::perturbations::{self, Inflaton};
+
+#[test]
+fn quadratic_inflaton_matches_slow_roll() {
+    let method = IntegrationParams::default().method();
+    let inflaton = Inflaton::default();
+    let spectrum = perturbations::spectrum(&inflaton, &method, 60.0, 7, 1.5).unwrap();
+    let (numerical, flow) = (spectrum.numerical, spectrum.slow_roll);
+    let potential = inflaton.slow_roll(inflaton.field_at(&method, spectrum.pivot_n).unwrap());
+    assert_eq!(spectrum.modes.len(), 7);
+
+    // m²φ²/2 with the pivot 60 e-folds before the end: n_s ≈ 1 − 2/60.5, r ≈ 8/60.5
+    assert!((numerical.n_s - flow.n_s).abs() < 1e-3 && (numerical.n_s - potential.n_s).abs() < 1e-3,
+            "{:?} {:?} {:?}", numerical, flow, potential);
+    assert!((numerical.n_s - (1.0 - 2.0/60.5)).abs() < 2e-3, "n_s = {}", numerical.n_s);
+    assert!((numerical.r/flow.r - 1.0).abs() < 0.03 && (numerical.r/potential.r - 1.0).abs() < 0.03,
+            "{:?} {:?} {:?}", numerical, flow, potential);
+    assert!((numerical.r - 8.0/60.5).abs() < 5e-3, "r = {}", numerical.r);
+    assert!(numerical.running < 0.0 && (numerical.running - potential.running).abs() < 1e-4,
+            "{:?} {:?}", numerical, potential);
+    assert!((numerical.amplitude/flow.amplitude - 1.0).abs() < 0.03, "{:?} {:?}", numerical, flow);
+}
+
+#[test]
+fn running_vacuum_tilt_follows_the_hubble_flow() {
+    // ε grows as a^{4(1−ν)} in the primeval phase: n_s − 1 ≈ −4(1 − ν)
+    let config = config(&[]);
+    let background = RunningVacuum::new(&config.cosmology, &config.plasma).unwrap();
+    let spectrum = perturbations::spectrum(&background, &config.integration.method(), 10.0, 5, 1.0).unwrap();
+    assert!((spectrum.numerical.n_s - spectrum.slow_roll.n_s).abs() < 0.05, "{:?}", spectrum);
+}
+
+#[test]
+fn spectrum_writes_one_row_per_mode() {
+    let dir = scratch("spectrum-table");
+    let table = dir.join("spectrum.csv");
+    let output = first_product(&["spectrum", "--modes", "5", "--table", &table.display().to_string()]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let (header, rows) = read_table(&table);
+    assert_eq!(&header[..2], ["k", "ln_k_over_pivot"]);
+    assert_eq!(rows.len(), 5);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn spectrum_rejects_a_pivot_before_the_start() {
+    let output = first_product(&["spectrum", "--efolds", "80", "--table", "unused.csv"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("inflates only"));
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+
+use common::{first_product, scratch};
+use first_product::sweep::SweepConfig;
+
+fn config(args: &[&str]) -> SweepConfig {
+    SweepConfig::from_args(args.iter().map(|a| a.to_string()).collect()).unwrap()
+}
+
+/// Runs a two-point sweep into `table` with the run flags `flags`.
+fn sweep(table: &Path, flags: &[&str]) {
+    let table = table.display().to_string();
+    let output = first_product(&[&["sweep", "--grid", "1,2", "--threads", "2", "--table", &table][..], flags].concat());
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
+
+#[test]
+fn latin_hypercube_samples_are_fixed_by_the_seed() {
+    let points = config(&["--lhs", "16", "--seed", "7"]).points();
+    assert_eq!(points, config(&["--lhs", "16", "--seed", "7"]).points());
+    assert_ne!(points, config(&["--lhs", "16", "--seed", "8"]).points());
+
+    // One sample in each of the 16 strata of ν and of ln M
+    let ranges = config(&["--lhs", "16"]);
+    let stratum = |x: f64, [lo, hi]: [f64; 2]| ((x - lo)/(hi - lo)*16.0).floor() as usize;
+    let mut nu: Vec<usize> = points.iter().map(|p| stratum(p.0, ranges.nu_range)).collect();
+    let mut m: Vec<usize> = points.iter().map(|p| stratum(p.1.ln(), ranges.m_range.map(f64::ln))).collect();
+    nu.sort();
+    m.sort();
+    assert_eq!(nu, (0..16).collect::<Vec<_>>());
+    assert_eq!(m, (0..16).collect::<Vec<_>>());
+}
+
+#[test]
+fn sweeps_are_validated_point_by_point() {
+    // ν = 1/2 ends the range but is only sampled by a grid with more than one ν
+    let args = |grid| ["--grid", grid, "--nu-range", "0,0.5"].map(String::from).to_vec();
+    assert_eq!(SweepConfig::from_args(args("1,3")).unwrap().points().len(), 3);
+    let err = SweepConfig::from_args(args("2,3")).unwrap_err().to_string();
+    assert!(err.contains("run 3 (nu = 0.5"), "{}", err);
+}
+
+#[test]
+fn resume_keeps_complete_rows_and_reruns_the_rest() {
+    let dir = scratch("sweep-resume");
+    let table = dir.join("sweep.csv");
+    sweep(&table, &["--rtol", "1e-6"]);
+    let full = fs::read_to_string(&table).unwrap();
+    let lines: Vec<&str> = full.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", full);
+
+    // Interrupted while writing run 1: its line has every comma but no newline. Run 0 is
+    // marked so that a rerun would show.
+    let mut marked: Vec<String> = lines[1].split(',').map(String::from).collect();
+    marked[8] = "123".to_string();
+    let marked = marked.join(",");
+    fs::write(&table, format!("{}\n{}\n{}", lines[0], marked, &lines[2][..lines[2].len() - 1])).unwrap();
+    sweep(&table, &["--rtol", "1e-6"]);
+    assert_eq!(fs::read_to_string(&table).unwrap(), format!("{}\n{}\n{}\n", lines[0], marked, lines[2]));
+
+    // A different sweep does not resume the table
+    let output = first_product(&["sweep", "--grid", "1,2", "--table", &table.display().to_string(), "--rtol", "1e-7"]);
+    assert!(!output.status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_runs_leave_a_row_with_the_error() {
+    let dir = scratch("sweep-failed");
+    let table = dir.join("sweep.csv");
+    // No step can meet a relative tolerance of 1e-300
+    sweep(&table, &["--rtol", "1e-300"]);
+    let text = fs::read_to_string(&table).unwrap();
+    let rows: Vec<Vec<&str>> = text.lines().skip(1).map(|l| l.split(',').collect()).collect();
+    assert_eq!(rows.len(), 2, "{}", text);
+    for (index, row) in rows.iter().enumerate() {
+        assert_eq!(row[0], index.to_string());
+        assert!(row[3..12].iter().all(|v| v.is_empty()), "{:?}", row);
+        assert!(row[12].contains("step size underflow"), "{:?}", row);
+    }
+
+    // A 300 e-fold RK4 step overflows the cosmic time: the run stops with its metrics so far
+    let overflow = dir.join("overflow.csv");
+    sweep(&overflow, &["--method", "rk4", "--rk4-step", "300"]);
+    let text = fs::read_to_string(&overflow).unwrap();
+    for row in text.lines().skip(1).map(|l| l.split(',').collect::<Vec<&str>>()) {
+        assert!(row[10].parse::<usize>().is_ok(), "{:?}", row);
+        assert!(row[12].contains("is not finite at step"), "{:?}", row);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
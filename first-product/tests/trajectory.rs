@@ -0,0 +1,125 @@
+mod common;
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use common::{first_product, scratch};
+use first_product::output::{self, ColumnData, Kind, OutputFormat};
+
+/// Runs the default model into `dir/name` with the flags; returns stdout.
+fn run(dir: &Path, name: &str, flags: &[&str]) -> String {
+    let path = dir.join(name).display().to_string();
+    let mut args = vec!["--output", &path];
+    args.extend_from_slice(flags);
+    let output = first_product(&args);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+/// The `step` column of a binary trajectory.
+fn steps(path: &Path) -> Vec<u64> {
+    let (metadata, columns) = output::read_binary(path).unwrap();
+    assert_eq!(metadata.columns[0].name, "step");
+    match &columns[0] {
+        ColumnData::Integer(steps) => steps.clone(),
+        other => panic!("step column is {:?}", other),
+    }
+}
+
+#[test]
+fn formats_carry_the_same_rows() {
+    let dir = scratch("trajectory-formats");
+    run(&dir, "run.csv", &[]);
+    run(&dir, "run.jsonl", &["--format", "jsonl"]);
+    run(&dir, "run.bin", &["--format", "binary"]);
+
+    let csv = fs::read_to_string(dir.join("run.csv")).unwrap();
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap().split(',').collect();
+    let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+
+    let jsonl = fs::read_to_string(dir.join("run.jsonl")).unwrap();
+    assert_eq!(jsonl.lines().count(), rows.len());
+    let last = jsonl.lines().last().unwrap();
+    assert!(last.starts_with(&format!("{{\"step\":{},\"t\":", rows.len() - 1)), "{}", last);
+    assert!(last.contains("\"Era\":\"vacuum\""), "{}", last);
+
+    let (metadata, columns) = output::read_binary(&dir.join("run.bin")).unwrap();
+    assert_eq!(metadata.run.trajectory.format, OutputFormat::Binary);
+    assert_eq!(metadata.columns.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), header);
+    assert_eq!(metadata.columns[11].kind, Kind::Text);
+    for (k, column) in columns.iter().enumerate() {
+        for (row, csv_row) in rows.iter().enumerate() {
+            let cell = csv_row[k];
+            match column {
+                ColumnData::Float(v) => assert_eq!(v[row], cell.parse::<f64>().unwrap(), "{} at {}", header[k], row),
+                ColumnData::Integer(v) => assert_eq!(v[row].to_string(), cell),
+                ColumnData::Text(v) => assert_eq!(v[row], cell),
+            }
+        }
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn decimation_keeps_the_final_row() {
+    let dir = scratch("trajectory-decimation");
+    run(&dir, "full.bin", &["--format", "binary"]);
+    run(&dir, "every.bin", &["--format", "binary", "--every", "100"]);
+    run(&dir, "dn.bin", &["--format", "binary", "--min-dn", "5"]);
+    let full = steps(&dir.join("full.bin"));
+    let last = *full.last().unwrap();
+
+    let every = steps(&dir.join("every.bin"));
+    let (end, thinned) = every.split_last().unwrap();
+    assert_eq!(*end, last);
+    assert!(thinned.iter().enumerate().all(|(k, &step)| step == 100*k as u64), "{:?}", every);
+    assert_eq!(thinned.len() as u64, last.div_ceil(100));
+
+    let dn = steps(&dir.join("dn.bin"));
+    assert_eq!(dn.first(), Some(&0));
+    assert_eq!(dn.last(), Some(&last));
+    assert!(dn.len() > 5 && dn.len() < full.len()/10, "{} of {} rows", dn.len(), full.len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resumed_run_reproduces_the_uninterrupted_one() {
+    let dir = scratch("trajectory-resume");
+    for format in ["csv", "binary"] {
+        let full = format!("full.{}", format);
+        let stdout = run(&dir, &full, &["--format", format]);
+        let checkpoint = dir.join(format!("{}.ckpt", format)).display().to_string();
+        let flags = ["--format", format, "--every", "3", "--checkpoint", &checkpoint, "--checkpoint-every", "500"];
+        run(&dir, &format!("cut.{}", format), &flags);
+        let thinned = fs::read(dir.join(format!("cut.{}", format))).unwrap();
+
+        // An interrupted run leaves rows past its last checkpoint, and maybe half of one
+        let cut = dir.join(format!("cut.{}", format));
+        OpenOptions::new().append(true).open(&cut).unwrap().write_all(b"3.14,interrupted").unwrap();
+        let resumed = first_product(&["--resume", &checkpoint]);
+        assert!(resumed.status.success(), "{}", String::from_utf8_lossy(&resumed.stderr));
+        assert_eq!(fs::read(&cut).unwrap(), thinned, "{}", format);
+
+        // Same steps and summary as a run without decimation or checkpoints
+        let summary = |out: &str| out.lines().filter(|l| l.contains("accepted") || l.contains(':'))
+            .filter(|l| !l.contains("Data in")).map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(summary(&String::from_utf8_lossy(&resumed.stdout)), summary(&stdout));
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resume_rejects_other_flags_and_foreign_files() {
+    let dir = scratch("trajectory-reject");
+    let path = dir.join("run.csv");
+    run(&dir, "run.csv", &[]);
+    let with_flags = first_product(&["--resume", path.to_str().unwrap(), "--every", "2"]);
+    assert_eq!(with_flags.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&with_flags.stderr).contains("--resume takes the checkpoint file"));
+    let foreign = first_product(&["--resume", path.to_str().unwrap()]);
+    assert_eq!(foreign.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&foreign.stderr).contains("is not a first-product checkpoint"));
+    fs::remove_dir_all(&dir).unwrap();
+}
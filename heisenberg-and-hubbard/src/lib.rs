@@ -1,9 +1,13 @@
 // lib.rs
 
-use std::f64::consts::PI;
+use serde::Deserialize;
+
+pub mod scenario;
+pub mod statistics;
 
 /// Enum to represent the type of particle: Fermion or Boson.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ParticleType {
     Fermion,
     Boson,
@@ -57,7 +61,7 @@ impl Particle {
 }
 
 /// Struct to represent the system containing particles and observables.
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct System {
     pub particles: Vec<Particle>,
     pub total_energy: f64,       // in MeV
@@ -70,14 +74,7 @@ pub struct System {
 impl System {
     /// Creates a new system.
     pub fn new() -> Self {
-        System {
-            particles: Vec::new(),
-            total_energy: 0.0,
-            total_mass: 0.0,
-            dark_energy_percentage: 0.0,
-            dark_matter_percentage: 0.0,
-            atoms_percentage: 0.0,
-        }
+        System::default()
     }
 
     /// Adds a particle to the system.
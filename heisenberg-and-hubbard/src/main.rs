@@ -1,58 +1,31 @@
 // main.rs
 
-mod lib;
+use std::env;
+use std::process;
 
-use lib::{Particle, ParticleType, System};
+use heisenberg_and_hubbard::scenario::Scenario;
 
 fn main() {
-    let mut system = System::new();
-
-    // Create initial particles: electron and positron
-    let electron = Particle::new("Electron", ParticleType::Fermion, 0.511, -1.0, 0.5, 3);
-    let positron = Particle::new("Positron", ParticleType::Fermion, 0.511, 1.0, 0.5, 3);
-
-    // Add particles to the system
-    system.add_particle(electron);
-    system.add_particle(positron);
-
-    println!("Before annihilation:\n\n");
-    system.log_observables();
-
-    // Simulate the annihilation
-    system.simulate_annihilation();
-
-    println!("\nAfter annihilation:\n\n");
-    system.log_observables();
-}
-
-/*
-
-mod lib;
-
-use lib::{Particle, ParticleType, System};
-
-fn main() {
-    let mut system = System::new();
-
-    // Create initial particles: electron, positron, neutrino, and a proton
-    let electron = Particle::new("Electron", ParticleType::Fermion, 0.511, -1.0, 0.5, 3);
-    let positron = Particle::new("Positron", ParticleType::Fermion, 0.511, 1.0, 0.5, 3);
-    let neutrino = Particle::new("Neutrino", ParticleType::Fermion, 0.0, 0.0, 0.5, 1);
-    let proton = Particle::new("Proton", ParticleType::Fermion, 938.272, 1.0, 0.5, 3);
-
-    // Add particles to the system
-    system.add_particle(electron);
-    system.add_particle(positron);
-    system.add_particle(neutrino);
-    system.add_particle(proton);
-
-    println!("Before annihilation:");
-    system.log_observables();
-
-    // Simulate the annihilation
-    system.simulate_annihilation();
-
-    println!("\nAfter annihilation:");
-    system.log_observables();
+    // Usage: heisenberg-and-hubbard [scenario.toml|scenario.json]
+    // Without a file, the electron + positron annihilation is run.
+    let scenario = match env::args().nth(1) {
+        Some(path) => Scenario::from_file(&path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }),
+        None => Scenario::electron_positron(),
+    };
+
+    if !scenario.name.is_empty() {
+        println!("Scenario: {}\n", scenario.name);
+    }
+
+    scenario.run_with(|step, system| {
+        if step == 0 {
+            println!("Before reactions:\n\n");
+        } else {
+            println!("\nAfter step {}:\n\n", step);
+        }
+        scenario.report(step, system);
+    });
 }
-*/
\ No newline at end of file
@@ -0,0 +1,295 @@
+// scenario.rs
+
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::{Particle, ParticleType, System};
+
+/// Error raised while loading or validating a scenario description.
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    UnknownFormat(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScenarioError::Io(e) => write!(f, "could not read scenario: {}", e),
+            ScenarioError::Toml(e) => write!(f, "invalid TOML scenario: {}", e),
+            ScenarioError::Json(e) => write!(f, "invalid JSON scenario: {}", e),
+            ScenarioError::UnknownFormat(ext) => {
+                write!(f, "unknown scenario format '{}' (expected .toml or .json)", ext)
+            }
+            ScenarioError::Invalid(msg) => write!(f, "invalid scenario: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError {}
+
+impl From<std::io::Error> for ScenarioError {
+    fn from(e: std::io::Error) -> Self {
+        ScenarioError::Io(e)
+    }
+}
+
+impl From<toml::de::Error> for ScenarioError {
+    fn from(e: toml::de::Error) -> Self {
+        ScenarioError::Toml(e)
+    }
+}
+
+impl From<serde_json::Error> for ScenarioError {
+    fn from(e: serde_json::Error) -> Self {
+        ScenarioError::Json(e)
+    }
+}
+
+/// Initial particle entry of a scenario file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ParticleSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub particle_type: ParticleType,
+    pub mass: f64,               // in MeV/c^2
+    pub charge: f64,             // in elementary charge units
+    pub spin: f64,
+    pub point_split_density: u32,
+    #[serde(default = "default_count")]
+    pub count: usize,            // number of identical copies to add
+}
+
+fn default_count() -> usize {
+    1
+}
+
+impl ParticleSpec {
+    /// Builds the particle described by this entry.
+    pub fn to_particle(&self) -> Particle {
+        Particle::new(
+            &self.name,
+            self.particle_type.clone(),
+            self.mass,
+            self.charge,
+            self.spin,
+            self.point_split_density,
+        )
+    }
+}
+
+/// A reaction applied to the system, in the order listed in the scenario.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reaction {
+    /// `System::simulate_annihilation`
+    Annihilation,
+    /// `System::simulate_emergent_particles` with the given energy budget (MeV)
+    EmergentParticles { available_energy: f64 },
+    /// `System::update_percentages`
+    UpdatePercentages,
+}
+
+impl Reaction {
+    /// Applies the reaction to the system.
+    pub fn apply(&self, system: &mut System) {
+        match self {
+            Reaction::Annihilation => system.simulate_annihilation(),
+            Reaction::EmergentParticles { available_energy } => {
+                system.simulate_emergent_particles(*available_energy)
+            }
+            Reaction::UpdatePercentages => system.update_percentages(),
+        }
+    }
+}
+
+/// Output produced before the first step and after every step.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    /// Full `System::log_observables` dump
+    Observables,
+    /// One line with the conserved totals
+    Totals,
+    /// Particle names only
+    Particles,
+}
+
+/// Quantities the reactions are expected to conserve (or not).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Totals {
+    pub mass: f64,               // Σ rest mass tracked by the system, MeV/c^2
+    pub energy: f64,             // energy released, MeV
+    pub charge: f64,             // net charge of the particles present
+    pub particle_count: usize,
+}
+
+impl Totals {
+    /// Computes the totals of a system.
+    pub fn of(system: &System) -> Self {
+        Totals {
+            mass: system.total_mass,
+            energy: system.total_energy,
+            charge: system.particles.iter().map(|p| p.charge).sum(),
+            particle_count: system.particles.len(),
+        }
+    }
+
+    /// Mass plus released energy, conserved by every reaction (c = 1).
+    pub fn mass_energy(&self) -> f64 {
+        self.mass + self.energy
+    }
+}
+
+/// A scenario: initial particles, reactions applied in order, number of steps and outputs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: String,
+    pub particles: Vec<ParticleSpec>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+    #[serde(default = "default_steps")]
+    pub steps: usize,
+    #[serde(default = "default_outputs")]
+    pub outputs: Vec<Output>,
+}
+
+fn default_steps() -> usize {
+    1
+}
+
+fn default_outputs() -> Vec<Output> {
+    vec![Output::Observables]
+}
+
+impl Scenario {
+    /// Loads a scenario, picking the format from the file extension.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        match ext {
+            "toml" => Self::from_toml(&text),
+            "json" => Self::from_json(&text),
+            other => Err(ScenarioError::UnknownFormat(other.to_string())),
+        }
+    }
+
+    /// Parses a TOML scenario.
+    pub fn from_toml(text: &str) -> Result<Self, ScenarioError> {
+        let scenario: Scenario = toml::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Parses a JSON scenario.
+    pub fn from_json(text: &str) -> Result<Self, ScenarioError> {
+        let scenario: Scenario = serde_json::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    /// Electron + positron annihilation, the scenario `main` used to hard-code.
+    pub fn electron_positron() -> Self {
+        let lepton = |name: &str, charge: f64| ParticleSpec {
+            name: name.to_string(),
+            particle_type: ParticleType::Fermion,
+            mass: 0.511,
+            charge,
+            spin: 0.5,
+            point_split_density: 3,
+            count: 1,
+        };
+        Scenario {
+            name: "Electron-positron annihilation".to_string(),
+            particles: vec![lepton("Electron", -1.0), lepton("Positron", 1.0)],
+            reactions: vec![Reaction::Annihilation],
+            steps: 1,
+            outputs: default_outputs(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), ScenarioError> {
+        for p in &self.particles {
+            if !(p.mass >= 0.0 && p.mass.is_finite()) {
+                return Err(ScenarioError::Invalid(format!(
+                    "particle '{}' has mass {}, expected a finite value >= 0",
+                    p.name, p.mass
+                )));
+            }
+        }
+        for r in &self.reactions {
+            if let Reaction::EmergentParticles { available_energy } = r {
+                if !(*available_energy >= 0.0 && available_energy.is_finite()) {
+                    return Err(ScenarioError::Invalid(format!(
+                        "emergent_particles energy {} must be a finite value >= 0",
+                        available_energy
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the initial system.
+    pub fn build_system(&self) -> System {
+        let mut system = System::new();
+        for spec in &self.particles {
+            for _ in 0..spec.count {
+                system.add_particle(spec.to_particle());
+            }
+        }
+        system
+    }
+
+    /// Applies every reaction once, in order.
+    pub fn step(&self, system: &mut System) {
+        for reaction in &self.reactions {
+            reaction.apply(system);
+        }
+    }
+
+    /// Runs all steps, calling `observe` before the first step (with step 0)
+    /// and after each step (with steps 1..=steps).
+    pub fn run_with<F: FnMut(usize, &System)>(&self, mut observe: F) -> System {
+        let mut system = self.build_system();
+        observe(0, &system);
+        for step in 1..=self.steps {
+            self.step(&mut system);
+            observe(step, &system);
+        }
+        system
+    }
+
+    /// Runs all steps and returns the final system.
+    pub fn run(&self) -> System {
+        self.run_with(|_, _| {})
+    }
+
+    /// Prints the requested outputs for one step.
+    pub fn report(&self, step: usize, system: &System) {
+        for output in &self.outputs {
+            match output {
+                Output::Observables => system.log_observables(),
+                Output::Totals => {
+                    let t = Totals::of(system);
+                    println!(
+                        "step {}: mass {:.3} MeV/c^2, energy {:.3} MeV, mass+energy {:.3} MeV, charge {:+.1}, particles {}",
+                        step, t.mass, t.energy, t.mass_energy(), t.charge, t.particle_count
+                    );
+                }
+                Output::Particles => {
+                    let names: Vec<&str> = system.particles.iter().map(|p| p.name.as_str()).collect();
+                    println!("step {}: [{}]", step, names.join(", "));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,386 @@
+// statistics.rs
+//
+// Quantum statistics for the particles of a `System`.
+// Units: ħ = c = k_B = 1, energies and temperatures in MeV,
+// number densities in MeV^3, energy densities and pressures in MeV^4.
+
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::{Particle, ParticleType, System};
+
+/// Riemann ζ(3), for the massless number densities.
+pub const ZETA_3: f64 = 1.202_056_903_159_594_2;
+
+/// Number of Simpson intervals used for the momentum integrals.
+const MOMENTUM_INTERVALS: usize = 4000;
+/// Integrate up to (E - μ)/T = this, beyond which f < e^-60.
+const BOLTZMANN_CUTOFF: f64 = 60.0;
+
+/// Error raised by the statistical-mechanics routines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatisticsError {
+    /// T must be strictly positive and finite.
+    InvalidTemperature(f64),
+    /// A boson with μ ≥ m would have negative occupation (condensate not modelled).
+    BoseCondensation { name: String, mu: f64, mass: f64 },
+    /// Pauli exclusion: the level already holds as many fermions as it has states.
+    PauliExclusion { name: String, level: usize, capacity: usize },
+    /// Level index outside the level scheme.
+    NoSuchLevel(usize),
+    /// Levels must be given in order of energy; `level` lies below the one before it.
+    UnsortedLevels { level: usize },
+}
+
+impl fmt::Display for StatisticsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatisticsError::InvalidTemperature(t) => write!(f, "temperature {} MeV must be > 0", t),
+            StatisticsError::BoseCondensation { name, mu, mass } => write!(
+                f,
+                "{}: chemical potential {} MeV reaches the mass {} MeV (Bose condensation)",
+                name, mu, mass
+            ),
+            StatisticsError::PauliExclusion { name, level, capacity } => write!(
+                f,
+                "{}: level {} already holds {} fermions (Pauli exclusion)",
+                name, level, capacity
+            ),
+            StatisticsError::NoSuchLevel(level) => write!(f, "no single-particle level {}", level),
+            StatisticsError::UnsortedLevels { level } => {
+                write!(f, "level {} is out of energy order", level)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatisticsError {}
+
+impl ParticleType {
+    /// +1 for Fermi-Dirac, -1 for Bose-Einstein (the sign in 1/(e^x ± 1)).
+    pub fn statistics_sign(&self) -> f64 {
+        match self {
+            ParticleType::Fermion => 1.0,
+            ParticleType::Boson => -1.0,
+        }
+    }
+}
+
+impl Particle {
+    /// Internal degrees of freedom: 2s+1, or the 2 helicities of a massless spinning particle.
+    pub fn degeneracy(&self) -> f64 {
+        if self.mass == 0.0 && self.spin > 0.0 {
+            2.0
+        } else {
+            2.0 * self.spin + 1.0
+        }
+    }
+}
+
+/// Mean occupation of a single-particle state of energy `energy`:
+/// f = 1/(e^{(E-μ)/T} + 1) for fermions, 1/(e^{(E-μ)/T} - 1) for bosons.
+pub fn occupation(particle_type: &ParticleType, energy: f64, mu: f64, temperature: f64) -> f64 {
+    let x = (energy - mu) / temperature;
+    if x > 700.0 {
+        return 0.0;
+    }
+    1.0 / (x.exp() + particle_type.statistics_sign())
+}
+
+/// Thermodynamic densities of one species.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Densities {
+    pub number: f64,   // n, MeV^3
+    pub energy: f64,   // ρ, MeV^4
+    pub pressure: f64, // P, MeV^4
+}
+
+impl Densities {
+    /// Equation of state w = P/ρ.
+    pub fn equation_of_state(&self) -> f64 {
+        self.pressure / self.energy
+    }
+}
+
+impl std::ops::Add for Densities {
+    type Output = Densities;
+
+    fn add(self, other: Densities) -> Densities {
+        Densities {
+            number: self.number + other.number,
+            energy: self.energy + other.energy,
+            pressure: self.pressure + other.pressure,
+        }
+    }
+}
+
+/// Relativistic densities of an ideal quantum gas with g internal states:
+/// n = g/(2π²) ∫ p² f dp, ρ = g/(2π²) ∫ p² E f dp, P = g/(6π²) ∫ p⁴/E f dp.
+pub fn densities(
+    particle_type: &ParticleType,
+    degeneracy: f64,
+    mass: f64,
+    mu: f64,
+    temperature: f64,
+) -> Densities {
+    // Momentum range where the occupation is not yet negligible.
+    let e_max = mass.max(mu) + BOLTZMANN_CUTOFF * temperature;
+    let p_max = (e_max * e_max - mass * mass).max(0.0).sqrt();
+    let h = p_max / MOMENTUM_INTERVALS as f64;
+
+    let mut sums = [0.0; 3];
+    for i in 0..=MOMENTUM_INTERVALS {
+        let weight = if i == 0 || i == MOMENTUM_INTERVALS {
+            1.0
+        } else if i % 2 == 1 {
+            4.0
+        } else {
+            2.0
+        };
+        let p = i as f64 * h;
+        if p == 0.0 {
+            continue; // every integrand vanishes at p = 0
+        }
+        let e = (p * p + mass * mass).sqrt();
+        let f = occupation(particle_type, e, mu, temperature);
+        sums[0] += weight * p * p * f;
+        sums[1] += weight * p * p * e * f;
+        sums[2] += weight * p.powi(4) / e * f;
+    }
+
+    let norm = degeneracy / (2.0 * PI * PI) * h / 3.0;
+    Densities {
+        number: norm * sums[0],
+        energy: norm * sums[1],
+        pressure: norm * sums[2] / 3.0,
+    }
+}
+
+/// Massless (m = 0, μ = 0) limit: n = g ζ(3)/π² T³, ρ = g π²/30 T⁴, P = ρ/3,
+/// with the 3/4 and 7/8 factors for fermions.
+pub fn massless_densities(particle_type: &ParticleType, degeneracy: f64, temperature: f64) -> Densities {
+    let (number_factor, energy_factor) = match particle_type {
+        ParticleType::Boson => (1.0, 1.0),
+        ParticleType::Fermion => (0.75, 0.875),
+    };
+    let energy = energy_factor * degeneracy * PI * PI / 30.0 * temperature.powi(4);
+    Densities {
+        number: number_factor * degeneracy * ZETA_3 / (PI * PI) * temperature.powi(3),
+        energy,
+        pressure: energy / 3.0,
+    }
+}
+
+/// Non-relativistic (T ≪ m - μ) limit, identical for both statistics:
+/// n = g (mT/2π)^{3/2} e^{-(m-μ)/T}, ρ = n (m + 3T/2), P = nT.
+pub fn nonrelativistic_densities(degeneracy: f64, mass: f64, mu: f64, temperature: f64) -> Densities {
+    let number = degeneracy
+        * (mass * temperature / (2.0 * PI)).powf(1.5)
+        * (-(mass - mu) / temperature).exp();
+    Densities {
+        number,
+        energy: number * (mass + 1.5 * temperature),
+        pressure: number * temperature,
+    }
+}
+
+/// Densities of one species of a system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciesState {
+    pub name: String,
+    pub particle_type: ParticleType,
+    pub degeneracy: f64,
+    pub mass: f64,
+    pub mu: f64,
+    pub densities: Densities,
+}
+
+/// Grand-canonical ensemble at temperature T with a chemical potential per species name
+/// (species without an entry have μ = 0).
+#[derive(Debug, Clone)]
+pub struct Ensemble {
+    pub temperature: f64,                        // MeV
+    pub chemical_potentials: HashMap<String, f64>, // MeV
+}
+
+impl Ensemble {
+    /// Creates an ensemble with all chemical potentials zero.
+    pub fn new(temperature: f64) -> Self {
+        Ensemble {
+            temperature,
+            chemical_potentials: HashMap::new(),
+        }
+    }
+
+    /// Sets the chemical potential of a species.
+    pub fn with_chemical_potential(mut self, name: &str, mu: f64) -> Self {
+        self.chemical_potentials.insert(name.to_string(), mu);
+        self
+    }
+
+    /// Chemical potential of a species.
+    pub fn mu(&self, name: &str) -> f64 {
+        self.chemical_potentials.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Densities of one particle species in this ensemble.
+    pub fn species(&self, particle: &Particle) -> Result<SpeciesState, StatisticsError> {
+        if !(self.temperature > 0.0 && self.temperature.is_finite()) {
+            return Err(StatisticsError::InvalidTemperature(self.temperature));
+        }
+        let mu = self.mu(&particle.name);
+        // μ = m = 0 (photons, gravitons) is the usual massless gas.
+        let condenses = mu > particle.mass || (mu == particle.mass && particle.mass > 0.0);
+        if particle.particle_type == ParticleType::Boson && condenses {
+            return Err(StatisticsError::BoseCondensation {
+                name: particle.name.clone(),
+                mu,
+                mass: particle.mass,
+            });
+        }
+        let degeneracy = particle.degeneracy();
+        Ok(SpeciesState {
+            name: particle.name.clone(),
+            particle_type: particle.particle_type.clone(),
+            degeneracy,
+            mass: particle.mass,
+            mu,
+            densities: densities(&particle.particle_type, degeneracy, particle.mass, mu, self.temperature),
+        })
+    }
+
+    /// Densities of every distinct species (by name) present in the system.
+    pub fn species_states(&self, system: &System) -> Result<Vec<SpeciesState>, StatisticsError> {
+        let mut seen: Vec<&str> = Vec::new();
+        let mut states = Vec::new();
+        for particle in &system.particles {
+            if seen.contains(&particle.name.as_str()) {
+                continue;
+            }
+            seen.push(&particle.name);
+            states.push(self.species(particle)?);
+        }
+        Ok(states)
+    }
+
+    /// Summed densities of all species of the system.
+    pub fn total(&self, system: &System) -> Result<Densities, StatisticsError> {
+        Ok(self
+            .species_states(system)?
+            .iter()
+            .fold(Densities::default(), |sum, s| sum + s.densities))
+    }
+}
+
+/// A discrete single-particle level with its orbital degeneracy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub energy: f64,     // MeV
+    pub degeneracy: usize, // orbital states, spin states are added per particle
+}
+
+/// Particles placed into discrete single-particle levels, enforcing Pauli exclusion for fermions.
+#[derive(Debug, Clone)]
+pub struct LevelScheme {
+    levels: Vec<Level>,
+    occupancy: HashMap<(String, usize), usize>,
+}
+
+impl LevelScheme {
+    /// Creates an empty scheme. Levels are indexed as given, so they must already be
+    /// ordered by energy (equal energies are allowed).
+    pub fn new(levels: Vec<Level>) -> Result<Self, StatisticsError> {
+        if let Some(level) = levels.iter().position(|l| l.energy.is_nan()) {
+            return Err(StatisticsError::UnsortedLevels { level });
+        }
+        if let Some(i) = levels.windows(2).position(|pair| pair[1].energy < pair[0].energy) {
+            return Err(StatisticsError::UnsortedLevels { level: i + 1 });
+        }
+        Ok(LevelScheme {
+            levels,
+            occupancy: HashMap::new(),
+        })
+    }
+
+    /// The levels, lowest first.
+    pub fn levels(&self) -> &[Level] {
+        &self.levels
+    }
+
+    /// How many particles of this species fit in a level (`usize::MAX` for bosons).
+    pub fn capacity(&self, particle: &Particle, level: usize) -> Result<usize, StatisticsError> {
+        let level = self.levels.get(level).ok_or(StatisticsError::NoSuchLevel(level))?;
+        Ok(match particle.particle_type {
+            ParticleType::Boson => usize::MAX,
+            ParticleType::Fermion => level.degeneracy * particle.degeneracy() as usize,
+        })
+    }
+
+    /// Number of particles of a species in a level.
+    pub fn occupancy(&self, name: &str, level: usize) -> usize {
+        self.occupancy.get(&(name.to_string(), level)).copied().unwrap_or(0)
+    }
+
+    /// Places one particle into a level.
+    pub fn place(&mut self, particle: &Particle, level: usize) -> Result<(), StatisticsError> {
+        let capacity = self.capacity(particle, level)?;
+        let count = self.occupancy.entry((particle.name.clone(), level)).or_insert(0);
+        if *count >= capacity {
+            return Err(StatisticsError::PauliExclusion {
+                name: particle.name.clone(),
+                level,
+                capacity,
+            });
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Places each particle into the lowest level that still accepts it (T = 0 ground state).
+    pub fn fill_ground_state(&mut self, particles: &[Particle]) -> Result<(), StatisticsError> {
+        for particle in particles {
+            let mut free = None;
+            for level in 0..self.levels.len() {
+                if self.occupancy(&particle.name, level) < self.capacity(particle, level)? {
+                    free = Some(level);
+                    break;
+                }
+            }
+            match free {
+                Some(level) => self.place(particle, level)?,
+                None => {
+                    // With no levels at all there is no level 0 to put the particle in
+                    let last = self.levels.len().checked_sub(1).ok_or(StatisticsError::NoSuchLevel(0))?;
+                    return Err(StatisticsError::PauliExclusion {
+                        name: particle.name.clone(),
+                        level: last,
+                        capacity: self.capacity(particle, last)?,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Total energy of the placed particles.
+    pub fn energy(&self) -> f64 {
+        self.occupancy
+            .iter()
+            .map(|((_, level), count)| self.levels[*level].energy * *count as f64)
+            .sum()
+    }
+
+    /// Thermal mean occupation of every level for one species (per level, all states).
+    pub fn thermal_occupation(&self, particle: &Particle, mu: f64, temperature: f64) -> Vec<f64> {
+        let spin_states = particle.degeneracy();
+        self.levels
+            .iter()
+            .map(|level| {
+                level.degeneracy as f64
+                    * spin_states
+                    * occupation(&particle.particle_type, level.energy, mu, temperature)
+            })
+            .collect()
+    }
+}
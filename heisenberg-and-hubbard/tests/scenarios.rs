@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+
+use heisenberg_and_hubbard::scenario::{Reaction, Scenario, ScenarioError, Totals};
+
+fn scenario_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("scenarios").join(name)
+}
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures").join(name)
+}
+
+/// Runs a shipped scenario and checks mass + released energy at every step.
+fn run_conserving(name: &str) -> (Totals, Totals) {
+    let scenario = Scenario::from_file(scenario_path(name)).unwrap();
+    let mut initial = None;
+    let system = scenario.run_with(|_, system| {
+        let totals = Totals::of(system);
+        let start = *initial.get_or_insert(totals);
+        assert!(
+            (totals.mass_energy() - start.mass_energy()).abs() < 1e-9,
+            "{}: mass+energy drifted from {} to {}",
+            name,
+            start.mass_energy(),
+            totals.mass_energy()
+        );
+    });
+    (initial.unwrap(), Totals::of(&system))
+}
+
+#[test]
+fn annihilation_conserves_mass_energy() {
+    let (initial, last) = run_conserving("annihilation.toml");
+    assert!((initial.mass - 1.022).abs() < 1e-9);
+    assert_eq!(initial.charge, 0.0);
+    // e+ e- -> graviton + photon, then electron + neutrino emerge from 1.022 MeV
+    assert_eq!(last.particle_count, 4);
+    assert!((last.mass - 0.511).abs() < 1e-9);
+    assert!((last.energy - 0.511).abs() < 1e-9);
+}
+
+#[test]
+fn spectators_are_untouched() {
+    let (initial, last) = run_conserving("proton_spectator.toml");
+    assert!((initial.mass_energy() - (1.022 + 938.272)).abs() < 1e-9);
+    assert!((last.mass - (938.272 + 0.511)).abs() < 1e-9);
+    assert_eq!(last.particle_count, 6);
+}
+
+#[test]
+fn pair_cascade_runs_every_step() {
+    let scenario = Scenario::from_file(scenario_path("pair_cascade.json")).unwrap();
+    assert_eq!(scenario.steps, 3);
+    assert_eq!(scenario.reactions[0], Reaction::Annihilation);
+
+    let (initial, last) = run_conserving("pair_cascade.json");
+    assert_eq!(initial.particle_count, 6);
+    // Each step: -2 leptons, +graviton +photon +electron +neutrino
+    assert_eq!(last.particle_count, 12);
+    assert!((last.energy - 3.0 * 0.511).abs() < 1e-9);
+}
+
+#[test]
+fn default_scenario_matches_shipped_file() {
+    let shipped = Scenario::from_file(scenario_path("annihilation.toml")).unwrap().run();
+    let builtin = Scenario::electron_positron().run();
+    assert_eq!(Totals::of(&shipped), Totals::of(&builtin));
+}
+
+#[test]
+fn rejects_unknown_format_and_bad_values() {
+    let err = Scenario::from_file(fixture_path("bad.yaml")).unwrap_err();
+    assert!(matches!(err, ScenarioError::UnknownFormat(ref ext) if ext == "yaml"));
+
+    let err = Scenario::from_toml(
+        r#"
+        [[particles]]
+        name = "Tachyon"
+        type = "boson"
+        mass = -1.0
+        charge = 0.0
+        spin = 0.0
+        point_split_density = 1
+        "#,
+    )
+    .unwrap_err();
+    assert!(matches!(err, ScenarioError::Invalid(_)));
+}
@@ -0,0 +1,137 @@
+use heisenberg_and_hubbard::statistics::{
+    densities, massless_densities, nonrelativistic_densities, occupation, Ensemble, Level,
+    LevelScheme, StatisticsError,
+};
+use heisenberg_and_hubbard::{Particle, ParticleType, System};
+
+fn relative(a: f64, b: f64) -> f64 {
+    ((a - b) / b).abs()
+}
+
+fn electron() -> Particle {
+    Particle::new("Electron", ParticleType::Fermion, 0.511, -1.0, 0.5, 3)
+}
+
+fn photon() -> Particle {
+    Particle::new("Photon", ParticleType::Boson, 0.0, 0.0, 1.0, 1)
+}
+
+#[test]
+fn occupation_numbers() {
+    // At E = μ a fermion state is half filled
+    assert!((occupation(&ParticleType::Fermion, 1.0, 1.0, 0.1) - 0.5).abs() < 1e-12);
+    // Bosons are always more populated than fermions at the same energy
+    let fd = occupation(&ParticleType::Fermion, 0.3, 0.0, 0.1);
+    let be = occupation(&ParticleType::Boson, 0.3, 0.0, 0.1);
+    assert!(be > fd);
+    // Fermi-Dirac never exceeds one
+    assert!(occupation(&ParticleType::Fermion, -10.0, 0.0, 0.1) <= 1.0);
+}
+
+#[test]
+fn massless_limit_matches_closed_form() {
+    for particle_type in [ParticleType::Boson, ParticleType::Fermion] {
+        let numeric = densities(&particle_type, 2.0, 0.0, 0.0, 1.0);
+        let exact = massless_densities(&particle_type, 2.0, 1.0);
+        assert!(relative(numeric.number, exact.number) < 1e-4, "{:?}", particle_type);
+        assert!(relative(numeric.energy, exact.energy) < 1e-6, "{:?}", particle_type);
+        assert!((numeric.equation_of_state() - 1.0 / 3.0).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn nonrelativistic_limit_matches_boltzmann() {
+    // T = m/2000: both statistics reduce to Maxwell-Boltzmann,
+    // up to the 15T/8m relativistic correction
+    // (μ = m - 20T keeps the densities representable)
+    let t = 938.272 / 2000.0;
+    let mu = 938.272 - 20.0 * t;
+    for particle_type in [ParticleType::Boson, ParticleType::Fermion] {
+        let numeric = densities(&particle_type, 2.0, 938.272, mu, t);
+        let exact = nonrelativistic_densities(2.0, 938.272, mu, t);
+        assert!(relative(numeric.number, exact.number) < 2e-3);
+        assert!(relative(numeric.pressure, exact.pressure) < 2e-3);
+        assert!(numeric.equation_of_state() < 0.05);
+    }
+}
+
+#[test]
+fn ensemble_over_system_species() {
+    let mut system = System::new();
+    system.add_particle(electron());
+    system.add_particle(electron());
+    system.add_particle(photon());
+
+    let ensemble = Ensemble::new(100.0).with_chemical_potential("Electron", 0.0);
+    let states = ensemble.species_states(&system).unwrap();
+    assert_eq!(states.len(), 2);
+
+    // Ultra-relativistic electrons: 7/8 of a boson gas with the same g
+    let e = &states[0].densities;
+    let expected = massless_densities(&ParticleType::Fermion, 2.0, 100.0);
+    assert!(relative(e.energy, expected.energy) < 1e-3);
+
+    let total = ensemble.total(&system).unwrap();
+    assert!((total.energy - states[0].densities.energy - states[1].densities.energy).abs() < 1e-6 * total.energy);
+}
+
+#[test]
+fn rejects_bose_condensation_and_bad_temperature() {
+    let mut system = System::new();
+    system.add_particle(photon());
+    let err = Ensemble::new(1.0).with_chemical_potential("Photon", 0.1).total(&system).unwrap_err();
+    assert!(matches!(err, StatisticsError::BoseCondensation { .. }));
+
+    let err = Ensemble::new(0.0).total(&system).unwrap_err();
+    assert_eq!(err, StatisticsError::InvalidTemperature(0.0));
+}
+
+#[test]
+fn pauli_exclusion_in_discrete_levels() {
+    let mut scheme = LevelScheme::new(vec![
+        Level { energy: 0.0, degeneracy: 1 },
+        Level { energy: 1.0, degeneracy: 1 },
+    ])
+    .unwrap();
+    let e = electron();
+    scheme.place(&e, 0).unwrap();
+    scheme.place(&e, 0).unwrap();
+    let err = scheme.place(&e, 0).unwrap_err();
+    assert!(matches!(err, StatisticsError::PauliExclusion { level: 0, capacity: 2, .. }));
+
+    // Bosons all condense into the ground state
+    let mut scheme = LevelScheme::new(vec![Level { energy: 0.0, degeneracy: 1 }]).unwrap();
+    scheme.fill_ground_state(&vec![photon(); 10]).unwrap();
+    assert_eq!(scheme.occupancy("Photon", 0), 10);
+
+    // Four electrons fill both levels, a fifth does not fit
+    let mut scheme = LevelScheme::new(vec![
+        Level { energy: 0.0, degeneracy: 1 },
+        Level { energy: 1.0, degeneracy: 1 },
+    ])
+    .unwrap();
+    scheme.fill_ground_state(&vec![e.clone(); 4]).unwrap();
+    assert!((scheme.energy() - 2.0).abs() < 1e-12);
+    assert!(scheme.fill_ground_state(&[e]).is_err());
+}
+
+#[test]
+fn level_indices_are_checked_not_reordered() {
+    // Sorting would silently renumber the caller's levels, so unsorted input is refused
+    let err = LevelScheme::new(vec![
+        Level { energy: 1.0, degeneracy: 1 },
+        Level { energy: 0.0, degeneracy: 1 },
+    ])
+    .unwrap_err();
+    assert_eq!(err, StatisticsError::UnsortedLevels { level: 1 });
+    assert!(LevelScheme::new(vec![Level { energy: f64::NAN, degeneracy: 1 }]).is_err());
+
+    let e = electron();
+    let scheme = LevelScheme::new(vec![Level { energy: 0.0, degeneracy: 1 }]).unwrap();
+    assert_eq!(scheme.capacity(&e, 0), Ok(2));
+    assert_eq!(scheme.capacity(&e, 1), Err(StatisticsError::NoSuchLevel(1)));
+
+    let mut empty = LevelScheme::new(Vec::new()).unwrap();
+    assert_eq!(empty.fill_ground_state(&[e]), Err(StatisticsError::NoSuchLevel(0)));
+    assert!(empty.fill_ground_state(&[]).is_ok());
+}